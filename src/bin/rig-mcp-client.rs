@@ -0,0 +1,114 @@
+//! rig智能体客户端：连接正在运行的MCP服务器，把服务器上的工具交给rig智能体使用。
+//!
+//! 使用 `--prompt` 执行单条提示词，不带 `--prompt` 时进入交互式REPL。
+//! 需要通过 `OPENAI_API_KEY` 环境变量提供OpenAI凭据。
+
+use std::io::{self, BufRead, Write};
+
+use anyhow::Result;
+use clap::Parser;
+use mcp_core::client::{Client, ClientBuilder};
+use mcp_core::transport::ClientSseTransport;
+use mcp_core::types::{ClientCapabilities, Implementation};
+use rig::agent::Agent;
+use rig::completion::{Chat, CompletionModel, Message, Prompt};
+use rig::providers;
+
+#[derive(Debug, Parser)]
+#[command(name = "rig-mcp-client", version, about = "使用rig智能体调用MCP服务器上的工具")]
+struct Args {
+    /// MCP服务器的SSE地址
+    #[arg(long, default_value = "http://127.0.0.1:1116/sse")]
+    url: String,
+
+    /// OpenAI模型名称
+    #[arg(long, default_value = "gpt-3.5-turbo-0125")]
+    model: String,
+
+    /// 执行一条提示词后退出；不指定时进入交互模式
+    #[arg(long)]
+    prompt: Option<String>,
+}
+
+// 连接MCP服务器并完成初始化握手
+async fn connect(url: &str) -> Result<Client<ClientSseTransport>> {
+    let transport = ClientSseTransport::builder(url.to_string()).build();
+    let client = ClientBuilder::new(transport).build();
+    client.open().await?;
+    let init_res = client
+        .initialize(
+            Implementation {
+                name: "rig-mcp-client".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+            },
+            ClientCapabilities::default(),
+        )
+        .await?;
+    tracing::info!("已连接到服务器: {:?}", init_res.server_info);
+    Ok(client)
+}
+
+// 交互模式：逐行读取提示词，保留对话历史，输入exit或quit退出
+async fn repl<M: CompletionModel>(agent: &Agent<M>) -> Result<()> {
+    let stdin = io::stdin();
+    let mut history: Vec<Message> = Vec::new();
+
+    loop {
+        print!("> ");
+        io::stdout().flush()?;
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            break;
+        }
+        let prompt = line.trim();
+        if prompt.is_empty() {
+            continue;
+        }
+        if prompt == "exit" || prompt == "quit" {
+            break;
+        }
+
+        match agent.chat(prompt, history.clone()).await {
+            Ok(response) => {
+                println!("{}", response);
+                history.push(Message::user(prompt));
+                history.push(Message::assistant(response));
+            }
+            Err(e) => eprintln!("请求失败: {}", e),
+        }
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt().with_writer(io::stderr).init();
+    let args = Args::parse();
+
+    let mcp_client = connect(&args.url).await?;
+    let tools = mcp_client.list_tools(None, None).await?.tools;
+    tracing::info!(
+        "可用工具: {:?}",
+        tools.iter().map(|tool| tool.name.as_str()).collect::<Vec<_>>()
+    );
+
+    tracing::info!("Building RIG agent");
+    let completion_model = providers::openai::Client::from_env();
+    let agent = tools
+        .into_iter()
+        .fold(completion_model.agent(&args.model), |builder, tool| {
+            builder.mcp_tool(tool, mcp_client.clone())
+        })
+        .build();
+
+    match args.prompt {
+        Some(prompt) => {
+            tracing::info!("Prompting RIG agent");
+            let response = agent.prompt(prompt.as_str()).await?;
+            println!("{}", response);
+        }
+        None => repl(&agent).await?,
+    }
+    Ok(())
+}
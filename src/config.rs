@@ -0,0 +1,481 @@
+//! 服务器配置：默认值 < TOML配置文件 < 环境变量 < 命令行参数，逐层覆盖。
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::registry::ToolGroups;
+
+/// 未指定配置文件时，在当前目录查找的默认文件名
+pub const DEFAULT_CONFIG_FILE: &str = "wei-server-mcp.toml";
+
+/// 环境变量前缀，例如 `WEI_MCP_PORT`
+pub const ENV_PREFIX: &str = "WEI_MCP_";
+
+/// 服务器使用的传输方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransportMode {
+    /// 通过标准输入输出交换JSON-RPC消息，供以子进程方式启动服务器的宿主使用
+    Stdio,
+    /// 在本地端口上提供SSE服务
+    Sse,
+}
+
+impl fmt::Display for TransportMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransportMode::Stdio => f.write_str("stdio"),
+            TransportMode::Sse => f.write_str("sse"),
+        }
+    }
+}
+
+impl FromStr for TransportMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "stdio" => Ok(TransportMode::Stdio),
+            "sse" => Ok(TransportMode::Sse),
+            other => Err(anyhow::anyhow!(
+                "未知的传输方式: {}，可选值：stdio或sse",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub tools: ToolsConfig,
+    pub wei_run: WeiRunConfig,
+    pub scheduler: SchedulerConfig,
+    pub jobs: JobsConfig,
+    pub suppliers: SuppliersConfig,
+    pub gpu_specs: GpuSpecsConfig,
+    pub prompts: PromptsConfig,
+    pub log: LogConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// 在initialize响应中报告的服务器名称
+    pub name: String,
+    /// 在initialize响应中报告的服务器版本
+    pub version: String,
+    pub transport: TransportMode,
+    /// SSE模式监听的地址
+    pub host: String,
+    /// 起始端口，被占用时依次向上查找
+    pub port: u16,
+    /// 查找可用端口的上限（含）
+    pub max_port: u16,
+    /// 记录实际使用端口的状态文件
+    pub state_file: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ToolsConfig {
+    /// 启用的工具分组，默认全部启用
+    pub enabled: ToolGroups,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WeiRunConfig {
+    /// wei-run可执行文件路径
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// 单次wei-run调用的超时时间（秒），超时后终止子进程
+    pub timeout_secs: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SchedulerConfig {
+    /// 同时运行的GPU任务数量上限
+    pub max_concurrent_jobs: usize,
+    /// 排队等待的任务数量上限，超过时新任务直接返回busy错误
+    pub max_queue_depth: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JobsConfig {
+    /// 后台任务结束后保留状态和结果的时间（秒）
+    pub ttl_secs: u64,
+    /// 后台任务的最长运行时间（秒），超过时取消任务并记为超时
+    pub max_runtime_secs: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SuppliersConfig {
+    /// 供应商SQLite数据库文件，不存在时自动创建
+    pub db_path: PathBuf,
+    /// ExportSuppliersCsv写文件的目录，导出路径只能位于此目录内
+    pub export_dir: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct GpuSpecsConfig {
+    /// 用户的GPU规格文件（TOML或JSON），其中的型号覆盖或补充内置目录
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub catalog: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PromptsConfig {
+    /// 提示词模板目录，每个.toml文件一个模板；目录不存在时不提供prompts能力
+    pub dir: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    /// 日志级别：trace、debug、info、warn或error
+    pub level: String,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            name: "wei-server-mcp".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            transport: TransportMode::Sse,
+            host: "127.0.0.1".to_string(),
+            port: 1116,
+            max_port: 65535,
+            state_file: PathBuf::from("wei-server-mcp.dat"),
+        }
+    }
+}
+
+impl Default for WeiRunConfig {
+    fn default() -> Self {
+        WeiRunConfig {
+            path: None,
+            timeout_secs: 300,
+        }
+    }
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        SchedulerConfig {
+            max_concurrent_jobs: 1,
+            max_queue_depth: 16,
+        }
+    }
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        JobsConfig {
+            ttl_secs: 3600,
+            max_runtime_secs: 3600,
+        }
+    }
+}
+
+impl Default for SuppliersConfig {
+    fn default() -> Self {
+        SuppliersConfig {
+            db_path: PathBuf::from("suppliers.db"),
+            export_dir: PathBuf::from("exports"),
+        }
+    }
+}
+
+impl Default for PromptsConfig {
+    fn default() -> Self {
+        PromptsConfig {
+            dir: PathBuf::from("prompts"),
+        }
+    }
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        LogConfig {
+            level: "info".to_string(),
+        }
+    }
+}
+
+impl Config {
+    /// 从TOML文件读取配置，文件中缺省的字段使用默认值
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("无法读取配置文件 {}", path.display()))?;
+        Self::from_toml(&contents).with_context(|| format!("配置文件 {} 格式错误", path.display()))
+    }
+
+    pub fn from_toml(contents: &str) -> Result<Self> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+
+    /// 加载配置文件并叠加环境变量。
+    ///
+    /// 配置文件依次取 `explicit`、`WEI_MCP_CONFIG` 和当前目录下的 `wei-server-mcp.toml`；
+    /// 显式指定的文件不存在时报错，默认文件不存在时直接使用默认配置。
+    pub fn load(explicit: Option<&Path>) -> Result<Self> {
+        let from_env = std::env::var_os(format!("{}CONFIG", ENV_PREFIX)).map(PathBuf::from);
+        let mut config = match explicit.map(Path::to_path_buf).or(from_env) {
+            Some(path) => Self::from_file(&path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Self::from_file(Path::new(DEFAULT_CONFIG_FILE))?
+            }
+            None => Config::default(),
+        };
+        config.apply_env(|key| std::env::var(key).ok())?;
+        Ok(config)
+    }
+
+    /// 用 `WEI_MCP_*` 环境变量覆盖配置，`lookup` 按完整变量名取值
+    pub fn apply_env(&mut self, lookup: impl Fn(&str) -> Option<String>) -> Result<()> {
+        let var = |name: &str| {
+            let key = format!("{}{}", ENV_PREFIX, name);
+            lookup(&key).map(|value| (key, value))
+        };
+        let parse_port = |key: &str, value: &str| {
+            value
+                .trim()
+                .parse::<u16>()
+                .with_context(|| format!("{} 不是合法的端口: {}", key, value))
+        };
+
+        if let Some((_, value)) = var("TRANSPORT") {
+            self.server.transport = value.parse()?;
+        }
+        if let Some((_, value)) = var("HOST") {
+            self.server.host = value;
+        }
+        if let Some((key, value)) = var("PORT") {
+            self.server.port = parse_port(&key, &value)?;
+        }
+        if let Some((key, value)) = var("MAX_PORT") {
+            self.server.max_port = parse_port(&key, &value)?;
+        }
+        if let Some((_, value)) = var("STATE_FILE") {
+            self.server.state_file = PathBuf::from(value);
+        }
+        if let Some((_, value)) = var("TOOLS") {
+            self.tools.enabled = value.parse()?;
+        }
+        if let Some((_, value)) = var("WEI_RUN") {
+            self.wei_run.path = Some(PathBuf::from(value));
+        }
+        if let Some((key, value)) = var("WEI_RUN_TIMEOUT") {
+            self.wei_run.timeout_secs = value
+                .trim()
+                .parse()
+                .with_context(|| format!("{} 不是合法的秒数: {}", key, value))?;
+        }
+        if let Some((key, value)) = var("MAX_JOBS") {
+            self.scheduler.max_concurrent_jobs = value
+                .trim()
+                .parse()
+                .with_context(|| format!("{} 不是合法的数量: {}", key, value))?;
+        }
+        if let Some((key, value)) = var("MAX_QUEUE") {
+            self.scheduler.max_queue_depth = value
+                .trim()
+                .parse()
+                .with_context(|| format!("{} 不是合法的数量: {}", key, value))?;
+        }
+        if let Some((key, value)) = var("JOB_TTL") {
+            self.jobs.ttl_secs = value
+                .trim()
+                .parse()
+                .with_context(|| format!("{} 不是合法的秒数: {}", key, value))?;
+        }
+        if let Some((key, value)) = var("JOB_MAX_RUNTIME") {
+            self.jobs.max_runtime_secs = value
+                .trim()
+                .parse()
+                .with_context(|| format!("{} 不是合法的秒数: {}", key, value))?;
+        }
+        if let Some((_, value)) = var("SUPPLIER_DB") {
+            self.suppliers.db_path = PathBuf::from(value);
+        }
+        if let Some((_, value)) = var("EXPORT_DIR") {
+            self.suppliers.export_dir = PathBuf::from(value);
+        }
+        if let Some((_, value)) = var("GPU_CATALOG") {
+            self.gpu_specs.catalog = Some(PathBuf::from(value));
+        }
+        if let Some((_, value)) = var("PROMPTS_DIR") {
+            self.prompts.dir = PathBuf::from(value);
+        }
+        if let Some((_, value)) = var("LOG_LEVEL") {
+            self.log.level = value;
+        }
+        Ok(())
+    }
+
+    /// 检查各字段之间的约束
+    pub fn validate(&self) -> Result<()> {
+        if self.server.port > self.server.max_port {
+            anyhow::bail!(
+                "起始端口 {} 大于端口上限 {}",
+                self.server.port,
+                self.server.max_port
+            );
+        }
+        if self.wei_run.timeout_secs == 0 {
+            anyhow::bail!("wei_run.timeout_secs 必须大于0");
+        }
+        if self.scheduler.max_concurrent_jobs == 0 {
+            anyhow::bail!("scheduler.max_concurrent_jobs 必须大于0");
+        }
+        if self.jobs.ttl_secs == 0 {
+            anyhow::bail!("jobs.ttl_secs 必须大于0");
+        }
+        if self.jobs.max_runtime_secs == 0 {
+            anyhow::bail!("jobs.max_runtime_secs 必须大于0");
+        }
+        self.log_level()?;
+        Ok(())
+    }
+
+    pub fn log_level(&self) -> Result<tracing::Level> {
+        tracing::Level::from_str(&self.log.level)
+            .map_err(|_| anyhow::anyhow!("未知的日志级别: {}", self.log.level))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::ToolGroup;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_partial_toml_keeps_defaults() {
+        let config = Config::from_toml(
+            r#"
+            [server]
+            port = 2000
+            transport = "stdio"
+
+            [tools]
+            enabled = ["math", "demo"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.server.port, 2000);
+        assert_eq!(config.server.transport, TransportMode::Stdio);
+        assert_eq!(config.server.host, "127.0.0.1");
+        assert_eq!(config.server.max_port, 65535);
+        assert!(config.tools.enabled.is_enabled(ToolGroup::Angel));
+        assert!(!config.tools.enabled.is_enabled(ToolGroup::WeiGpu));
+        assert_eq!(config.log.level, "info");
+    }
+
+    #[test]
+    fn test_unknown_field_is_rejected() {
+        assert!(Config::from_toml("[server]\nprot = 1").is_err());
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let vars: HashMap<&str, &str> = [
+            ("WEI_MCP_HOST", "0.0.0.0"),
+            ("WEI_MCP_PORT", "3000"),
+            ("WEI_MCP_TOOLS", "gpu-specs"),
+            ("WEI_MCP_WEI_RUN", "/opt/wei/wei-run"),
+            ("WEI_MCP_WEI_RUN_TIMEOUT", "60"),
+            ("WEI_MCP_MAX_JOBS", "2"),
+            ("WEI_MCP_MAX_QUEUE", "0"),
+            ("WEI_MCP_JOB_TTL", "600"),
+            ("WEI_MCP_JOB_MAX_RUNTIME", "7200"),
+            ("WEI_MCP_SUPPLIER_DB", "/var/lib/wei/suppliers.db"),
+            ("WEI_MCP_EXPORT_DIR", "/var/lib/wei/exports"),
+            ("WEI_MCP_GPU_CATALOG", "/etc/wei/gpus.toml"),
+            ("WEI_MCP_PROMPTS_DIR", "/etc/wei/prompts"),
+            ("WEI_MCP_LOG_LEVEL", "debug"),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut config = Config::default();
+        config
+            .apply_env(|key| vars.get(key).map(|value| value.to_string()))
+            .unwrap();
+
+        assert_eq!(config.server.host, "0.0.0.0");
+        assert_eq!(config.server.port, 3000);
+        assert_eq!(config.tools.enabled.iter().collect::<Vec<_>>(), vec![ToolGroup::GpuSpecs]);
+        assert_eq!(config.wei_run.path, Some(PathBuf::from("/opt/wei/wei-run")));
+        assert_eq!(config.wei_run.timeout_secs, 60);
+        assert_eq!(config.scheduler.max_concurrent_jobs, 2);
+        assert_eq!(config.scheduler.max_queue_depth, 0);
+        assert_eq!(config.jobs.ttl_secs, 600);
+        assert_eq!(config.jobs.max_runtime_secs, 7200);
+        assert_eq!(config.suppliers.db_path, PathBuf::from("/var/lib/wei/suppliers.db"));
+        assert_eq!(config.suppliers.export_dir, PathBuf::from("/var/lib/wei/exports"));
+        assert_eq!(config.gpu_specs.catalog, Some(PathBuf::from("/etc/wei/gpus.toml")));
+        assert_eq!(config.prompts.dir, PathBuf::from("/etc/wei/prompts"));
+        assert_eq!(config.log_level().unwrap(), tracing::Level::DEBUG);
+
+        // 非法的端口值报错而不是静默忽略
+        let mut config = Config::default();
+        let result = config.apply_env(|key| (key == "WEI_MCP_PORT").then(|| "abc".to_string()));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_toml_round_trip() {
+        let mut config = Config::default();
+        config.wei_run.path = Some(PathBuf::from("/usr/local/bin/wei-run"));
+        let text = config.to_toml().unwrap();
+        assert_eq!(Config::from_toml(&text).unwrap(), config);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(Config::default().validate().is_ok());
+
+        let mut config = Config::default();
+        config.server.port = 2000;
+        config.server.max_port = 1999;
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.log.level = "verbose".to_string();
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.wei_run.timeout_secs = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.scheduler.max_concurrent_jobs = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.jobs.ttl_secs = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.jobs.max_runtime_secs = 0;
+        assert!(config.validate().is_err());
+    }
+}
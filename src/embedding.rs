@@ -0,0 +1,184 @@
+//! 嵌入向量的解析与后处理。
+//!
+//! wei-run `embed` 的输出可以是JSON数组、带 `embedding` 字段的JSON对象，
+//! 或以逗号、空白分隔的数字，这里统一解析为 `Vec<f32>`。
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// 解析后的单个嵌入向量
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedEmbedding {
+    /// wei-run在输出中报告的模型名称
+    pub model: Option<String>,
+    pub vector: Vec<f32>,
+}
+
+/// 单条文本的嵌入结果
+#[derive(Debug, Clone, Serialize)]
+pub struct EmbeddingResult {
+    pub model: String,
+    pub dimensions: usize,
+    pub embedding: Vec<f32>,
+}
+
+/// 批量文本的嵌入结果，顺序与输入一致
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchEmbeddingResult {
+    pub model: String,
+    pub dimensions: usize,
+    pub embeddings: Vec<Vec<f32>>,
+}
+
+/// 解析wei-run输出的嵌入向量
+pub fn parse_embedding(output: &str) -> Result<ParsedEmbedding, String> {
+    let output = output.trim();
+    if output.is_empty() {
+        return Err("empty embedding output".to_string());
+    }
+
+    if let Ok(value) = serde_json::from_str::<Value>(output) {
+        return parse_json(&value);
+    }
+
+    let vector = output
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            part.parse::<f32>()
+                .map_err(|_| format!("invalid number in embedding output: {:?}", part))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    check_vector(vector).map(|vector| ParsedEmbedding { model: None, vector })
+}
+
+fn parse_json(value: &Value) -> Result<ParsedEmbedding, String> {
+    match value {
+        Value::Array(_) => Ok(ParsedEmbedding {
+            model: None,
+            vector: json_vector(value)?,
+        }),
+        Value::Object(map) => {
+            let vector = map
+                .get("embedding")
+                .or_else(|| map.get("vector"))
+                .ok_or_else(|| "embedding output has no \"embedding\" field".to_string())?;
+            Ok(ParsedEmbedding {
+                model: map.get("model").and_then(Value::as_str).map(str::to_string),
+                vector: json_vector(vector)?,
+            })
+        }
+        // 单个数字也是合法的JSON，但不是向量
+        _ => Err(format!("unexpected embedding output: {}", value)),
+    }
+}
+
+fn json_vector(value: &Value) -> Result<Vec<f32>, String> {
+    let items = value
+        .as_array()
+        .ok_or_else(|| format!("embedding is not an array: {}", value))?;
+    let vector = items
+        .iter()
+        .map(|item| {
+            item.as_f64()
+                .map(|x| x as f32)
+                .ok_or_else(|| format!("invalid number in embedding output: {}", item))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    check_vector(vector)
+}
+
+fn check_vector(vector: Vec<f32>) -> Result<Vec<f32>, String> {
+    if vector.is_empty() {
+        return Err("embedding is empty".to_string());
+    }
+    if vector.iter().any(|x| !x.is_finite()) {
+        return Err("embedding contains NaN or infinite values".to_string());
+    }
+    Ok(vector)
+}
+
+/// 检查所有向量的维度一致，并与期望的维度相同，返回维度
+pub fn check_dimensions(vectors: &[Vec<f32>], expected: Option<usize>) -> Result<usize, String> {
+    let dimensions = expected
+        .or_else(|| vectors.first().map(Vec::len))
+        .unwrap_or_default();
+    for (index, vector) in vectors.iter().enumerate() {
+        if vector.len() != dimensions {
+            return Err(format!(
+                "embedding {} has {} dimensions, expected {}",
+                index,
+                vector.len(),
+                dimensions
+            ));
+        }
+    }
+    Ok(dimensions)
+}
+
+/// L2归一化，零向量保持不变
+pub fn l2_normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for x in vector.iter_mut() {
+            *x /= norm;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_formats() {
+        let expected = vec![0.1, -0.2, 3.0];
+
+        let parsed = parse_embedding("[0.1, -0.2, 3]\n").unwrap();
+        assert_eq!(parsed.vector, expected);
+        assert_eq!(parsed.model, None);
+
+        let parsed = parse_embedding(r#"{"model": "bge-m3", "embedding": [0.1, -0.2, 3]}"#).unwrap();
+        assert_eq!(parsed.vector, expected);
+        assert_eq!(parsed.model.as_deref(), Some("bge-m3"));
+
+        assert_eq!(parse_embedding("0.1 -0.2\n3.0").unwrap().vector, expected);
+        assert_eq!(parse_embedding("0.1,-0.2,3").unwrap().vector, expected);
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_output() {
+        assert!(parse_embedding("").is_err());
+        assert!(parse_embedding("[]").is_err());
+        assert!(parse_embedding("42").is_err());
+        assert!(parse_embedding("model not loaded").is_err());
+        assert!(parse_embedding(r#"{"model": "bge-m3"}"#).is_err());
+        assert!(parse_embedding(r#"[0.1, "x"]"#).is_err());
+        assert!(parse_embedding("0.1 NaN").is_err());
+    }
+
+    #[test]
+    fn test_check_dimensions() {
+        let vectors = vec![vec![1.0, 2.0], vec![3.0, 4.0]];
+        assert_eq!(check_dimensions(&vectors, None).unwrap(), 2);
+        assert_eq!(check_dimensions(&vectors, Some(2)).unwrap(), 2);
+        assert!(check_dimensions(&vectors, Some(3)).is_err());
+
+        let mismatched = vec![vec![1.0, 2.0], vec![3.0]];
+        let err = check_dimensions(&mismatched, None).unwrap_err();
+        assert!(err.contains("embedding 1 has 1 dimensions"));
+    }
+
+    #[test]
+    fn test_l2_normalize() {
+        let mut vector = vec![3.0, 4.0];
+        l2_normalize(&mut vector);
+        assert_eq!(vector, vec![0.6, 0.8]);
+
+        let mut zero = vec![0.0, 0.0];
+        l2_normalize(&mut zero);
+        assert_eq!(zero, vec![0.0, 0.0]);
+    }
+}
@@ -0,0 +1,1014 @@
+//! GPU规格目录，供QueryGPUSpecs等工具查询。
+//!
+//! 内置目录 `data/gpu_specs.toml` 编译时打包进程序；`[gpu_specs] catalog` 可以指定一个
+//! TOML或JSON文件，其中的型号按名称（不区分大小写）覆盖内置的整条记录，新型号追加在后面。
+//! 增加型号或修正数据不需要重新编译。
+//!
+//! 型号匹配忽略大小写、空格、连字符和厂商前缀，也可以使用记录中的别名；
+//! 同一型号的不同版本按外形规格（SXM/PCIe）和显存容量区分，如 `H100 PCIe`、`NVIDIA A100 40GB`。
+//! 都不匹配时按字符串相似度给出最接近的型号作为建议。
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::config::GpuSpecsConfig;
+
+const BUNDLED_CATALOG: &str = include_str!("../data/gpu_specs.toml");
+
+/// 匹配型号时忽略的厂商和产品线前缀
+const VENDOR_WORDS: [&str; 4] = ["nvidia", "英伟达", "geforce", "tesla"];
+
+/// 相似度不低于此值的型号才作为建议
+const SUGGESTION_THRESHOLD: f64 = 0.75;
+
+/// 最多给出的建议数
+const MAX_SUGGESTIONS: usize = 3;
+
+/// 一款GPU的规格。
+///
+/// 数值字段为None表示没有数据，JSON输出中为null；`nvlink_bandwidth_gbs` 为0表示不支持NVLink，
+/// `mig_instances` 为0表示不支持MIG。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GpuSpec {
+    /// 型号，如 `H100`
+    pub name: String,
+    /// 其他常用名称
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default = "default_vendor")]
+    pub vendor: String,
+    pub architecture: String,
+    /// 外形规格，如 `SXM5`、`PCIe`
+    #[serde(default)]
+    pub form_factor: Option<String>,
+    #[serde(default)]
+    pub cuda_cores: Option<u32>,
+    #[serde(default)]
+    pub tensor_cores: Option<u32>,
+    #[serde(default)]
+    pub memory_gb: Option<f64>,
+    #[serde(default)]
+    pub memory_type: Option<String>,
+    /// 显存带宽（GB/s）
+    #[serde(default)]
+    pub memory_bandwidth_gbs: Option<f64>,
+    /// 不含稀疏的峰值算力（TFLOPS）
+    #[serde(default)]
+    pub fp8_tflops: Option<f64>,
+    #[serde(default)]
+    pub fp16_tflops: Option<f64>,
+    #[serde(default)]
+    pub fp32_tflops: Option<f64>,
+    /// NVLink总带宽（GB/s）
+    #[serde(default)]
+    pub nvlink_bandwidth_gbs: Option<f64>,
+    #[serde(default)]
+    pub tdp_watts: Option<u32>,
+    /// 最多划分的MIG实例数
+    #[serde(default)]
+    pub mig_instances: Option<u32>,
+    #[serde(default)]
+    pub notes: Vec<String>,
+    /// 英文说明，没有时英文输出不显示说明
+    #[serde(default)]
+    pub notes_en: Vec<String>,
+}
+
+fn default_vendor() -> String {
+    "NVIDIA".to_string()
+}
+
+/// 格式化数值：整数部分每三位加逗号，最多保留两位小数
+pub(crate) fn format_number(value: f64) -> String {
+    let text = format!("{:.2}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    let (integer, fraction) = text.split_once('.').unwrap_or((text, ""));
+    let (sign, digits) = integer.strip_prefix('-').map_or(("", integer), |digits| ("-", digits));
+    let mut grouped = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    if fraction.is_empty() {
+        format!("{}{}", sign, grouped)
+    } else {
+        format!("{}{}.{}", sign, grouped, fraction)
+    }
+}
+
+/// 输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Markdown,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "text" | "txt" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            other => Err(format!("unknown format {:?}, expected text, json or markdown", other)),
+        }
+    }
+}
+
+/// 文本和Markdown输出的语言，JSON输出与语言无关
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lang {
+    #[default]
+    Zh,
+    En,
+}
+
+impl FromStr for Lang {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "zh" | "zh-cn" | "cn" | "chinese" => Ok(Lang::Zh),
+            "en" | "en-us" | "english" => Ok(Lang::En),
+            other => Err(format!("unknown lang {:?}, expected zh or en", other)),
+        }
+    }
+}
+
+/// GPU规格工具的错误
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum GpuSpecError {
+    #[error("Invalid arguments: {0}")]
+    InvalidArguments(String),
+    /// 目录中没有该型号，`suggestions` 为最接近的型号
+    #[error("Unknown GPU model {model:?}{}; known models: {}", did_you_mean(suggestions), known.join(", "))]
+    UnknownModel {
+        model: String,
+        suggestions: Vec<String>,
+        known: Vec<String>,
+    },
+}
+
+fn did_you_mean(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!(" (did you mean {}?)", suggestions.join(", "))
+    }
+}
+
+impl GpuSpecError {
+    /// 错误类型的稳定名称，供客户端区分错误
+    pub fn kind(&self) -> &'static str {
+        match self {
+            GpuSpecError::InvalidArguments(_) => "invalid_arguments",
+            GpuSpecError::UnknownModel { .. } => "unknown_model",
+        }
+    }
+
+    /// 结构化的错误信息，放在工具响应的 `_meta.error` 中
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut error = json!({
+            "type": self.kind(),
+            "message": self.to_string(),
+        });
+        if let GpuSpecError::UnknownModel { model, suggestions, known } = self {
+            error["model"] = json!(model);
+            error["suggestions"] = json!(suggestions);
+            error["knownModels"] = json!(known);
+        }
+        error
+    }
+}
+
+/// 规格字段，按显示顺序排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Architecture,
+    FormFactor,
+    CudaCores,
+    TensorCores,
+    Memory,
+    MemoryBandwidth,
+    Fp8,
+    Fp16,
+    Fp32,
+    NvLink,
+    Tdp,
+    Mig,
+}
+
+impl Field {
+    pub const ALL: [Field; 12] = [
+        Field::Architecture,
+        Field::FormFactor,
+        Field::CudaCores,
+        Field::TensorCores,
+        Field::Memory,
+        Field::MemoryBandwidth,
+        Field::Fp8,
+        Field::Fp16,
+        Field::Fp32,
+        Field::NvLink,
+        Field::Tdp,
+        Field::Mig,
+    ];
+
+    /// JSON中的字段名，与 [`GpuSpec`] 的字段名相同
+    pub fn key(self) -> &'static str {
+        match self {
+            Field::Architecture => "architecture",
+            Field::FormFactor => "form_factor",
+            Field::CudaCores => "cuda_cores",
+            Field::TensorCores => "tensor_cores",
+            Field::Memory => "memory_gb",
+            Field::MemoryBandwidth => "memory_bandwidth_gbs",
+            Field::Fp8 => "fp8_tflops",
+            Field::Fp16 => "fp16_tflops",
+            Field::Fp32 => "fp32_tflops",
+            Field::NvLink => "nvlink_bandwidth_gbs",
+            Field::Tdp => "tdp_watts",
+            Field::Mig => "mig_instances",
+        }
+    }
+
+    pub fn label(self, lang: Lang) -> &'static str {
+        match (self, lang) {
+            (Field::Architecture, Lang::Zh) => "架构",
+            (Field::Architecture, Lang::En) => "Architecture",
+            (Field::FormFactor, Lang::Zh) => "外形规格",
+            (Field::FormFactor, Lang::En) => "Form factor",
+            (Field::CudaCores, Lang::Zh) => "CUDA核心",
+            (Field::CudaCores, Lang::En) => "CUDA cores",
+            (Field::TensorCores, Lang::Zh) => "Tensor核心",
+            (Field::TensorCores, Lang::En) => "Tensor cores",
+            (Field::Memory, Lang::Zh) => "显存",
+            (Field::Memory, Lang::En) => "Memory",
+            (Field::MemoryBandwidth, Lang::Zh) => "显存带宽",
+            (Field::MemoryBandwidth, Lang::En) => "Memory bandwidth",
+            (Field::Fp8, Lang::Zh) => "FP8算力",
+            (Field::Fp8, Lang::En) => "FP8",
+            (Field::Fp16, Lang::Zh) => "FP16算力",
+            (Field::Fp16, Lang::En) => "FP16",
+            (Field::Fp32, Lang::Zh) => "FP32算力",
+            (Field::Fp32, Lang::En) => "FP32",
+            (Field::NvLink, _) => "NVLink",
+            (Field::Tdp, Lang::Zh) => "功耗（TDP）",
+            (Field::Tdp, Lang::En) => "TDP",
+            (Field::Mig, _) => "MIG",
+        }
+    }
+
+    /// 数值字段的值，架构等非数值字段为None
+    pub fn number(self, spec: &GpuSpec) -> Option<f64> {
+        match self {
+            Field::Architecture | Field::FormFactor => None,
+            Field::CudaCores => spec.cuda_cores.map(f64::from),
+            Field::TensorCores => spec.tensor_cores.map(f64::from),
+            Field::Memory => spec.memory_gb,
+            Field::MemoryBandwidth => spec.memory_bandwidth_gbs,
+            Field::Fp8 => spec.fp8_tflops,
+            Field::Fp16 => spec.fp16_tflops,
+            Field::Fp32 => spec.fp32_tflops,
+            Field::NvLink => spec.nvlink_bandwidth_gbs,
+            Field::Tdp => spec.tdp_watts.map(f64::from),
+            Field::Mig => spec.mig_instances.map(f64::from),
+        }
+    }
+
+    /// 显示的值，没有数据时为None
+    pub fn display(self, spec: &GpuSpec, lang: Lang) -> Option<String> {
+        let tflops = |value: f64| format!("{} TFLOPS", format_number(value));
+        let value = match self {
+            Field::Architecture => match lang {
+                Lang::Zh => format!("{}架构", spec.architecture),
+                Lang::En => spec.architecture.clone(),
+            },
+            Field::FormFactor => spec.form_factor.clone()?,
+            Field::CudaCores | Field::TensorCores => {
+                let cores = format_number(self.number(spec)?);
+                match lang {
+                    Lang::Zh => format!("{}个", cores),
+                    Lang::En => cores,
+                }
+            }
+            Field::Memory => match &spec.memory_type {
+                Some(memory_type) => format!("{} GB {}", format_number(spec.memory_gb?), memory_type),
+                None => format!("{} GB", format_number(spec.memory_gb?)),
+            },
+            Field::MemoryBandwidth => format!("{} GB/s", format_number(spec.memory_bandwidth_gbs?)),
+            Field::Fp8 | Field::Fp16 | Field::Fp32 => tflops(self.number(spec)?),
+            Field::NvLink => match (spec.nvlink_bandwidth_gbs?, lang) {
+                (bandwidth, _) if bandwidth > 0.0 => format!("{} GB/s", format_number(bandwidth)),
+                (_, Lang::Zh) => "不支持".to_string(),
+                (_, Lang::En) => "not supported".to_string(),
+            },
+            Field::Tdp => format!("{} W", spec.tdp_watts?),
+            Field::Mig => match (spec.mig_instances?, lang) {
+                (0, Lang::Zh) => "不支持".to_string(),
+                (0, Lang::En) => "not supported".to_string(),
+                (instances, Lang::Zh) => format!("支持，最多{}个实例", instances),
+                (instances, Lang::En) => format!("up to {} instances", instances),
+            },
+        };
+        Some(value)
+    }
+}
+
+// Markdown表格单元格中的竖线和换行需要转义
+pub(crate) fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+// 按非字母数字字符切分并转为小写，去掉厂商前缀，"80 GB" 合并为 "80gb"
+fn tokens(name: &str) -> Vec<String> {
+    let mut text = name.to_lowercase();
+    for word in VENDOR_WORDS {
+        text = text.replace(word, " ");
+    }
+    let mut tokens: Vec<String> = Vec::new();
+    for token in text.split(|c: char| !c.is_alphanumeric()).filter(|token| !token.is_empty()) {
+        match tokens.last_mut() {
+            Some(last) if matches!(token, "gb" | "g") && last.chars().all(|c| c.is_ascii_digit()) => {
+                last.push_str("gb");
+            }
+            _ => tokens.push(token.to_string()),
+        }
+    }
+    tokens
+}
+
+/// 用于比较的型号名称，如 `NVIDIA H100-80GB` 为 `h10080gb`
+pub(crate) fn normalize(name: &str) -> String {
+    tokens(name).concat()
+}
+
+// 型号名称拆分为基础型号、外形规格和显存容量，如 "H100 SXM 80GB"
+#[derive(Debug, PartialEq)]
+struct Variant {
+    base: String,
+    form_factor: Option<String>,
+    memory_gb: Option<f64>,
+}
+
+impl Variant {
+    fn parse(name: &str) -> Self {
+        let mut variant = Variant {
+            base: String::new(),
+            form_factor: None,
+            memory_gb: None,
+        };
+        for token in tokens(name) {
+            let memory = token
+                .strip_suffix("gb")
+                .or_else(|| token.strip_suffix('g'))
+                .and_then(|digits| digits.parse::<f64>().ok());
+            if memory.is_some() {
+                variant.memory_gb = memory;
+            } else if token.starts_with("sxm") || token == "pcie" {
+                variant.form_factor = Some(token);
+            } else {
+                variant.base.push_str(&token);
+            }
+        }
+        variant
+    }
+}
+
+impl GpuSpec {
+    /// 型号和所有别名
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.name.as_str()).chain(self.aliases.iter().map(String::as_str))
+    }
+
+    /// 当前语言的说明
+    pub fn notes(&self, lang: Lang) -> &[String] {
+        match lang {
+            Lang::Zh => &self.notes,
+            Lang::En => &self.notes_en,
+        }
+    }
+
+    /// 按格式和语言输出规格，没有数据的字段不显示
+    pub fn render(&self, format: OutputFormat, lang: Lang) -> String {
+        let rows: Vec<(&str, String)> = Field::ALL
+            .iter()
+            .filter_map(|field| field.display(self, lang).map(|value| (field.label(lang), value)))
+            .collect();
+        let mut out = String::new();
+        match format {
+            OutputFormat::Json => {
+                return serde_json::to_string_pretty(self).unwrap_or_default();
+            }
+            OutputFormat::Text => {
+                match lang {
+                    Lang::Zh => out.push_str(&format!("{} {} GPU规格：\n", self.vendor, self.name)),
+                    Lang::En => out.push_str(&format!("{} {} specifications:\n", self.vendor, self.name)),
+                }
+                let separator = match lang {
+                    Lang::Zh => "：",
+                    Lang::En => ": ",
+                };
+                for (label, value) in rows {
+                    out.push_str(&format!("- {}{}{}\n", label, separator, value));
+                }
+                for note in self.notes(lang) {
+                    out.push_str(&format!("- {}\n", note));
+                }
+            }
+            OutputFormat::Markdown => {
+                out.push_str(&format!("### {} {}\n\n", self.vendor, self.name));
+                match lang {
+                    Lang::Zh => out.push_str("| 参数 | 值 |\n| --- | --- |\n"),
+                    Lang::En => out.push_str("| Spec | Value |\n| --- | --- |\n"),
+                }
+                for (label, value) in rows {
+                    out.push_str(&format!("| {} | {} |\n", label, markdown_cell(&value)));
+                }
+                if !self.notes(lang).is_empty() {
+                    out.push('\n');
+                    for note in self.notes(lang) {
+                        out.push_str(&format!("- {}\n", note));
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+impl fmt::Display for GpuSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(OutputFormat::Text, Lang::Zh))
+    }
+}
+
+/// GPU规格目录，按加载顺序保存
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GpuCatalog {
+    #[serde(default, rename = "gpu")]
+    pub gpus: Vec<GpuSpec>,
+}
+
+impl GpuCatalog {
+    /// 编译时打包的目录
+    pub fn bundled() -> Self {
+        Self::from_toml(BUNDLED_CATALOG).expect("bundled GPU catalog is valid")
+    }
+
+    pub fn from_toml(contents: &str) -> Result<Self> {
+        let catalog: GpuCatalog = toml::from_str(contents)?;
+        catalog.validate()?;
+        Ok(catalog)
+    }
+
+    /// JSON格式为 `{"gpu": [...]}` 或直接为规格数组
+    pub fn from_json(contents: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(contents)?;
+        let catalog = if value.is_array() {
+            GpuCatalog {
+                gpus: serde_json::from_value(value)?,
+            }
+        } else {
+            serde_json::from_value(value)?
+        };
+        catalog.validate()?;
+        Ok(catalog)
+    }
+
+    /// 按扩展名读取TOML或JSON文件
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents =
+            fs::read_to_string(path).with_context(|| format!("无法读取GPU规格文件 {}", path.display()))?;
+        let catalog = match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::from_json(&contents),
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => Self::from_toml(&contents),
+            _ => Err(anyhow::anyhow!("只支持.toml或.json文件")),
+        };
+        catalog.with_context(|| format!("GPU规格文件 {} 格式错误", path.display()))
+    }
+
+    fn validate(&self) -> Result<()> {
+        for (i, gpu) in self.gpus.iter().enumerate() {
+            if gpu.name.trim().is_empty() {
+                anyhow::bail!("第{}个GPU没有型号名称", i + 1);
+            }
+            if self.gpus[..i].iter().any(|other| other.name.eq_ignore_ascii_case(&gpu.name)) {
+                anyhow::bail!("GPU型号 {} 重复定义", gpu.name);
+            }
+            for key in gpu.keys() {
+                let normalized = normalize(key);
+                if normalized.is_empty() {
+                    anyhow::bail!("GPU型号 {} 的名称或别名 {:?} 无效", gpu.name, key);
+                }
+                if let Some(other) = self.gpus[..i]
+                    .iter()
+                    .find(|other| other.keys().any(|other_key| normalize(other_key) == normalized))
+                {
+                    anyhow::bail!("GPU型号 {} 的名称或别名 {:?} 与 {} 冲突", gpu.name, key, other.name);
+                }
+            }
+            let values = [
+                ("memory_gb", gpu.memory_gb),
+                ("memory_bandwidth_gbs", gpu.memory_bandwidth_gbs),
+                ("fp8_tflops", gpu.fp8_tflops),
+                ("fp16_tflops", gpu.fp16_tflops),
+                ("fp32_tflops", gpu.fp32_tflops),
+                ("nvlink_bandwidth_gbs", gpu.nvlink_bandwidth_gbs),
+            ];
+            for (field, value) in values {
+                if value.is_some_and(|value| !value.is_finite() || value < 0.0) {
+                    anyhow::bail!("GPU型号 {} 的 {} 不能为负数", gpu.name, field);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 合并另一个目录：同名型号整条替换，新型号追加在后面
+    pub fn merge(&mut self, other: GpuCatalog) {
+        for gpu in other.gpus {
+            match self.gpus.iter_mut().find(|existing| existing.name.eq_ignore_ascii_case(&gpu.name)) {
+                Some(existing) => *existing = gpu,
+                None => self.gpus.push(gpu),
+            }
+        }
+    }
+
+    /// 按型号或别名查找，先精确匹配，再按外形规格和显存容量匹配同一型号的版本
+    pub fn get(&self, name: &str) -> Option<&GpuSpec> {
+        let normalized = normalize(name);
+        if normalized.is_empty() {
+            return None;
+        }
+        if let Some(gpu) = self
+            .gpus
+            .iter()
+            .find(|gpu| gpu.keys().any(|key| normalize(key) == normalized))
+        {
+            return Some(gpu);
+        }
+
+        let query = Variant::parse(name);
+        if query.base.is_empty() {
+            return None;
+        }
+        self.gpus.iter().find(|gpu| {
+            gpu.keys().any(|key| Variant::parse(key).base == query.base)
+                && query.form_factor.as_ref().is_none_or(|form_factor| {
+                    gpu.form_factor
+                        .as_deref()
+                        .is_some_and(|own| normalize(own).starts_with(form_factor.as_str()))
+                })
+                && query
+                    .memory_gb
+                    .is_none_or(|memory| gpu.memory_gb.is_some_and(|own| (own - memory).abs() < 0.5))
+        })
+    }
+
+    /// 与 `name` 最接近的型号，按相似度从高到低排列
+    pub fn suggestions(&self, name: &str) -> Vec<&str> {
+        let normalized = normalize(name);
+        let mut scored: Vec<(f64, &str)> = self
+            .gpus
+            .iter()
+            .map(|gpu| {
+                let score = gpu
+                    .keys()
+                    .map(|key| strsim::jaro_winkler(&normalized, &normalize(key)))
+                    .fold(0.0, f64::max);
+                (score, gpu.name.as_str())
+            })
+            .filter(|(score, _)| *score >= SUGGESTION_THRESHOLD)
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored.into_iter().take(MAX_SUGGESTIONS).map(|(_, name)| name).collect()
+    }
+
+    /// 按型号查找，没有时返回列出建议和已知型号的错误
+    pub fn lookup(&self, name: &str) -> Result<&GpuSpec, GpuSpecError> {
+        self.get(name).ok_or_else(|| GpuSpecError::UnknownModel {
+            model: name.trim().to_string(),
+            suggestions: self.suggestions(name).into_iter().map(str::to_string).collect(),
+            known: self.names().into_iter().map(str::to_string).collect(),
+        })
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.gpus.iter().map(|gpu| gpu.name.as_str()).collect()
+    }
+}
+
+/// 多款GPU的对比，比值以第一款GPU为基准
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison<'a> {
+    pub gpus: Vec<&'a GpuSpec>,
+}
+
+fn format_ratio(ratio: f64) -> String {
+    format!("{:.2}x", ratio)
+}
+
+fn missing_label(lang: Lang) -> &'static str {
+    match lang {
+        Lang::Zh => "无数据",
+        Lang::En => "n/a",
+    }
+}
+
+impl<'a> Comparison<'a> {
+    /// 按型号查找要对比的GPU，至少两款且不能重复
+    pub fn new(catalog: &'a GpuCatalog, models: &[String]) -> Result<Self, GpuSpecError> {
+        let mut gpus: Vec<&GpuSpec> = Vec::new();
+        for model in models {
+            let gpu = catalog.lookup(model)?;
+            if gpus.iter().any(|existing| existing.name == gpu.name) {
+                return Err(GpuSpecError::InvalidArguments(format!("duplicate GPU model {:?}", gpu.name)));
+            }
+            gpus.push(gpu);
+        }
+        if gpus.len() < 2 {
+            return Err(GpuSpecError::InvalidArguments(
+                "at least two GPU models are required".to_string(),
+            ));
+        }
+        Ok(Comparison { gpus })
+    }
+
+    pub fn baseline(&self) -> &GpuSpec {
+        self.gpus[0]
+    }
+
+    /// 某个数值字段相对基准的比值，任一方没有数据或基准为0时为None
+    pub fn ratio(&self, field: Field, gpu: &GpuSpec) -> Option<f64> {
+        let base = field.number(self.baseline()).filter(|base| *base > 0.0)?;
+        Some(field.number(gpu)? / base)
+    }
+
+    /// 每款GPU没有数据的字段
+    pub fn missing(&self, gpu: &GpuSpec) -> Vec<Field> {
+        Field::ALL
+            .into_iter()
+            .filter(|field| field.display(gpu, Lang::Zh).is_none())
+            .collect()
+    }
+
+    /// 结构化的对比结果：`metrics` 中的 `values` 和 `ratios` 与 `models` 的顺序一致
+    pub fn to_json(&self) -> serde_json::Value {
+        let metrics: Vec<serde_json::Value> = Field::ALL
+            .into_iter()
+            .filter(|field| !matches!(field, Field::Architecture | Field::FormFactor))
+            .map(|field| {
+                let values: Vec<Option<f64>> = self.gpus.iter().map(|gpu| field.number(gpu)).collect();
+                let ratios: Vec<Option<f64>> = self
+                    .gpus
+                    .iter()
+                    .map(|gpu| self.ratio(field, gpu).map(|ratio| (ratio * 100.0).round() / 100.0))
+                    .collect();
+                json!({
+                    "field": field.key(),
+                    "values": values,
+                    "ratios": ratios,
+                })
+            })
+            .collect();
+        let missing: serde_json::Map<String, serde_json::Value> = self
+            .gpus
+            .iter()
+            .map(|gpu| {
+                let fields: Vec<&str> = self.missing(gpu).into_iter().map(Field::key).collect();
+                (gpu.name.clone(), json!(fields))
+            })
+            .collect();
+        json!({
+            "models": self.gpus.iter().map(|gpu| gpu.name.as_str()).collect::<Vec<_>>(),
+            "baseline": self.baseline().name,
+            "gpus": self.gpus,
+            "metrics": metrics,
+            "missing": missing,
+        })
+    }
+
+    /// Markdown对比表和比值表，没有数据的字段明确标出
+    pub fn to_markdown(&self, lang: Lang) -> String {
+        let names: Vec<&str> = self.gpus.iter().map(|gpu| gpu.name.as_str()).collect();
+        let missing = missing_label(lang);
+        let mut out = match lang {
+            Lang::Zh => format!("### GPU对比：{}\n\n| 参数 |", names.join(" vs ")),
+            Lang::En => format!("### GPU comparison: {}\n\n| Spec |", names.join(" vs ")),
+        };
+        for name in &names {
+            out.push_str(&format!(" {} |", markdown_cell(name)));
+        }
+        out.push_str(&format!("\n| --- |{}\n", " --- |".repeat(names.len())));
+        for field in Field::ALL {
+            out.push_str(&format!("| {} |", field.label(lang)));
+            for gpu in &self.gpus {
+                let value = field.display(gpu, lang).unwrap_or_else(|| missing.to_string());
+                out.push_str(&format!(" {} |", markdown_cell(&value)));
+            }
+            out.push('\n');
+        }
+
+        let baseline = &self.baseline().name;
+        match lang {
+            Lang::Zh => out.push_str(&format!("\n#### 相对{}的比值\n\n| 参数 |", baseline)),
+            Lang::En => out.push_str(&format!("\n#### Ratios relative to {}\n\n| Spec |", baseline)),
+        }
+        for gpu in &self.gpus[1..] {
+            out.push_str(&format!(" {}/{} |", markdown_cell(&gpu.name), markdown_cell(baseline)));
+        }
+        out.push_str(&format!("\n| --- |{}\n", " --- |".repeat(self.gpus.len() - 1)));
+        for field in Field::ALL
+            .into_iter()
+            .filter(|field| !matches!(field, Field::Architecture | Field::FormFactor))
+        {
+            out.push_str(&format!("| {} |", field.label(lang)));
+            for gpu in &self.gpus[1..] {
+                let ratio = self.ratio(field, gpu).map(format_ratio).unwrap_or_else(|| missing.to_string());
+                out.push_str(&format!(" {} |", ratio));
+            }
+            out.push('\n');
+        }
+
+        let missing_fields: Vec<String> = self
+            .gpus
+            .iter()
+            .filter_map(|gpu| {
+                let fields: Vec<&str> = self.missing(gpu).into_iter().map(|field| field.label(lang)).collect();
+                (!fields.is_empty()).then(|| match lang {
+                    Lang::Zh => format!("{}（{}）", gpu.name, fields.join("、")),
+                    Lang::En => format!("{} ({})", gpu.name, fields.join(", ")),
+                })
+            })
+            .collect();
+        if !missing_fields.is_empty() {
+            match lang {
+                Lang::Zh => out.push_str(&format!("\n无数据的字段：{}\n", missing_fields.join("；"))),
+                Lang::En => out.push_str(&format!("\nMissing fields: {}\n", missing_fields.join("; "))),
+            }
+        }
+        out
+    }
+}
+
+static CATALOG: RwLock<Option<Arc<GpuCatalog>>> = RwLock::new(None);
+
+/// 加载内置目录并合并用户指定的文件，文件不存在或格式错误时报错
+pub fn configure(config: &GpuSpecsConfig) -> Result<()> {
+    let mut catalog = GpuCatalog::bundled();
+    if let Some(path) = &config.catalog {
+        catalog.merge(GpuCatalog::from_file(path)?);
+        // 用户文件中的别名不能与内置型号冲突
+        catalog
+            .validate()
+            .with_context(|| format!("GPU规格文件 {} 与内置目录冲突", path.display()))?;
+    }
+    if let Ok(mut current) = CATALOG.write() {
+        *current = Some(Arc::new(catalog));
+    }
+    Ok(())
+}
+
+/// 当前使用的目录，未调用 [`configure`] 时为内置目录
+pub fn catalog() -> Arc<GpuCatalog> {
+    if let Some(catalog) = CATALOG.read().ok().and_then(|catalog| catalog.clone()) {
+        return catalog;
+    }
+    let catalog = Arc::new(GpuCatalog::bundled());
+    if let Ok(mut current) = CATALOG.write() {
+        current.get_or_insert_with(|| catalog.clone());
+    }
+    catalog
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_catalog() {
+        let catalog = GpuCatalog::bundled();
+        for name in ["A100", "H100", "H200", "B200", "L40S", "RTX 4090"] {
+            assert!(catalog.get(name).is_some(), "{}", name);
+        }
+        let h100 = catalog.get(" h100 ").unwrap();
+        assert_eq!(h100.architecture, "Hopper");
+        assert_eq!(h100.memory_gb, Some(80.0));
+        assert_eq!(h100.vendor, "NVIDIA");
+
+        let text = h100.to_string();
+        assert!(text.starts_with("NVIDIA H100 GPU规格：\n- 架构：Hopper架构\n- 外形规格：SXM5\n- CUDA核心：16,896个\n"), "{}", text);
+        assert!(text.contains("- 显存：80 GB HBM3\n- 显存带宽：3,350 GB/s\n"));
+        assert!(text.contains("- FP16算力：989.5 TFLOPS\n"));
+        assert!(text.contains("- MIG：支持，最多7个实例\n"));
+        let text = catalog.get("L40S").unwrap().to_string();
+        assert!(text.contains("- NVLink：不支持\n"));
+        // 没有数据的字段不显示
+        assert!(!catalog.get("B200").unwrap().to_string().contains("- CUDA核心："));
+    }
+
+    #[test]
+    fn test_render_formats() {
+        let catalog = GpuCatalog::bundled();
+        let a100 = catalog.get("A100").unwrap();
+
+        let text = a100.render(OutputFormat::Text, Lang::En);
+        assert!(text.starts_with("NVIDIA A100 specifications:\n- Architecture: Ampere\n- Form factor: SXM4\n- CUDA cores: 6,912\n"), "{}", text);
+        assert!(text.contains("- MIG: up to 7 instances\n- SXM4 80GB variant\n"));
+        // A100没有FP8数据
+        assert!(!text.contains("FP8:"));
+
+        let markdown = a100.render(OutputFormat::Markdown, Lang::Zh);
+        assert!(markdown.starts_with("### NVIDIA A100\n\n| 参数 | 值 |\n| --- | --- |\n| 架构 | Ampere架构 |\n"));
+        assert!(markdown.ends_with("\n\n- SXM4 80GB版本\n- 第三代Tensor核心，支持TF32和结构化稀疏\n- 不支持FP8\n"));
+
+        // JSON与语言无关，没有数据的字段为null
+        let json: serde_json::Value = serde_json::from_str(&a100.render(OutputFormat::Json, Lang::En)).unwrap();
+        assert_eq!(json["fp16_tflops"], 312.0);
+        assert!(json["fp8_tflops"].is_null());
+        assert_eq!(serde_json::from_value::<GpuSpec>(json).unwrap(), *a100);
+
+        assert_eq!("MD".parse(), Ok(OutputFormat::Markdown));
+        assert_eq!(" en ".parse(), Ok(Lang::En));
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+
+    fn models(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_compare() {
+        let catalog = GpuCatalog::bundled();
+        let comparison = Comparison::new(&catalog, &models(&["a100", "H100", "B200"])).unwrap();
+        assert_eq!(comparison.baseline().name, "A100");
+        let h100 = comparison.gpus[1];
+        assert!((comparison.ratio(Field::Fp16, h100).unwrap() - 989.5 / 312.0).abs() < 1e-9);
+        // 基准没有FP8数据，B200没有CUDA核心数
+        assert_eq!(comparison.ratio(Field::Fp8, h100), None);
+        assert_eq!(comparison.ratio(Field::CudaCores, comparison.gpus[2]), None);
+        assert_eq!(comparison.missing(comparison.gpus[0]), [Field::Fp8]);
+
+        let markdown = comparison.to_markdown(Lang::Zh);
+        assert!(markdown.starts_with("### GPU对比：A100 vs H100 vs B200\n\n| 参数 | A100 | H100 | B200 |\n| --- | --- | --- | --- |\n"));
+        assert!(markdown.contains("| FP8算力 | 无数据 | 1,979 TFLOPS | 4,500 TFLOPS |\n"));
+        assert!(markdown.contains("| 参数 | H100/A100 | B200/A100 |\n"));
+        assert!(markdown.contains("| FP16算力 | 3.17x | 7.21x |\n"));
+        assert!(markdown.contains("| FP8算力 | 无数据 | 无数据 |\n"));
+        assert!(markdown.ends_with("\n无数据的字段：A100（FP8算力）；B200（CUDA核心、Tensor核心、FP32算力）\n"), "{}", markdown);
+        let markdown = comparison.to_markdown(Lang::En);
+        assert!(markdown.contains("| CUDA cores | 6,912 | 16,896 | n/a |\n"));
+
+        let json = comparison.to_json();
+        assert_eq!(json["models"], json!(["A100", "H100", "B200"]));
+        assert_eq!(json["baseline"], "A100");
+        assert_eq!(json["gpus"][2]["name"], "B200");
+        let fp16 = json["metrics"].as_array().unwrap().iter().find(|m| m["field"] == "fp16_tflops").unwrap();
+        assert_eq!(fp16["values"], json!([312.0, 989.5, 2250.0]));
+        assert_eq!(fp16["ratios"], json!([1.0, 3.17, 7.21]));
+        assert_eq!(json["missing"]["B200"], json!(["cuda_cores", "tensor_cores", "fp32_tflops"]));
+        assert_eq!(json["missing"]["H100"], json!([]));
+    }
+
+    #[test]
+    fn test_compare_rejects_invalid_models() {
+        let catalog = GpuCatalog::bundled();
+        assert!(matches!(
+            Comparison::new(&catalog, &models(&["H100"])),
+            Err(GpuSpecError::InvalidArguments(_))
+        ));
+        assert!(matches!(
+            Comparison::new(&catalog, &models(&["H100", "h100"])),
+            Err(GpuSpecError::InvalidArguments(_))
+        ));
+        assert!(matches!(
+            Comparison::new(&catalog, &models(&["H100", "V100"])),
+            Err(GpuSpecError::UnknownModel { model, .. }) if model == "V100"
+        ));
+    }
+
+    #[test]
+    fn test_unknown_model_lists_known_models() {
+        let catalog = GpuCatalog::bundled();
+        assert_eq!(catalog.lookup("rtx 4090").unwrap().name, "RTX 4090");
+        let error = catalog.lookup(" RTX 4080 ").unwrap_err();
+        let json = error.to_json();
+        assert_eq!(json["type"], "unknown_model");
+        assert_eq!(json["model"], "RTX 4080");
+        assert_eq!(json["suggestions"], json!(["RTX 4090"]));
+        assert_eq!(json["knownModels"][0], "A100");
+        assert_eq!(
+            error.to_string(),
+            "Unknown GPU model \"RTX 4080\" (did you mean RTX 4090?); known models: \
+             A100, A100 40GB, A100 PCIe, H100, H100 PCIe, H200, B200, L40S, RTX 4090"
+        );
+        assert!(catalog.lookup("MI300X").unwrap_err().to_json()["suggestions"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_aliases_and_variants() {
+        let catalog = GpuCatalog::bundled();
+        for (query, expected) in [
+            ("H100 SXM", "H100"),
+            ("h100-80gb", "H100"),
+            ("H100 SXM5 80 GB", "H100"),
+            ("英伟达H100", "H100"),
+            ("nvidia h100 pcie", "H100 PCIe"),
+            ("H100-PCIe-80G", "H100 PCIe"),
+            ("NVIDIA A100 40GB", "A100 40GB"),
+            ("a100_sxm4_40gb", "A100 40GB"),
+            ("A100 80GB PCIe", "A100 PCIe"),
+            ("GeForce RTX 4090", "RTX 4090"),
+            ("4090", "RTX 4090"),
+            ("rtx4090 24gb", "RTX 4090"),
+            ("DGX-B200", "B200"),
+            ("l40-s", "L40S"),
+        ] {
+            assert_eq!(catalog.get(query).map(|gpu| gpu.name.as_str()), Some(expected), "{}", query);
+        }
+        // 没有对应的版本时不匹配
+        for query in ["H100 40GB", "H200 PCIe", "A100 SXM5", "NVIDIA", ""] {
+            assert!(catalog.get(query).is_none(), "{}", query);
+        }
+        assert_eq!(catalog.suggestions("H10")[0], "H100");
+        assert_eq!(catalog.suggestions("L40")[0], "L40S");
+    }
+
+    #[test]
+    fn test_user_catalog_overrides_bundled() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gpus.json");
+        fs::write(
+            &path,
+            r#"[
+                {"name": "h100", "architecture": "Hopper", "memory_gb": 94, "notes": ["NVL版本"]},
+                {"name": "MI300X", "vendor": "AMD", "architecture": "CDNA 3", "memory_gb": 192}
+            ]"#,
+        )
+        .unwrap();
+        let mut catalog = GpuCatalog::bundled();
+        let count = catalog.gpus.len();
+        catalog.merge(GpuCatalog::from_file(&path).unwrap());
+        assert_eq!(catalog.gpus.len(), count + 1);
+        // 同名型号整条替换
+        let h100 = catalog.get("H100").unwrap();
+        assert_eq!((h100.memory_gb, h100.cuda_cores), (Some(94.0), None));
+        assert_eq!(catalog.get("mi300x").unwrap().vendor, "AMD");
+        assert_eq!(catalog.names().last(), Some(&"MI300X"));
+
+        let path = dir.path().join("gpus.toml");
+        fs::write(&path, "[[gpu]]\nname = \"L4\"\narchitecture = \"Ada Lovelace\"\nmemory_gb = 24\n").unwrap();
+        assert_eq!(GpuCatalog::from_file(&path).unwrap().gpus[0].name, "L4");
+    }
+
+    #[test]
+    fn test_invalid_catalog_is_rejected() {
+        for invalid in [
+            "[[gpu]]\nname = \"A\"\n",
+            "[[gpu]]\nname = \"\"\narchitecture = \"x\"\n",
+            "[[gpu]]\nname = \"A\"\narchitecture = \"x\"\nmemory_gb = -1\n",
+            "[[gpu]]\nname = \"A\"\narchitecture = \"x\"\ncolor = \"green\"\n",
+            "[[gpu]]\nname = \"A\"\narchitecture = \"x\"\n[[gpu]]\nname = \"a\"\narchitecture = \"y\"\n",
+        ] {
+            assert!(GpuCatalog::from_toml(invalid).is_err(), "{}", invalid);
+        }
+        let dir = tempfile::tempdir().unwrap();
+        // 别名与其他型号冲突
+        let mut catalog = GpuCatalog::bundled();
+        catalog.merge(GpuCatalog::from_toml("[[gpu]]\nname = \"L4\"\naliases = [\"h-100\"]\narchitecture = \"x\"\n").unwrap());
+        assert!(catalog.validate().is_err());
+        assert!(GpuCatalog::from_toml("[[gpu]]\nname = \"A\"\naliases = [\"-\"]\narchitecture = \"x\"\n").is_err());
+
+        let path = dir.path().join("gpus.yaml");
+        fs::write(&path, "gpu: []").unwrap();
+        assert!(GpuCatalog::from_file(&path).is_err());
+        assert!(GpuCatalog::from_file(&dir.path().join("missing.toml")).is_err());
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(16896.0), "16,896");
+        assert_eq!(format_number(989.5), "989.5");
+        assert_eq!(format_number(1234567.126), "1,234,567.13");
+        assert_eq!(format_number(19.5), "19.5");
+        assert_eq!(format_number(0.0), "0");
+    }
+}
@@ -0,0 +1,361 @@
+//! 后台GPU任务：提交后立即返回任务ID，客户端通过JobStatus、JobResult轮询，
+//! 通过CancelJob取消。
+//!
+//! 任务状态只保存在内存中，服务器重启后清空；任务结束超过TTL后在下次访问时清除。
+//! 运行超过最长时间的任务会被取消并记为超时失败。
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio_util::sync::CancellationToken;
+
+use crate::config::JobsConfig;
+use crate::scheduler::{self, JobStats};
+use crate::server::context::{self, RequestContext};
+use crate::wei_run::WeiRunError;
+
+// CancelJob等待任务结束的最长时间
+const CANCEL_WAIT: Duration = Duration::from_secs(5);
+
+/// 查询、取消后台任务或取得结果时的错误
+#[derive(Debug, thiserror::Error)]
+pub enum JobError {
+    /// 工具参数不合法
+    #[error("Invalid arguments: {0}")]
+    InvalidArguments(String),
+    /// 没有这个后台任务，或者任务已经过期被清除
+    #[error("Job {0} not found or expired")]
+    NotFound(String),
+    /// 后台任务还没有结束
+    #[error("Job {job_id} is still {status}")]
+    NotFinished { job_id: String, status: String },
+    /// 后台任务失败或被取消，`error` 为任务本身的错误
+    #[error("Job {job_id} failed: {}", .error["message"].as_str().unwrap_or_default())]
+    Failed { job_id: String, error: Value },
+}
+
+impl JobError {
+    /// 错误类型的稳定名称，供客户端区分错误
+    pub fn kind(&self) -> &'static str {
+        match self {
+            JobError::InvalidArguments(_) => "invalid_arguments",
+            JobError::NotFound(_) => "job_not_found",
+            JobError::NotFinished { .. } => "job_not_finished",
+            JobError::Failed { .. } => "job_failed",
+        }
+    }
+
+    /// 结构化的错误信息，放在工具响应的 `_meta.error` 中
+    pub fn to_json(&self) -> Value {
+        let mut error = json!({
+            "type": self.kind(),
+            "message": self.to_string(),
+        });
+        match self {
+            JobError::NotFound(job_id) => {
+                error["jobId"] = json!(job_id);
+            }
+            JobError::NotFinished { job_id, status } => {
+                error["jobId"] = json!(job_id);
+                error["status"] = json!(status);
+            }
+            JobError::Failed { job_id, error: cause } => {
+                error["jobId"] = json!(job_id);
+                error["cause"] = cause.clone();
+            }
+            JobError::InvalidArguments(_) => {}
+        }
+        error
+    }
+}
+
+/// 任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobState {
+    pub fn name(&self) -> &'static str {
+        match self {
+            JobState::Running => "running",
+            JobState::Succeeded => "succeeded",
+            JobState::Failed => "failed",
+            JobState::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// JobStatus返回的任务信息，时间为Unix时间戳（秒）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JobInfo {
+    pub job_id: String,
+    /// 任务对应的wei-run命令，如 `generate`
+    pub kind: String,
+    pub status: JobState,
+    pub submitted_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<u64>,
+    /// 在GPU任务队列中等待的时间
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queue_wait_ms: Option<u64>,
+    /// 失败或取消时的错误，格式同工具响应的 `_meta.error`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<Value>,
+}
+
+struct Job {
+    info: JobInfo,
+    output: Option<String>,
+    cancel: CancellationToken,
+    // 任务结束时触发
+    done: CancellationToken,
+    finished: Option<Instant>,
+}
+
+/// 内存中的任务表，以任务ID为键
+pub struct JobStore {
+    jobs: BTreeMap<String, Job>,
+    ttl: Duration,
+    max_runtime: Duration,
+}
+
+impl JobStore {
+    pub const fn new(ttl: Duration, max_runtime: Duration) -> Self {
+        JobStore {
+            jobs: BTreeMap::new(),
+            ttl,
+            max_runtime,
+        }
+    }
+
+    fn insert(&mut self, job_id: &str, kind: &str, cancel: CancellationToken, done: CancellationToken, now: u64) -> JobInfo {
+        let info = JobInfo {
+            job_id: job_id.to_string(),
+            kind: kind.to_string(),
+            status: JobState::Running,
+            submitted_at: now,
+            finished_at: None,
+            queue_wait_ms: None,
+            error: None,
+        };
+        self.jobs.insert(
+            job_id.to_string(),
+            Job {
+                info: info.clone(),
+                output: None,
+                cancel,
+                done,
+                finished: None,
+            },
+        );
+        info
+    }
+
+    /// 记录任务结果
+    fn finish(&mut self, job_id: &str, result: Result<String, WeiRunError>, stats: JobStats, now: u64, instant: Instant) {
+        let Some(job) = self.jobs.get_mut(job_id) else {
+            return;
+        };
+        match result {
+            Ok(output) => {
+                job.info.status = JobState::Succeeded;
+                job.output = Some(output);
+            }
+            Err(e) => {
+                job.info.status = match e {
+                    WeiRunError::Cancelled => JobState::Cancelled,
+                    _ => JobState::Failed,
+                };
+                job.info.error = Some(e.to_json());
+            }
+        }
+        if stats.jobs > 0 {
+            job.info.queue_wait_ms = Some(stats.queue_wait.as_millis() as u64);
+        }
+        job.info.finished_at = Some(now);
+        job.finished = Some(instant);
+    }
+
+    // 清除结束时间超过TTL的任务
+    fn purge(&mut self, now: Instant) {
+        let ttl = self.ttl;
+        self.jobs
+            .retain(|_, job| job.finished.is_none_or(|finished| now.duration_since(finished) < ttl));
+    }
+
+    pub fn info(&mut self, job_id: &str, now: Instant) -> Option<JobInfo> {
+        self.purge(now);
+        self.jobs.get(job_id).map(|job| job.info.clone())
+    }
+
+    /// 任务信息和成功时的输出
+    pub fn output(&mut self, job_id: &str, now: Instant) -> Option<(JobInfo, Option<String>)> {
+        self.purge(now);
+        self.jobs
+            .get(job_id)
+            .map(|job| (job.info.clone(), job.output.clone()))
+    }
+
+    fn tokens(&mut self, job_id: &str, now: Instant) -> Option<(CancellationToken, CancellationToken)> {
+        self.purge(now);
+        self.jobs
+            .get(job_id)
+            .map(|job| (job.cancel.clone(), job.done.clone()))
+    }
+}
+
+static JOBS: Mutex<JobStore> = Mutex::new(JobStore::new(Duration::from_secs(3600), Duration::from_secs(3600)));
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// 按配置设置任务结果的保留时间和任务的最长运行时间
+pub fn configure(config: &JobsConfig) {
+    if let Ok(mut jobs) = JOBS.lock() {
+        jobs.ttl = Duration::from_secs(config.ttl_secs);
+        jobs.max_runtime = Duration::from_secs(config.max_runtime_secs);
+    }
+}
+
+/// 在后台执行任务，立即返回任务信息。
+///
+/// 任务在独立的上下文中执行，不受提交它的请求被取消的影响，只能通过 [`cancel`] 取消。
+pub fn submit<F>(kind: &str, job: F) -> JobInfo
+where
+    F: Future<Output = Result<String, WeiRunError>> + Send + 'static,
+{
+    submit_to(&JOBS, kind, job)
+}
+
+// 在指定的任务表中登记并执行任务
+fn submit_to<F>(store: &'static Mutex<JobStore>, kind: &str, job: F) -> JobInfo
+where
+    F: Future<Output = Result<String, WeiRunError>> + Send + 'static,
+{
+    let job_id = uuid::Uuid::new_v4().to_string();
+    let cancel = CancellationToken::new();
+    let done = CancellationToken::new();
+    let (info, max_runtime) = {
+        let mut jobs = store.lock().unwrap();
+        let info = jobs.insert(&job_id, kind, cancel.clone(), done.clone(), now());
+        (info, jobs.max_runtime)
+    };
+
+    tokio::spawn(async move {
+        let run = context::scope(RequestContext::detached(cancel.clone()), scheduler::track(job));
+        tokio::pin!(run);
+        let (result, stats) = tokio::select! {
+            output = &mut run => output,
+            _ = tokio::time::sleep(max_runtime) => {
+                // 超过最长运行时间：取消任务，等它终止wei-run后记为超时
+                cancel.cancel();
+                let (_, stats) = run.await;
+                (Err(WeiRunError::Timeout(max_runtime)), stats)
+            }
+        };
+        if let Err(e) = &result {
+            tracing::warn!("后台任务 {} 执行失败: {}", job_id, e);
+        }
+        store
+            .lock()
+            .unwrap()
+            .finish(&job_id, result, stats, now(), Instant::now());
+        done.cancel();
+    });
+    info
+}
+
+pub fn status(job_id: &str) -> Option<JobInfo> {
+    JOBS.lock().ok()?.info(job_id, Instant::now())
+}
+
+/// 任务信息和成功时的输出
+pub fn output(job_id: &str) -> Option<(JobInfo, Option<String>)> {
+    JOBS.lock().ok()?.output(job_id, Instant::now())
+}
+
+/// 取消任务并等待它结束，返回取消后的状态；已经结束的任务保持原状态
+pub async fn cancel(job_id: &str) -> Option<JobInfo> {
+    let (cancel, done) = JOBS.lock().ok()?.tokens(job_id, Instant::now())?;
+    cancel.cancel();
+    let _ = tokio::time::timeout(CANCEL_WAIT, done.cancelled()).await;
+    status(job_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store_with_job(ttl: Duration) -> JobStore {
+        let mut store = JobStore::new(ttl, Duration::from_secs(3600));
+        store.insert("a", "generate", CancellationToken::new(), CancellationToken::new(), 100);
+        store
+    }
+
+    #[test]
+    fn test_finish_records_outcome() {
+        let start = Instant::now();
+        let mut store = store_with_job(Duration::from_secs(60));
+        assert_eq!(store.info("a", start).unwrap().status, JobState::Running);
+
+        let stats = JobStats {
+            jobs: 1,
+            queue_wait: Duration::from_millis(250),
+        };
+        store.finish("a", Ok("hello".to_string()), stats, 105, start);
+        let (info, output) = store.output("a", start).unwrap();
+        assert_eq!(info.status, JobState::Succeeded);
+        assert_eq!(info.finished_at, Some(105));
+        assert_eq!(info.queue_wait_ms, Some(250));
+        assert_eq!(output.as_deref(), Some("hello"));
+
+        let mut store = store_with_job(Duration::from_secs(60));
+        store.finish("a", Err(WeiRunError::Cancelled), JobStats::default(), 105, start);
+        let info = store.info("a", start).unwrap();
+        assert_eq!(info.status, JobState::Cancelled);
+        assert_eq!(info.error.unwrap()["type"], "cancelled");
+        // 没有获得运行名额时不报告排队时间
+        assert_eq!(info.queue_wait_ms, None);
+    }
+
+    #[test]
+    fn test_finished_jobs_expire_after_ttl() {
+        let start = Instant::now();
+        let mut store = store_with_job(Duration::from_secs(60));
+        store.insert("b", "generate", CancellationToken::new(), CancellationToken::new(), 100);
+        store.finish("a", Err(WeiRunError::InvalidOutput("x".to_string())), JobStats::default(), 100, start);
+
+        assert!(store.info("a", start + Duration::from_secs(59)).is_some());
+        assert!(store.info("a", start + Duration::from_secs(60)).is_none());
+        // 运行中的任务不会过期
+        assert!(store.info("b", start + Duration::from_secs(3600)).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_running_jobs_time_out_after_max_runtime() {
+        // 使用独立的任务表，不改动全局配置
+        static STORE: Mutex<JobStore> = Mutex::new(JobStore::new(Duration::from_secs(60), Duration::from_secs(1)));
+        let info = submit_to(&STORE, "generate", async {
+            context::cancellation_token().cancelled().await;
+            Err(WeiRunError::Cancelled)
+        });
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+
+        let info = STORE.lock().unwrap().info(&info.job_id, Instant::now()).unwrap();
+        assert_eq!(info.status, JobState::Failed);
+        assert_eq!(info.error.unwrap()["type"], "timeout");
+    }
+}
@@ -0,0 +1,16 @@
+pub mod config;
+pub mod embedding;
+pub mod gpu_specs;
+pub mod jobs;
+pub mod local_gpus;
+pub mod migrations;
+pub mod model_fit;
+pub mod models;
+pub mod prompts;
+pub mod registry;
+pub mod resources;
+pub mod scheduler;
+pub mod server;
+pub mod suppliers;
+pub mod tools;
+pub mod wei_run;
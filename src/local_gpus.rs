@@ -0,0 +1,375 @@
+//! 本机GPU清单，读取 `nvidia-smi --query-gpu=... --format=csv` 的输出。
+//!
+//! nvidia-smi按 `NVIDIA_SMI` 环境变量、PATH的顺序查找，与wei-run一样通过
+//! [`wei_run::run`] 执行，受相同的超时和取消控制。PATH中找不到nvidia-smi，或者它报告没有设备、
+//! 驱动未加载时，返回空的清单而不是错误；`NVIDIA_SMI` 指向不存在的文件时返回错误。
+
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::json;
+use tokio_util::sync::CancellationToken;
+
+use crate::gpu_specs::{format_number, markdown_cell, Lang};
+use crate::wei_run::{self, WeiRunError};
+
+/// 指定nvidia-smi路径的环境变量
+pub const NVIDIA_SMI_ENV: &str = "NVIDIA_SMI";
+
+/// 查询的字段，没有表头时按此顺序解析
+pub const QUERY_FIELDS: [&str; 6] = [
+    "index",
+    "name",
+    "memory.total",
+    "memory.used",
+    "utilization.gpu",
+    "driver_version",
+];
+
+/// 表示没有GPU的nvidia-smi退出码：没有设备、驱动未加载、找不到NVML库
+const NO_GPU_EXIT_CODES: [i32; 3] = [6, 9, 12];
+
+/// 本机的一块GPU，nvidia-smi报告为 `[N/A]` 或 `[Not Supported]` 的字段为None
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LocalGpu {
+    pub index: u32,
+    pub name: String,
+    pub memory_total_mib: Option<u64>,
+    pub memory_used_mib: Option<u64>,
+    pub utilization_percent: Option<u32>,
+    pub driver_version: Option<String>,
+}
+
+// 去掉单位和方括号中的说明，空值和不支持的字段为None
+fn value(field: &str) -> Option<&str> {
+    let field = field.trim();
+    if field.is_empty() || field.starts_with('[') || field.eq_ignore_ascii_case("N/A") {
+        return None;
+    }
+    Some(field.trim_end_matches("MiB").trim_end_matches('%').trim_end())
+}
+
+fn number<T: std::str::FromStr>(field: &str, column: &str, line: &str) -> Result<Option<T>, WeiRunError> {
+    value(field)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| WeiRunError::InvalidOutput(format!("invalid {} {:?} in line {:?}", column, value, line)))
+        })
+        .transpose()
+}
+
+/// 解析 `--format=csv` 的输出，带不带表头和单位（`noheader`、`nounits`）都可以。
+///
+/// 有表头时按表头找列，列的顺序不限；型号中的逗号会合并回型号。
+pub fn parse_csv(output: &str) -> Result<Vec<LocalGpu>, WeiRunError> {
+    let mut lines = output.lines().map(str::trim).filter(|line| !line.is_empty()).peekable();
+    let columns_of = |line: &str| -> Vec<String> {
+        line.split(',')
+            .map(|column| column.split('[').next().unwrap_or_default().trim().to_string())
+            .collect()
+    };
+    // 第一行中有字段名时是表头
+    let header = match lines.peek().map(|first| columns_of(first)) {
+        Some(header) if header.iter().any(|column| QUERY_FIELDS.contains(&column.as_str())) => {
+            lines.next();
+            header
+        }
+        _ => QUERY_FIELDS.iter().map(|column| column.to_string()).collect(),
+    };
+    let position = |column: &str| {
+        header
+            .iter()
+            .position(|name| name == column)
+            .ok_or_else(|| WeiRunError::InvalidOutput(format!("missing column {:?} in nvidia-smi output", column)))
+    };
+    let columns: Vec<usize> = QUERY_FIELDS.iter().map(|column| position(column)).collect::<Result<_, _>>()?;
+    let name_column = columns[1];
+
+    let mut gpus = Vec::new();
+    for line in lines {
+        let mut fields: Vec<&str> = line.split(',').collect();
+        if fields.len() < header.len() {
+            return Err(WeiRunError::InvalidOutput(format!(
+                "expected {} columns in line {:?}",
+                header.len(),
+                line
+            )));
+        }
+        // 多出的逗号属于型号
+        let extra = fields.len() - header.len();
+        let name = fields[name_column..=name_column + extra].join(",");
+        fields.splice(name_column..=name_column + extra, [name.as_str()]);
+
+        let field = |i: usize| fields[columns[i]];
+        gpus.push(LocalGpu {
+            index: number(field(0), "index", line)?
+                .ok_or_else(|| WeiRunError::InvalidOutput(format!("missing index in line {:?}", line)))?,
+            name: field(1).trim().to_string(),
+            memory_total_mib: number(field(2), "memory.total", line)?,
+            memory_used_mib: number(field(3), "memory.used", line)?,
+            utilization_percent: number(field(4), "utilization.gpu", line)?,
+            driver_version: value(field(5)).map(str::to_string),
+        });
+    }
+    Ok(gpus)
+}
+
+/// 按 `NVIDIA_SMI`、PATH的顺序查找nvidia-smi，找不到时为None
+pub fn locate(env_path: Option<PathBuf>, path_var: Option<OsString>) -> Option<PathBuf> {
+    if let Some(path) = env_path {
+        return Some(path);
+    }
+    let name = format!("nvidia-smi{}", env::consts::EXE_SUFFIX);
+    env::split_paths(&path_var?)
+        .map(|dir| dir.join(&name))
+        .find(|candidate| wei_run::is_executable(candidate))
+}
+
+/// 执行nvidia-smi并解析输出，nvidia-smi报告没有GPU时返回空列表
+pub async fn query(path: &Path, timeout: Duration, cancel: &CancellationToken) -> Result<Vec<LocalGpu>, WeiRunError> {
+    let query = format!("--query-gpu={}", QUERY_FIELDS.join(","));
+    match wei_run::run(path, &query, &["--format=csv"], timeout, cancel).await {
+        Ok(output) => parse_csv(&output),
+        Err(WeiRunError::NonZeroExit { exit_code: Some(code), .. }) if NO_GPU_EXIT_CODES.contains(&code) => {
+            Ok(Vec::new())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// 查询本机的GPU，PATH中找不到nvidia-smi时返回空列表
+pub async fn list(timeout: Duration, cancel: &CancellationToken) -> Result<Vec<LocalGpu>, WeiRunError> {
+    let env_path = env::var_os(NVIDIA_SMI_ENV)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from);
+    list_with(env_path, env::var_os("PATH"), timeout, cancel).await
+}
+
+/// `NVIDIA_SMI` 指定的路径不存在时返回NotFound，而不是当作没有GPU
+async fn list_with(
+    env_path: Option<PathBuf>,
+    path_var: Option<OsString>,
+    timeout: Duration,
+    cancel: &CancellationToken,
+) -> Result<Vec<LocalGpu>, WeiRunError> {
+    let explicit = env_path.is_some();
+    match locate(env_path, path_var) {
+        Some(path) => match query(&path, timeout, cancel).await {
+            Err(WeiRunError::NotFound(_)) if !explicit => Ok(Vec::new()),
+            result => result,
+        },
+        None => Ok(Vec::new()),
+    }
+}
+
+/// 结构化的清单，没有GPU时 `gpus` 为空数组并附带说明
+pub fn to_json(gpus: &[LocalGpu]) -> serde_json::Value {
+    let mut result = json!({
+        "count": gpus.len(),
+        "gpus": gpus,
+    });
+    if gpus.is_empty() {
+        result["message"] = json!("No GPUs found");
+    }
+    result
+}
+
+fn format_mib(value: Option<u64>, missing: &str) -> String {
+    value
+        .map(|value| format!("{} MiB", format_number(value as f64)))
+        .unwrap_or_else(|| missing.to_string())
+}
+
+/// Markdown表格，没有GPU时只有一行说明
+pub fn to_markdown(gpus: &[LocalGpu], lang: Lang) -> String {
+    let missing = match lang {
+        Lang::Zh => "无数据",
+        Lang::En => "n/a",
+    };
+    if gpus.is_empty() {
+        return match lang {
+            Lang::Zh => "本机没有检测到GPU".to_string(),
+            Lang::En => "No GPUs found on this host".to_string(),
+        };
+    }
+    let mut out = match lang {
+        Lang::Zh => format!(
+            "### 本机GPU（{}块）\n\n| 编号 | 型号 | 显存总量 | 已用显存 | 利用率 | 驱动版本 |\n",
+            gpus.len()
+        ),
+        Lang::En => format!(
+            "### Local GPUs ({})\n\n| Index | Name | Memory total | Memory used | Utilization | Driver |\n",
+            gpus.len()
+        ),
+    };
+    out.push_str("| --- | --- | --- | --- | --- | --- |\n");
+    for gpu in gpus {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} |\n",
+            gpu.index,
+            markdown_cell(&gpu.name),
+            format_mib(gpu.memory_total_mib, missing),
+            format_mib(gpu.memory_used_mib, missing),
+            gpu.utilization_percent
+                .map(|percent| format!("{}%", percent))
+                .unwrap_or_else(|| missing.to_string()),
+            gpu.driver_version.as_deref().unwrap_or(missing),
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const H100X2: &str = include_str!("../tests/fixtures/nvidia-smi/h100x2.csv");
+    const MIXED: &str = include_str!("../tests/fixtures/nvidia-smi/mixed.csv");
+    const EMPTY: &str = include_str!("../tests/fixtures/nvidia-smi/empty.csv");
+    const NOHEADER_NOUNITS: &str = include_str!("../tests/fixtures/nvidia-smi/noheader_nounits.csv");
+
+    #[test]
+    fn test_parse_csv() {
+        let gpus = parse_csv(H100X2).unwrap();
+        assert_eq!(gpus.len(), 2);
+        assert_eq!(
+            gpus[0],
+            LocalGpu {
+                index: 0,
+                name: "NVIDIA H100 80GB HBM3".to_string(),
+                memory_total_mib: Some(81559),
+                memory_used_mib: Some(71235),
+                utilization_percent: Some(98),
+                driver_version: Some("535.129.03".to_string()),
+            }
+        );
+        assert_eq!(gpus[1].utilization_percent, Some(0));
+
+        let gpus = parse_csv(NOHEADER_NOUNITS).unwrap();
+        assert_eq!(gpus[0].name, "Tesla T4");
+        assert_eq!(gpus[0].memory_total_mib, Some(15360));
+
+        assert!(parse_csv(EMPTY).unwrap().is_empty());
+        assert!(parse_csv("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_csv_unsupported_fields() {
+        let gpus = parse_csv(MIXED).unwrap();
+        assert_eq!(gpus[1].name, "NVIDIA A100-SXM4-40GB");
+        assert_eq!(gpus[1].memory_used_mib, None);
+        assert_eq!(gpus[1].utilization_percent, None);
+        assert_eq!(gpus[1].driver_version.as_deref(), Some("550.54.14"));
+    }
+
+    #[test]
+    fn test_parse_csv_reordered_columns_and_comma_in_name() {
+        let output = "name, index, driver_version, utilization.gpu [%], memory.used [MiB], memory.total [MiB]\n\
+                      Quadro RTX 8000, Rev A, 3, 550.54.14, 5 %, 10 MiB, 49152 MiB\n";
+        let gpus = parse_csv(output).unwrap();
+        assert_eq!(gpus[0].name, "Quadro RTX 8000, Rev A");
+        assert_eq!(gpus[0].index, 3);
+        assert_eq!(gpus[0].memory_total_mib, Some(49152));
+    }
+
+    #[test]
+    fn test_parse_csv_invalid_output() {
+        for output in [
+            "0, Tesla T4, 15360 MiB\n",
+            "x, Tesla T4, 15360, 0, 0, 470.82.01\n",
+            "0, Tesla T4, lots, 0, 0, 470.82.01\n",
+            "index, name\n0, Tesla T4\n",
+        ] {
+            assert!(matches!(parse_csv(output), Err(WeiRunError::InvalidOutput(_))), "{:?}", output);
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let gpus = parse_csv(MIXED).unwrap();
+        let markdown = to_markdown(&gpus, Lang::Zh);
+        assert!(markdown.starts_with("### 本机GPU（2块）\n"));
+        assert!(markdown.contains("| 0 | NVIDIA GeForce RTX 4090 | 24,564 MiB | 1,130 MiB | 12% | 550.54.14 |"));
+        assert!(markdown.contains("| 40,960 MiB | 无数据 | 无数据 |"));
+
+        let json = to_json(&gpus);
+        assert_eq!(json["count"], 2);
+        assert_eq!(json["gpus"][1]["memory_used_mib"], serde_json::Value::Null);
+        assert!(json.get("message").is_none());
+
+        assert_eq!(to_markdown(&[], Lang::En), "No GPUs found on this host");
+        assert_eq!(to_json(&[]), json!({ "count": 0, "gpus": [], "message": "No GPUs found" }));
+    }
+
+    #[test]
+    fn test_locate() {
+        assert_eq!(locate(None, None), None);
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(locate(None, Some(dir.path().as_os_str().to_owned())), None);
+        let explicit = PathBuf::from("/opt/nvidia/nvidia-smi");
+        assert_eq!(locate(Some(explicit.clone()), None), Some(explicit));
+    }
+
+    #[cfg(unix)]
+    fn fake_nvidia_smi(dir: &Path, name: &str, script: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join(name);
+        std::fs::write(&path, script).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_query_runs_nvidia_smi() {
+        let dir = tempfile::tempdir().unwrap();
+        let timeout = Duration::from_secs(5);
+        let cancel = CancellationToken::new();
+
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/nvidia-smi/h100x2.csv");
+        let script = format!(
+            "#!/bin/sh\n[ \"$1\" = \"--query-gpu={}\" ] && [ \"$2\" = \"--format=csv\" ] || exit 2\ncat '{}'\n",
+            QUERY_FIELDS.join(","),
+            fixture.display()
+        );
+        let path = fake_nvidia_smi(dir.path(), "nvidia-smi", &script);
+        assert_eq!(query(&path, timeout, &cancel).await.unwrap().len(), 2);
+
+        // 没有设备时nvidia-smi以6退出
+        let path = fake_nvidia_smi(dir.path(), "no-devices", "#!/bin/sh\necho 'No devices were found'\nexit 6\n");
+        assert!(query(&path, timeout, &cancel).await.unwrap().is_empty());
+
+        let path = fake_nvidia_smi(dir.path(), "no-permission", "#!/bin/sh\necho 'Insufficient Permissions' >&2\nexit 4\n");
+        assert!(matches!(
+            query(&path, timeout, &cancel).await,
+            Err(WeiRunError::NonZeroExit { exit_code: Some(4), .. })
+        ));
+
+        assert!(matches!(
+            query(&dir.path().join("missing"), timeout, &cancel).await,
+            Err(WeiRunError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_list_missing_nvidia_smi() {
+        let dir = tempfile::tempdir().unwrap();
+        let timeout = Duration::from_secs(5);
+        let cancel = CancellationToken::new();
+
+        // PATH中没有nvidia-smi时当作没有GPU
+        let path_var = Some(dir.path().as_os_str().to_owned());
+        assert!(list_with(None, path_var.clone(), timeout, &cancel).await.unwrap().is_empty());
+
+        // 显式指定的路径不存在是配置错误
+        let explicit = Some(dir.path().join("missing"));
+        assert!(matches!(
+            list_with(explicit, path_var, timeout, &cancel).await,
+            Err(WeiRunError::NotFound(_))
+        ));
+    }
+}
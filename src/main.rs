@@ -1,25 +1,173 @@
 use anyhow::Result;
-use mcp_core::types::ClientCapabilities;
-use mcp_core::types::Implementation;
-use mcp_core::{
-    client::ClientBuilder, server::Server, transport::ServerSseTransport, types::ServerCapabilities,
-};
-use serde_json::json;
+use clap::{Parser, Subcommand};
+use rig_mcp_server::config::{Config, TransportMode};
+use rig_mcp_server::gpu_specs;
+use rig_mcp_server::jobs;
+use rig_mcp_server::prompts;
+use rig_mcp_server::registry::{ToolGroup, ToolGroups, ToolRegistry};
+use rig_mcp_server::scheduler;
+use rig_mcp_server::server;
+use rig_mcp_server::suppliers;
+use rig_mcp_server::tools::wei_run_self_check;
+use rig_mcp_server::wei_run;
 use std::fs;
 use std::net::TcpListener;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-mod tools;
-use tools::*;
+/// 命令行参数，优先级高于配置文件和环境变量
+#[derive(Debug, Parser)]
+#[command(name = "rig-mcp-server", version, about = "Wei MCP服务器")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
 
-use rig::{
-    completion::Prompt,
-    providers::{self},
-};
+    /// TOML配置文件路径，默认读取当前目录下的wei-server-mcp.toml
+    #[arg(long, short, global = true)]
+    config: Option<PathBuf>,
+
+    /// 传输方式：stdio或sse
+    #[arg(long)]
+    transport: Option<TransportMode>,
+
+    /// SSE模式监听的地址
+    #[arg(long)]
+    host: Option<String>,
+
+    /// 起始端口
+    #[arg(long, short)]
+    port: Option<u16>,
+
+    /// 查找可用端口的上限
+    #[arg(long)]
+    max_port: Option<u16>,
+
+    /// 记录实际使用端口的状态文件
+    #[arg(long)]
+    state_file: Option<PathBuf>,
+
+    /// 启用的工具分组，逗号分隔，例如 math,gpu-specs,wei-gpu
+    #[arg(long)]
+    tools: Option<ToolGroups>,
+
+    /// wei-run可执行文件路径
+    #[arg(long)]
+    wei_run: Option<PathBuf>,
+
+    /// 单次wei-run调用的超时时间（秒）
+    #[arg(long)]
+    wei_run_timeout: Option<u64>,
+
+    /// 同时运行的GPU任务数量上限
+    #[arg(long)]
+    max_jobs: Option<usize>,
+
+    /// 排队等待的GPU任务数量上限
+    #[arg(long)]
+    max_queue: Option<usize>,
+
+    /// 后台任务结束后保留结果的时间（秒）
+    #[arg(long)]
+    job_ttl: Option<u64>,
+
+    /// 后台任务的最长运行时间（秒）
+    #[arg(long)]
+    job_max_runtime: Option<u64>,
+
+    /// 供应商SQLite数据库文件
+    #[arg(long, global = true)]
+    supplier_db: Option<PathBuf>,
+
+    /// 供应商导出文件的目录
+    #[arg(long)]
+    export_dir: Option<PathBuf>,
+
+    /// 用户的GPU规格文件（TOML或JSON），覆盖或补充内置目录
+    #[arg(long)]
+    gpu_catalog: Option<PathBuf>,
+
+    /// 提示词模板目录
+    #[arg(long)]
+    prompts_dir: Option<PathBuf>,
+
+    /// 日志级别：trace、debug、info、warn或error
+    #[arg(long)]
+    log_level: Option<String>,
+
+    /// 打印合并后的有效配置并退出
+    #[arg(long)]
+    print_config: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// 应用供应商数据库尚未应用的迁移后退出（服务器启动时也会自动应用）
+    Migrate {
+        /// 只显示已应用和待应用的迁移，不修改数据库
+        #[arg(long)]
+        status: bool,
+    },
+}
+
+impl Cli {
+    // 用命令行参数覆盖配置
+    fn apply(&self, config: &mut Config) {
+        if let Some(transport) = self.transport {
+            config.server.transport = transport;
+        }
+        if let Some(host) = &self.host {
+            config.server.host = host.clone();
+        }
+        if let Some(port) = self.port {
+            config.server.port = port;
+        }
+        if let Some(max_port) = self.max_port {
+            config.server.max_port = max_port;
+        }
+        if let Some(state_file) = &self.state_file {
+            config.server.state_file = state_file.clone();
+        }
+        if let Some(tools) = &self.tools {
+            config.tools.enabled = tools.clone();
+        }
+        if let Some(wei_run) = &self.wei_run {
+            config.wei_run.path = Some(wei_run.clone());
+        }
+        if let Some(timeout) = self.wei_run_timeout {
+            config.wei_run.timeout_secs = timeout;
+        }
+        if let Some(max_jobs) = self.max_jobs {
+            config.scheduler.max_concurrent_jobs = max_jobs;
+        }
+        if let Some(max_queue) = self.max_queue {
+            config.scheduler.max_queue_depth = max_queue;
+        }
+        if let Some(job_ttl) = self.job_ttl {
+            config.jobs.ttl_secs = job_ttl;
+        }
+        if let Some(max_runtime) = self.job_max_runtime {
+            config.jobs.max_runtime_secs = max_runtime;
+        }
+        if let Some(supplier_db) = &self.supplier_db {
+            config.suppliers.db_path = supplier_db.clone();
+        }
+        if let Some(export_dir) = &self.export_dir {
+            config.suppliers.export_dir = export_dir.clone();
+        }
+        if let Some(gpu_catalog) = &self.gpu_catalog {
+            config.gpu_specs.catalog = Some(gpu_catalog.clone());
+        }
+        if let Some(prompts_dir) = &self.prompts_dir {
+            config.prompts.dir = prompts_dir.clone();
+        }
+        if let Some(log_level) = &self.log_level {
+            config.log.level = log_level.clone();
+        }
+    }
+}
 
 // 从文件读取端口号，如果文件存在
-fn read_port_from_file() -> Option<u16> {
-    if let Ok(contents) = fs::read_to_string("wei-server-mcp.dat") {
+fn read_port_from_file(path: &Path) -> Option<u16> {
+    if let Ok(contents) = fs::read_to_string(path) {
         if let Ok(port) = contents.trim().parse::<u16>() {
             return Some(port);
         }
@@ -28,38 +176,121 @@ fn read_port_from_file() -> Option<u16> {
 }
 
 // 保存端口号到文件
-fn save_port_to_file(port: u16) -> Result<(), std::io::Error> {
-    fs::write("wei-server-mcp.dat", port.to_string())
+fn save_port_to_file(path: &Path, port: u16) -> Result<(), std::io::Error> {
+    fs::write(path, port.to_string())
 }
 
 // 检查端口是否可用
-fn is_port_available(port: u16) -> bool {
-    TcpListener::bind(format!("127.0.0.1:{}", port)).is_ok()
+fn is_port_available(host: &str, port: u16) -> bool {
+    TcpListener::bind((host, port)).is_ok()
 }
 
 // 查找可用端口
-fn find_available_port(start_port: u16) -> Option<u16> {
-    let mut port = start_port;
-    let max_port = 65535;
-    
-    while port <= max_port {
-        if is_port_available(port) {
-            return Some(port);
+fn find_available_port(host: &str, start_port: u16, max_port: u16) -> Option<u16> {
+    (start_port..=max_port).find(|&port| is_port_available(host, port))
+}
+
+// migrate子命令：迁移结果写到stdout
+fn run_migrate(config: &Config, status_only: bool) -> Result<()> {
+    let path = config.suppliers.db_path.as_path();
+    println!("供应商数据库: {}", path.display());
+    if status_only {
+        let status = suppliers::migration_status(path)?;
+        print!("{}", status);
+        if status.is_too_new() {
+            anyhow::bail!("数据库版本 {} 高于本程序支持的版本 {}", status.current, status.latest);
         }
-        port += 1;
+        return Ok(());
+    }
+    let applied = suppliers::migrate(path)?;
+    if applied.is_empty() {
+        println!("没有待应用的迁移");
+    } else {
+        println!("已应用迁移: {:?}", applied);
+    }
+    Ok(())
+}
+
+// 启动自检：报告使用的wei-run及其版本，找不到时只给出警告
+async fn check_wei_run() {
+    match wei_run::locate() {
+        Ok(location) => match wei_run_self_check().await {
+            Ok((_, version)) => tracing::info!(
+                "使用wei-run: {} (来源: {})，版本: {}",
+                location.path.display(),
+                location.source,
+                version
+            ),
+            Err(e) => tracing::warn!(
+                "wei-run {} (来源: {}) 执行version失败: {}",
+                location.path.display(),
+                location.source,
+                e
+            ),
+        },
+        Err(e) => tracing::warn!("{}，wei-gpu分组的工具将无法使用", e),
     }
-    None
 }
 
 #[tokio::main]
 async fn main()->Result<(), anyhow::Error>  {
-    tracing_subscriber::fmt::init();
-    
-    // 初始端口，优先从文件读取，否则默认使用1116
-    let initial_port = read_port_from_file().unwrap_or(1116);
+    let cli = Cli::parse();
+    let mut config = Config::load(cli.config.as_deref())?;
+    cli.apply(&mut config);
+    config.validate()?;
+
+    if cli.print_config {
+        print!("{}", config.to_toml()?);
+        return Ok(());
+    }
+    if let Some(Command::Migrate { status }) = cli.command {
+        return run_migrate(&config, status);
+    }
+
+    // 日志统一写到stderr，stdio模式下stdout只用于协议消息
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(config.log_level()?)
+        .init();
+
+    wei_run::configure(&config.wei_run);
+    scheduler::configure(&config.scheduler);
+    jobs::configure(&config.jobs);
+    suppliers::configure(&config.suppliers);
+    gpu_specs::configure(&config.gpu_specs)?;
+    if config.tools.enabled.is_enabled(ToolGroup::WeiGpu) {
+        check_wei_run().await;
+    }
+    // 已有的供应商数据库在启动时打开并应用迁移，迁移失败或数据库版本高于本程序时拒绝启动；
+    // 数据库不存在时不在启动时创建，第一次使用时才创建
+    if config.tools.enabled.is_enabled(ToolGroup::Suppliers) && config.suppliers.db_path.exists() {
+        suppliers::with_store(|_| Ok(())).await?;
+    }
+
+    let templates = prompts::load_dir(&config.prompts.dir)?;
+    if !templates.is_empty() {
+        tracing::info!("从 {} 加载了 {} 个提示词模板", config.prompts.dir.display(), templates.len());
+    }
+
+    let router = ToolRegistry::new()
+        .build_router(&config.server.name, &config.server.version, &config.tools.enabled)
+        .prompts(templates);
+
+    if config.server.transport == TransportMode::Stdio {
+        tracing::info!("服务器以stdio模式启动");
+        return server::stdio::serve(router).await;
+    }
+
+    let host = config.server.host.as_str();
+    let state_file = config.server.state_file.as_path();
+
+    // 初始端口，优先从状态文件读取，否则使用配置的起始端口
+    let initial_port = read_port_from_file(state_file)
+        .filter(|port| (config.server.port..=config.server.max_port).contains(port))
+        .unwrap_or(config.server.port);
     
     // 查找可用端口，从初始端口开始
-    let port = match find_available_port(initial_port) {
+    let port = match find_available_port(host, initial_port, config.server.max_port) {
         Some(p) => p,
         None => {
             eprintln!("无法找到可用端口");
@@ -68,71 +299,16 @@ async fn main()->Result<(), anyhow::Error>  {
     };
     
     // 如果找到的端口与初始端口不同，或者文件不存在，则保存到文件
-    if port != initial_port || !Path::new("wei-server-mcp.dat").exists() {
-        if let Err(e) = save_port_to_file(port) {
+    if port != initial_port || !state_file.exists() {
+        if let Err(e) = save_port_to_file(state_file, port) {
             eprintln!("无法保存端口到文件: {}", e);
         } else {
-            println!("端口 {} 已保存到 wei-server-mcp.dat", port);
+            println!("端口 {} 已保存到 {}", port, state_file.display());
         }
     }
-    
-    let mcp_server_protocol = Server::builder("add".to_string(), "1.0".to_string())
-        .capabilities(ServerCapabilities {
-            tools: Some(json!({
-                "listChanged": false,
-            })),
-            ..Default::default()
-        })
-        .register_tool(AddTool::tool(), AddTool::call())
-        .register_tool(SubTool::tool(), SubTool::call())
-        .register_tool(CheckAngel::tool(), CheckAngel::call())
-        .register_tool(QueryAngelType::tool(), QueryAngelType::call())
-        .register_tool(QueryGpuSpecs::tool(), QueryGpuSpecs::call())
-        .build();
-
-    println!("服务器启动于端口: {}", port);
-    let mcp_server_transport =
-        ServerSseTransport::new("127.0.0.1".to_string(), port, mcp_server_protocol);
-
-    let _ = Server::start(mcp_server_transport.clone()).await;
-
-    // Create the MCP client
-    let mcp_client = ClientBuilder::new(mcp_server_transport).build();
-
-    // Start the MCP client
-    let _ = mcp_client.open().await;
-    let init_res = mcp_client
-        .initialize(
-            Implementation {
-                name: "mcp-client".to_string(),
-                version: "0.1.0".to_string(),
-            },
-            ClientCapabilities::default(),
-        )
-        .await;
-    println!("Initialized: {:?}", init_res);
-
-    let tools_list_res = mcp_client.list_tools(None, None).await;
-    println!("Tools: {:?}", tools_list_res);
-
-    tracing::info!("Building RIG agent");
-    let completion_model = providers::openai::Client::from_env();
-    let mut agent_builder = completion_model.agent("gpt-3.5-turbo-0125");
-
-    // Add MCP tools to the agent
-    agent_builder = tools_list_res
-        .unwrap()
-        .tools
-        .into_iter()
-        .fold(agent_builder, |builder, tool| {
-            builder.mcp_tool(tool, mcp_client.clone().into())
-        });
-    let agent = agent_builder.build();
-
-    tracing::info!("Prompting RIG agent");
-    let response = agent.prompt("Add 10 + 10").await;
-    tracing::info!("Agent response: {:?}", response);
-    Ok(())
+
+    println!("服务器启动于 {}:{}", host, port);
+    server::sse::serve(host, port, router).await
 }
 
 #[cfg(test)]
@@ -140,28 +316,78 @@ mod tests {
     use super::*;
     use std::fs::remove_file;
     use std::net::TcpListener;
-    
+
+    // 测试命令行参数覆盖配置文件中的值
+    #[test]
+    fn test_cli_overrides_config() {
+        let mut config = Config::from_toml("[server]\nport = 2000\nhost = \"0.0.0.0\"").unwrap();
+        let cli = Cli::try_parse_from([
+            "rig-mcp-server",
+            "--transport",
+            "stdio",
+            "--port",
+            "3000",
+            "--tools",
+            "math,gpu-specs",
+            "--wei-run",
+            "/opt/wei-run",
+            "--max-jobs",
+            "4",
+        ])
+        .unwrap();
+        cli.apply(&mut config);
+
+        assert_eq!(config.server.transport, TransportMode::Stdio);
+        assert_eq!(config.server.port, 3000);
+        // 未在命令行中指定的值保持配置文件中的设置
+        assert_eq!(config.server.host, "0.0.0.0");
+        assert_eq!(config.tools.enabled, "math,gpu-specs".parse().unwrap());
+        assert_eq!(config.wei_run.path, Some(PathBuf::from("/opt/wei-run")));
+        assert_eq!(config.scheduler.max_concurrent_jobs, 4);
+        assert_eq!(config.scheduler.max_queue_depth, 16);
+    }
+
+    // 测试非法的命令行参数
+    #[test]
+    fn test_cli_rejects_invalid_values() {
+        assert!(Cli::try_parse_from(["rig-mcp-server", "--transport", "websocket"]).is_err());
+        assert!(Cli::try_parse_from(["rig-mcp-server", "--tools", "math,unknown"]).is_err());
+        assert!(Cli::try_parse_from(["rig-mcp-server", "--port", "70000"]).is_err());
+    }
+
+    // 测试migrate子命令，全局参数可以写在子命令之后
+    #[test]
+    fn test_cli_migrate_subcommand() {
+        let cli = Cli::try_parse_from(["rig-mcp-server", "migrate", "--status", "--supplier-db", "/tmp/s.db"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Migrate { status: true })));
+        assert_eq!(cli.supplier_db, Some(PathBuf::from("/tmp/s.db")));
+
+        let cli = Cli::try_parse_from(["rig-mcp-server", "migrate"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Migrate { status: false })));
+        assert!(Cli::try_parse_from(["rig-mcp-server"]).unwrap().command.is_none());
+    }
+
     // 测试端口可用性检查函数
     #[test]
     fn test_is_port_available() {
         // 查找一个可用端口
         let start_port = 50000;
-        let available_port = find_available_port(start_port).unwrap();
+        let available_port = find_available_port("127.0.0.1", start_port, 65535).unwrap();
         
         // 验证端口可用
-        assert!(is_port_available(available_port));
+        assert!(is_port_available("127.0.0.1", available_port));
         
         // 占用该端口
         let listener = TcpListener::bind(format!("127.0.0.1:{}", available_port)).unwrap();
         
         // 验证端口不再可用
-        assert!(!is_port_available(available_port));
+        assert!(!is_port_available("127.0.0.1", available_port));
         
         // 释放占用的资源
         drop(listener);
         
         // 端口应该再次可用
-        assert!(is_port_available(available_port));
+        assert!(is_port_available("127.0.0.1", available_port));
     }
     
     // 测试查找可用端口函数
@@ -169,7 +395,7 @@ mod tests {
     fn test_find_available_port() {
         // 查找可用端口
         let start_port = 50100;
-        let port1 = find_available_port(start_port).unwrap();
+        let port1 = find_available_port("127.0.0.1", start_port, 65535).unwrap();
         
         // 端口应该大于等于起始端口
         assert!(port1 >= start_port);
@@ -178,7 +404,7 @@ mod tests {
         let listener = TcpListener::bind(format!("127.0.0.1:{}", port1)).unwrap();
         
         // 再次查找应该找到不同的端口
-        let port2 = find_available_port(start_port).unwrap();
+        let port2 = find_available_port("127.0.0.1", start_port, 65535).unwrap();
         assert_ne!(port1, port2);
         
         // 释放资源
@@ -214,13 +440,13 @@ mod tests {
     fn test_port_increment() {
         // 查找一个可用端口作为起点
         let start_port = 52000;
-        let port1 = find_available_port(start_port).unwrap();
+        let port1 = find_available_port("127.0.0.1", start_port, 65535).unwrap();
         
         // 占用这个端口
         let listener = TcpListener::bind(format!("127.0.0.1:{}", port1)).unwrap();
         
         // 从相同的起始端口开始查找，应该找到下一个可用端口
-        let port2 = find_available_port(start_port).unwrap();
+        let port2 = find_available_port("127.0.0.1", start_port, 65535).unwrap();
         
         // 第二个端口应该大于第一个
         assert!(port2 > port1);
@@ -264,7 +490,7 @@ mod tests {
         };
         
         // 查找可用端口
-        let allocated_port = find_available_port(file_port).unwrap();
+        let allocated_port = find_available_port("127.0.0.1", file_port, 65535).unwrap();
         
         // 分配的端口应该大于初始端口（因为初始端口已被占用）
         assert!(allocated_port > initial_port);
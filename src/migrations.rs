@@ -0,0 +1,269 @@
+//! SQLite数据库的版本迁移。
+//!
+//! 每个迁移有一个递增的版本号，已应用的版本记录在 `schema_migrations` 表中。
+//! 打开数据库时按版本顺序应用尚未应用的迁移，每个迁移在单独的事务中执行，失败时回滚。
+//! 数据库中记录的版本高于程序已知的最新版本时拒绝使用，避免旧程序写坏新格式的数据。
+
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+
+const METADATA_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS schema_migrations (
+    version INTEGER PRIMARY KEY,    -- 迁移版本
+    description TEXT NOT NULL,      -- 迁移说明
+    applied_at INTEGER NOT NULL     -- 应用时间（Unix时间戳）
+);
+";
+
+/// 一个迁移：版本号从1开始递增
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    pub sql: &'static str,
+}
+
+/// 已应用的迁移
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedMigration {
+    pub version: u32,
+    pub description: String,
+    pub applied_at: i64,
+}
+
+/// 数据库的迁移状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+    /// 数据库当前的版本，全新的数据库为0
+    pub current: u32,
+    /// 程序已知的最新版本
+    pub latest: u32,
+    pub applied: Vec<AppliedMigration>,
+    pub pending: Vec<Migration>,
+}
+
+impl MigrationStatus {
+    /// 数据库是否由更新版本的程序创建
+    pub fn is_too_new(&self) -> bool {
+        self.current > self.latest
+    }
+}
+
+impl fmt::Display for MigrationStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "当前版本: {}，最新版本: {}", self.current, self.latest)?;
+        if !self.applied.is_empty() {
+            writeln!(f, "已应用:")?;
+            for migration in &self.applied {
+                writeln!(
+                    f,
+                    "  {:>3}  {}（应用于 {}）",
+                    migration.version, migration.description, migration.applied_at
+                )?;
+            }
+        }
+        if self.is_too_new() {
+            writeln!(f, "数据库版本高于本程序支持的版本，请升级rig-mcp-server")?;
+        } else if self.pending.is_empty() {
+            writeln!(f, "没有待应用的迁移")?;
+        } else {
+            writeln!(f, "待应用:")?;
+            for migration in &self.pending {
+                writeln!(f, "  {:>3}  {}", migration.version, migration.description)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 迁移失败
+#[derive(Debug, thiserror::Error)]
+pub enum MigrationError {
+    #[error("database schema version {found} is newer than the latest version {supported} known to this build, upgrade rig-mcp-server")]
+    TooNew { found: u32, supported: u32 },
+    #[error("migration {version} ({description}) failed: {source}")]
+    Failed {
+        version: u32,
+        description: &'static str,
+        source: rusqlite::Error,
+    },
+    #[error(transparent)]
+    Database(#[from] rusqlite::Error),
+}
+
+fn latest(migrations: &[Migration]) -> u32 {
+    migrations.iter().map(|migration| migration.version).max().unwrap_or(0)
+}
+
+fn applied(db: &Connection) -> Result<Vec<AppliedMigration>, MigrationError> {
+    let exists = db
+        .query_row(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'schema_migrations'",
+            [],
+            |_| Ok(()),
+        )
+        .optional()?;
+    if exists.is_none() {
+        return Ok(Vec::new());
+    }
+    let mut statement = db.prepare("SELECT version, description, applied_at FROM schema_migrations ORDER BY version")?;
+    let applied = statement
+        .query_map([], |row| {
+            Ok(AppliedMigration {
+                version: row.get("version")?,
+                description: row.get("description")?,
+                applied_at: row.get("applied_at")?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(applied)
+}
+
+/// 读取迁移状态，不修改数据库
+pub fn status(db: &Connection, migrations: &[Migration]) -> Result<MigrationStatus, MigrationError> {
+    let applied = applied(db)?;
+    Ok(status_of(applied, migrations))
+}
+
+/// 尚未创建的数据库的迁移状态
+pub fn empty_status(migrations: &[Migration]) -> MigrationStatus {
+    status_of(Vec::new(), migrations)
+}
+
+fn status_of(applied: Vec<AppliedMigration>, migrations: &[Migration]) -> MigrationStatus {
+    let mut pending: Vec<Migration> = migrations
+        .iter()
+        .filter(|migration| !applied.iter().any(|a| a.version == migration.version))
+        .copied()
+        .collect();
+    pending.sort_by_key(|migration| migration.version);
+    MigrationStatus {
+        current: applied.iter().map(|a| a.version).max().unwrap_or(0),
+        latest: latest(migrations),
+        applied,
+        pending,
+    }
+}
+
+/// 按版本顺序应用尚未应用的迁移，返回本次应用的版本
+pub fn migrate(db: &mut Connection, migrations: &[Migration]) -> Result<Vec<u32>, MigrationError> {
+    let status = status(db, migrations)?;
+    if status.is_too_new() {
+        return Err(MigrationError::TooNew {
+            found: status.current,
+            supported: status.latest,
+        });
+    }
+    if status.pending.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    db.execute_batch(METADATA_SCHEMA)?;
+    let mut versions = Vec::new();
+    for migration in status.pending {
+        apply(db, &migration).map_err(|source| MigrationError::Failed {
+            version: migration.version,
+            description: migration.description,
+            source,
+        })?;
+        versions.push(migration.version);
+    }
+    Ok(versions)
+}
+
+// 事务在出错返回时被丢弃，自动回滚
+fn apply(db: &mut Connection, migration: &Migration) -> Result<(), rusqlite::Error> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
+    let transaction = db.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    transaction.execute_batch(migration.sql)?;
+    transaction.execute(
+        "INSERT INTO schema_migrations (version, description, applied_at) VALUES (?, ?, ?)",
+        params![migration.version, migration.description, now],
+    )?;
+    transaction.commit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIGRATIONS: &[Migration] = &[
+        Migration {
+            version: 1,
+            description: "create items",
+            sql: "CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT NOT NULL);",
+        },
+        Migration {
+            version: 2,
+            description: "add price",
+            sql: "ALTER TABLE items ADD COLUMN price REAL;",
+        },
+    ];
+
+    fn columns(db: &Connection) -> Vec<String> {
+        let mut statement = db.prepare("PRAGMA table_info(items)").unwrap();
+        let names = statement.query_map([], |row| row.get("name")).unwrap();
+        names.map(Result::unwrap).collect()
+    }
+
+    #[test]
+    fn test_migrate_applies_pending_in_order() {
+        let mut db = Connection::open_in_memory().unwrap();
+        let status = status(&db, MIGRATIONS).unwrap();
+        assert_eq!((status.current, status.latest, status.pending.len()), (0, 2, 2));
+        // 读取状态不创建元数据表
+        let tables: i64 = db.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get(0)).unwrap();
+        assert_eq!(tables, 0);
+
+        assert_eq!(migrate(&mut db, &MIGRATIONS[..1]).unwrap(), vec![1]);
+        assert_eq!(columns(&db), ["id", "name"]);
+        let status = super::status(&db, MIGRATIONS).unwrap();
+        assert_eq!(status.current, 1);
+        assert_eq!(status.pending, MIGRATIONS[1..]);
+        assert!(status.to_string().contains("待应用:\n    2  add price"));
+
+        assert_eq!(migrate(&mut db, MIGRATIONS).unwrap(), vec![2]);
+        assert_eq!(columns(&db), ["id", "name", "price"]);
+        assert!(migrate(&mut db, MIGRATIONS).unwrap().is_empty());
+        let status = super::status(&db, MIGRATIONS).unwrap();
+        assert_eq!(status.applied.len(), 2);
+        assert_eq!(status.applied[1].description, "add price");
+    }
+
+    #[test]
+    fn test_newer_database_is_refused() {
+        let mut db = Connection::open_in_memory().unwrap();
+        migrate(&mut db, MIGRATIONS).unwrap();
+
+        let status = status(&db, &MIGRATIONS[..1]).unwrap();
+        assert!(status.is_too_new());
+        assert!(matches!(
+            migrate(&mut db, &MIGRATIONS[..1]),
+            Err(MigrationError::TooNew { found: 2, supported: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_failed_migration_is_rolled_back() {
+        let mut db = Connection::open_in_memory().unwrap();
+        let broken = [
+            MIGRATIONS[0],
+            Migration {
+                version: 2,
+                description: "broken",
+                sql: "ALTER TABLE items ADD COLUMN price REAL; ALTER TABLE missing ADD COLUMN x;",
+            },
+        ];
+        let result = migrate(&mut db, &broken);
+        assert!(matches!(result, Err(MigrationError::Failed { version: 2, .. })), "{:?}", result);
+        // 第一个迁移已提交，失败的迁移没有留下任何修改
+        assert_eq!(columns(&db), ["id", "name"]);
+        assert_eq!(status(&db, MIGRATIONS).unwrap().current, 1);
+        assert_eq!(migrate(&mut db, MIGRATIONS).unwrap(), vec![2]);
+    }
+}
@@ -0,0 +1,632 @@
+//! 估算大模型的显存占用，并根据GPU规格目录判断需要哪些GPU、每种几张。
+//!
+//! 只做粗略估算，按标准的稠密Transformer计算：
+//! - 权重：参数量 × 每个参数的字节数。
+//! - 推理：权重 + KV缓存，KV缓存为 2（K和V）× 层数 × batch × 上下文长度 × KV维度 × 每个值的字节数。
+//! - 训练：混合精度Adam，权重和梯度按训练精度，优化器状态按fp32（主权重和两个动量）；
+//!   激活值按开启选择性重计算估算，每层每个token约 17 × hidden size 个值。
+//! - 以上合计再加10%的运行时开销（CUDA上下文、临时缓冲、显存碎片）。
+//!
+//! 没有提供模型结构时按 参数量 ≈ 12 × 层数 × hidden²、hidden ≈ 128 × 层数 推算层数和hidden size；
+//! 没有提供KV头数时按多头注意力（不分组）计算，结果偏保守。
+//! 显存以GiB（1024³字节）为单位，GPU的显存容量也按GiB计。多卡时假设权重、KV缓存和优化器状态
+//! 可以均匀切分（张量并行、ZeRO-3/FSDP）。
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
+use serde_json::json;
+
+use crate::gpu_specs::{format_number, markdown_cell, GpuCatalog, GpuSpec, GpuSpecError, Lang};
+
+const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// 运行时开销占其余各项之和的比例
+const OVERHEAD_FRACTION: f64 = 0.1;
+
+/// 每层每个token的激活值个数与hidden size之比（选择性重计算）
+const ACTIVATION_VALUES_PER_HIDDEN: f64 = 17.0;
+
+/// 优化器状态每个参数的字节数：fp32主权重和Adam的两个动量
+const MIXED_PRECISION_OPTIMIZER_BYTES: f64 = 12.0;
+
+/// fp32训练不需要单独的主权重，只有两个动量
+const FP32_OPTIMIZER_BYTES: f64 = 8.0;
+
+pub const DEFAULT_CONTEXT_LENGTH: u64 = 4096;
+
+/// 权重精度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Precision {
+    Fp32,
+    #[default]
+    Fp16,
+    Bf16,
+    Fp8,
+    Int8,
+    Int4,
+}
+
+impl Precision {
+    pub fn name(self) -> &'static str {
+        match self {
+            Precision::Fp32 => "fp32",
+            Precision::Fp16 => "fp16",
+            Precision::Bf16 => "bf16",
+            Precision::Fp8 => "fp8",
+            Precision::Int8 => "int8",
+            Precision::Int4 => "int4",
+        }
+    }
+
+    /// 每个参数的字节数
+    pub fn bytes(self) -> f64 {
+        match self {
+            Precision::Fp32 => 4.0,
+            Precision::Fp16 | Precision::Bf16 => 2.0,
+            Precision::Fp8 | Precision::Int8 => 1.0,
+            Precision::Int4 => 0.5,
+        }
+    }
+
+    /// KV缓存每个值的字节数：整数量化只作用于权重，KV缓存仍为fp16
+    fn kv_bytes(self) -> f64 {
+        match self {
+            Precision::Int8 | Precision::Int4 => 2.0,
+            precision => precision.bytes(),
+        }
+    }
+
+    /// 训练时激活值每个值的字节数，fp8训练的激活值仍为16位
+    fn activation_bytes(self) -> f64 {
+        match self {
+            Precision::Fp32 => 4.0,
+            _ => 2.0,
+        }
+    }
+}
+
+impl fmt::Display for Precision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Precision {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "fp32" | "float32" => Ok(Precision::Fp32),
+            "fp16" | "float16" | "half" => Ok(Precision::Fp16),
+            "bf16" | "bfloat16" => Ok(Precision::Bf16),
+            "fp8" => Ok(Precision::Fp8),
+            "int8" => Ok(Precision::Int8),
+            "int4" => Ok(Precision::Int4),
+            other => Err(format!(
+                "unknown precision {:?}, expected fp32, fp16, bf16, fp8, int8 or int4",
+                other
+            )),
+        }
+    }
+}
+
+/// 推理还是训练
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Workload {
+    #[default]
+    Inference,
+    Training,
+}
+
+impl FromStr for Workload {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "inference" | "infer" | "serving" => Ok(Workload::Inference),
+            "training" | "train" => Ok(Workload::Training),
+            other => Err(format!("unknown mode {:?}, expected inference or training", other)),
+        }
+    }
+}
+
+/// 估算用到的模型结构
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ModelShape {
+    pub num_layers: u32,
+    pub hidden_size: u32,
+    /// 每层KV缓存的维度，分组查询注意力时小于hidden size
+    pub kv_dim: u32,
+    /// 层数或hidden size是否由参数量推算
+    pub estimated: bool,
+}
+
+/// 估算的输入
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FitRequest {
+    /// 参数量（十亿）
+    pub parameters_b: f64,
+    pub precision: Precision,
+    pub context_length: u64,
+    pub batch_size: u64,
+    pub workload: Workload,
+    pub num_layers: Option<u32>,
+    pub hidden_size: Option<u32>,
+    pub num_attention_heads: Option<u32>,
+    pub num_kv_heads: Option<u32>,
+}
+
+/// 各部分的显存（GiB），不适用的部分为None
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MemoryEstimate {
+    pub weights: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kv_cache: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gradients: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub optimizer_states: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub activations: Option<f64>,
+    pub overhead: f64,
+    pub total: f64,
+}
+
+fn invalid(message: impl Into<String>) -> GpuSpecError {
+    GpuSpecError::InvalidArguments(message.into())
+}
+
+impl FitRequest {
+    fn validate(&self) -> Result<(), GpuSpecError> {
+        if !self.parameters_b.is_finite() || self.parameters_b <= 0.0 {
+            return Err(invalid("parameters_b must be a positive number"));
+        }
+        if self.context_length == 0 {
+            return Err(invalid("context_length must be at least 1"));
+        }
+        if self.batch_size == 0 {
+            return Err(invalid("batch_size must be at least 1"));
+        }
+        for (name, value) in [
+            ("num_layers", self.num_layers),
+            ("hidden_size", self.hidden_size),
+            ("num_attention_heads", self.num_attention_heads),
+            ("num_kv_heads", self.num_kv_heads),
+        ] {
+            if value == Some(0) {
+                return Err(invalid(format!("{} must be at least 1", name)));
+            }
+        }
+        match (self.num_attention_heads, self.num_kv_heads) {
+            (None, Some(_)) => Err(invalid("num_kv_heads requires num_attention_heads")),
+            (Some(heads), Some(kv_heads)) if kv_heads > heads => {
+                Err(invalid("num_kv_heads cannot exceed num_attention_heads"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 模型结构，没有提供的层数和hidden size按参数量推算
+    pub fn shape(&self) -> ModelShape {
+        let parameters = self.parameters_b * 1e9;
+        let (num_layers, hidden_size) = match (self.num_layers, self.hidden_size) {
+            (Some(layers), Some(hidden)) => (layers as f64, hidden as f64),
+            (Some(layers), None) => (layers as f64, (parameters / (12.0 * layers as f64)).sqrt()),
+            (None, Some(hidden)) => (parameters / (12.0 * (hidden as f64).powi(2)), hidden as f64),
+            (None, None) => {
+                let layers = (parameters / (12.0 * 128.0 * 128.0)).cbrt();
+                (layers, 128.0 * layers)
+            }
+        };
+        let num_layers = num_layers.round().max(1.0) as u32;
+        let hidden_size = hidden_size.round().max(1.0) as u32;
+        let kv_dim = match (self.num_attention_heads, self.num_kv_heads) {
+            (Some(heads), Some(kv_heads)) => {
+                (hidden_size as f64 * kv_heads as f64 / heads as f64).round().max(1.0) as u32
+            }
+            _ => hidden_size,
+        };
+        ModelShape {
+            num_layers,
+            hidden_size,
+            kv_dim,
+            estimated: self.num_layers.is_none() || self.hidden_size.is_none(),
+        }
+    }
+
+    /// 估算各部分显存，整数精度不支持训练
+    pub fn estimate(&self) -> Result<MemoryEstimate, GpuSpecError> {
+        self.validate()?;
+        let shape = self.shape();
+        let parameters = self.parameters_b * 1e9;
+        // 每层所有token的数量
+        let tokens = self.context_length as f64 * self.batch_size as f64 * shape.num_layers as f64;
+        let weights = parameters * self.precision.bytes() / GIB;
+        let mut estimate = MemoryEstimate {
+            weights,
+            kv_cache: None,
+            gradients: None,
+            optimizer_states: None,
+            activations: None,
+            overhead: 0.0,
+            total: 0.0,
+        };
+        let subtotal = match self.workload {
+            Workload::Inference => {
+                let kv_cache = 2.0 * tokens * shape.kv_dim as f64 * self.precision.kv_bytes() / GIB;
+                estimate.kv_cache = Some(kv_cache);
+                weights + kv_cache
+            }
+            Workload::Training => {
+                let optimizer_bytes = match self.precision {
+                    Precision::Int8 | Precision::Int4 => {
+                        return Err(invalid(format!(
+                            "training is not supported with {} precision, use fp32, fp16, bf16 or fp8",
+                            self.precision
+                        )))
+                    }
+                    Precision::Fp32 => FP32_OPTIMIZER_BYTES,
+                    _ => MIXED_PRECISION_OPTIMIZER_BYTES,
+                };
+                let gradients = weights;
+                let optimizer_states = parameters * optimizer_bytes / GIB;
+                let activations = ACTIVATION_VALUES_PER_HIDDEN
+                    * tokens
+                    * shape.hidden_size as f64
+                    * self.precision.activation_bytes()
+                    / GIB;
+                estimate.gradients = Some(gradients);
+                estimate.optimizer_states = Some(optimizer_states);
+                estimate.activations = Some(activations);
+                weights + gradients + optimizer_states + activations
+            }
+        };
+        estimate.overhead = subtotal * OVERHEAD_FRACTION;
+        estimate.total = subtotal + estimate.overhead;
+        Ok(estimate)
+    }
+}
+
+/// 某款GPU需要的数量
+#[derive(Debug, Clone, PartialEq)]
+pub struct GpuFit<'a> {
+    pub gpu: &'a GpuSpec,
+    pub gpus_needed: u64,
+}
+
+impl GpuFit<'_> {
+    pub fn fits_single(&self) -> bool {
+        self.gpus_needed == 1
+    }
+}
+
+/// 一次估算的结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelFit<'a> {
+    pub request: FitRequest,
+    pub shape: ModelShape,
+    pub memory: MemoryEstimate,
+    /// 按所需数量、显存从少到多排列
+    pub fits: Vec<GpuFit<'a>>,
+    /// 目录中没有显存数据、无法判断的GPU
+    pub unknown: Vec<&'a GpuSpec>,
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+fn format_gib(value: f64) -> String {
+    format!("{} GiB", format_number(value))
+}
+
+impl<'a> ModelFit<'a> {
+    /// 估算显存并计算每款GPU需要的数量；`models` 为空时使用目录中的所有GPU
+    pub fn new(catalog: &'a GpuCatalog, request: FitRequest, models: &[String]) -> Result<Self, GpuSpecError> {
+        let memory = request.estimate()?;
+        let gpus: Vec<&GpuSpec> = if models.is_empty() {
+            catalog.gpus.iter().collect()
+        } else {
+            let mut gpus: Vec<&GpuSpec> = Vec::new();
+            for model in models {
+                let gpu = catalog.lookup(model)?;
+                if !gpus.iter().any(|existing| existing.name == gpu.name) {
+                    gpus.push(gpu);
+                }
+            }
+            gpus
+        };
+        let mut fits = Vec::new();
+        let mut unknown = Vec::new();
+        for gpu in gpus {
+            match gpu.memory_gb.filter(|memory_gb| *memory_gb > 0.0) {
+                Some(memory_gb) => fits.push(GpuFit {
+                    gpu,
+                    gpus_needed: (memory.total / memory_gb).ceil().max(1.0) as u64,
+                }),
+                None => unknown.push(gpu),
+            }
+        }
+        fits.sort_by(|a, b| {
+            a.gpus_needed
+                .cmp(&b.gpus_needed)
+                .then(a.gpu.memory_gb.partial_cmp(&b.gpu.memory_gb).unwrap_or(std::cmp::Ordering::Equal))
+        });
+        Ok(ModelFit {
+            shape: request.shape(),
+            request,
+            memory,
+            fits,
+            unknown,
+        })
+    }
+
+    /// 结构化的结果，显存单位为GiB，保留两位小数
+    pub fn to_json(&self) -> serde_json::Value {
+        let memory = &self.memory;
+        let round = |value: Option<f64>| value.map(round2);
+        json!({
+            "parameters_b": self.request.parameters_b,
+            "precision": self.request.precision,
+            "context_length": self.request.context_length,
+            "batch_size": self.request.batch_size,
+            "mode": self.request.workload,
+            "shape": self.shape,
+            "memory_gib": MemoryEstimate {
+                weights: round2(memory.weights),
+                kv_cache: round(memory.kv_cache),
+                gradients: round(memory.gradients),
+                optimizer_states: round(memory.optimizer_states),
+                activations: round(memory.activations),
+                overhead: round2(memory.overhead),
+                total: round2(memory.total),
+            },
+            "gpus": self.fits.iter().map(|fit| json!({
+                "name": fit.gpu.name,
+                "memory_gb": fit.gpu.memory_gb,
+                "gpus_needed": fit.gpus_needed,
+                "fits_single": fit.fits_single(),
+            })).collect::<Vec<_>>(),
+            "unknown": self.unknown.iter().map(|gpu| gpu.name.as_str()).collect::<Vec<_>>(),
+        })
+    }
+
+    /// Markdown格式的显存明细和GPU需求表
+    pub fn to_markdown(&self, lang: Lang) -> String {
+        let request = &self.request;
+        let shape = &self.shape;
+        let memory = &self.memory;
+        let parameters = format_number(request.parameters_b);
+        let mut out = match (lang, request.workload) {
+            (Lang::Zh, Workload::Inference) => format!("### 显存估算：{}B参数，{}，推理\n\n", parameters, request.precision),
+            (Lang::Zh, Workload::Training) => format!("### 显存估算：{}B参数，{}，训练\n\n", parameters, request.precision),
+            (Lang::En, Workload::Inference) => {
+                format!("### Memory estimate: {}B parameters, {}, inference\n\n", parameters, request.precision)
+            }
+            (Lang::En, Workload::Training) => {
+                format!("### Memory estimate: {}B parameters, {}, training\n\n", parameters, request.precision)
+            }
+        };
+        match lang {
+            Lang::Zh => {
+                out.push_str(&format!(
+                    "上下文长度{}，batch size {}；模型结构：{}层，hidden size {}，KV维度{}",
+                    request.context_length, request.batch_size, shape.num_layers, shape.hidden_size, shape.kv_dim
+                ));
+                if shape.estimated {
+                    out.push_str("（按参数量推算）");
+                }
+                out.push_str("\n\n| 项目 | 显存 |\n| --- | --- |\n");
+            }
+            Lang::En => {
+                out.push_str(&format!(
+                    "Context length {}, batch size {}; model shape: {} layers, hidden size {}, KV dim {}",
+                    request.context_length, request.batch_size, shape.num_layers, shape.hidden_size, shape.kv_dim
+                ));
+                if shape.estimated {
+                    out.push_str(" (estimated from parameter count)");
+                }
+                out.push_str("\n\n| Item | Memory |\n| --- | --- |\n");
+            }
+        }
+        let rows = [
+            (("权重", "Weights"), Some(memory.weights)),
+            (("KV缓存", "KV cache"), memory.kv_cache),
+            (("梯度", "Gradients"), memory.gradients),
+            (("优化器状态", "Optimizer states"), memory.optimizer_states),
+            (("激活值", "Activations"), memory.activations),
+            (("运行时开销", "Overhead"), Some(memory.overhead)),
+            (("合计", "Total"), Some(memory.total)),
+        ];
+        for ((zh, en), value) in rows {
+            if let Some(value) = value {
+                let label = match lang {
+                    Lang::Zh => zh,
+                    Lang::En => en,
+                };
+                out.push_str(&format!("| {} | {} |\n", label, format_gib(value)));
+            }
+        }
+
+        match lang {
+            Lang::Zh => out.push_str("\n| GPU | 单卡显存 | 所需数量 | 单卡可容纳 |\n"),
+            Lang::En => out.push_str("\n| GPU | Memory per GPU | GPUs needed | Fits on one GPU |\n"),
+        }
+        out.push_str("| --- | --- | --- | --- |\n");
+        for fit in &self.fits {
+            let fits_single = match (lang, fit.fits_single()) {
+                (Lang::Zh, true) => "是",
+                (Lang::Zh, false) => "否",
+                (Lang::En, true) => "yes",
+                (Lang::En, false) => "no",
+            };
+            out.push_str(&format!(
+                "| {} | {} GB | {} | {} |\n",
+                markdown_cell(&fit.gpu.name),
+                format_number(fit.gpu.memory_gb.unwrap_or_default()),
+                fit.gpus_needed,
+                fits_single
+            ));
+        }
+        if !self.unknown.is_empty() {
+            let names: Vec<&str> = self.unknown.iter().map(|gpu| gpu.name.as_str()).collect();
+            match lang {
+                Lang::Zh => out.push_str(&format!("\n没有显存数据、无法判断：{}\n", names.join("、"))),
+                Lang::En => out.push_str(&format!("\nNo memory data: {}\n", names.join(", "))),
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(parameters_b: f64, precision: Precision, workload: Workload) -> FitRequest {
+        FitRequest {
+            parameters_b,
+            precision,
+            context_length: DEFAULT_CONTEXT_LENGTH,
+            batch_size: 1,
+            workload,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_options() {
+        assert_eq!("BF16".parse::<Precision>(), Ok(Precision::Bf16));
+        assert_eq!("int4".parse::<Precision>(), Ok(Precision::Int4));
+        assert!("fp64".parse::<Precision>().is_err());
+        assert_eq!("train".parse::<Workload>(), Ok(Workload::Training));
+        assert!("finetune".parse::<Workload>().is_err());
+    }
+
+    #[test]
+    fn test_shape_from_parameter_count() {
+        // 7B模型约为32层、hidden size 4096
+        let shape = request(7.0, Precision::Fp16, Workload::Inference).shape();
+        assert_eq!(shape.num_layers, 33);
+        assert!(shape.estimated);
+        assert_eq!(shape.kv_dim, shape.hidden_size);
+
+        let llama = FitRequest {
+            num_layers: Some(80),
+            hidden_size: Some(8192),
+            num_attention_heads: Some(64),
+            num_kv_heads: Some(8),
+            ..request(70.0, Precision::Fp16, Workload::Inference)
+        };
+        let shape = llama.shape();
+        assert_eq!((shape.num_layers, shape.hidden_size, shape.kv_dim), (80, 8192, 1024));
+        assert!(!shape.estimated);
+    }
+
+    #[test]
+    fn test_inference_estimate() {
+        let llama = FitRequest {
+            num_layers: Some(80),
+            hidden_size: Some(8192),
+            num_attention_heads: Some(64),
+            num_kv_heads: Some(8),
+            ..request(70.0, Precision::Fp16, Workload::Inference)
+        };
+        let memory = llama.estimate().unwrap();
+        // 70e9 × 2字节
+        assert_eq!(round2(memory.weights), 130.39);
+        // 2 × 80层 × 4096 × 1024 × 2字节 = 1.25 GiB
+        assert_eq!(memory.kv_cache, Some(1.25));
+        assert_eq!(round2(memory.total), round2((130.385 + 1.25) * 1.1));
+        assert!(memory.gradients.is_none());
+
+        // int4权重减为四分之一，KV缓存仍为fp16
+        let int4 = FitRequest { precision: Precision::Int4, ..llama }.estimate().unwrap();
+        assert_eq!(round2(int4.weights), 32.6);
+        assert_eq!(int4.kv_cache, Some(1.25));
+    }
+
+    #[test]
+    fn test_training_estimate() {
+        let memory = request(7.0, Precision::Bf16, Workload::Training).estimate().unwrap();
+        // 权重、梯度各2字节，优化器状态12字节，共16字节每参数
+        let states = memory.weights + memory.gradients.unwrap() + memory.optimizer_states.unwrap();
+        assert_eq!(round2(states), round2(7e9 * 16.0 / GIB));
+        assert!(memory.activations.unwrap() > 0.0);
+        assert!(memory.kv_cache.is_none());
+
+        let fp32 = request(7.0, Precision::Fp32, Workload::Training).estimate().unwrap();
+        assert_eq!(round2(fp32.optimizer_states.unwrap()), round2(7e9 * 8.0 / GIB));
+
+        assert!(matches!(
+            request(7.0, Precision::Int4, Workload::Training).estimate(),
+            Err(GpuSpecError::InvalidArguments(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_requests() {
+        let base = request(7.0, Precision::Fp16, Workload::Inference);
+        let cases = [
+            FitRequest { parameters_b: 0.0, ..base.clone() },
+            FitRequest { parameters_b: f64::NAN, ..base.clone() },
+            FitRequest { context_length: 0, ..base.clone() },
+            FitRequest { batch_size: 0, ..base.clone() },
+            FitRequest { num_layers: Some(0), ..base.clone() },
+            FitRequest { num_kv_heads: Some(8), ..base.clone() },
+            FitRequest { num_attention_heads: Some(8), num_kv_heads: Some(16), ..base.clone() },
+        ];
+        for case in cases {
+            assert!(matches!(case.estimate(), Err(GpuSpecError::InvalidArguments(_))), "{:?}", case);
+        }
+    }
+
+    #[test]
+    fn test_gpu_fits() {
+        let catalog = GpuCatalog::bundled();
+        let fit = ModelFit::new(&catalog, request(70.0, Precision::Fp16, Workload::Inference), &[]).unwrap();
+        assert!(fit.unknown.is_empty());
+        assert_eq!(fit.fits.len(), catalog.gpus.len());
+        let needed = |name: &str| fit.fits.iter().find(|fit| fit.gpu.name == name).unwrap().gpus_needed;
+        assert_eq!(needed("H200"), 2);
+        assert_eq!(needed("H100"), 2);
+        assert_eq!(needed("RTX 4090"), 7);
+        // 按所需数量排列
+        assert!(fit.fits.windows(2).all(|pair| pair[0].gpus_needed <= pair[1].gpus_needed));
+
+        let models = ["B200".to_string(), "A100 40GB".to_string()];
+        let fit = ModelFit::new(&catalog, request(7.0, Precision::Fp16, Workload::Inference), &models).unwrap();
+        let names: Vec<&str> = fit.fits.iter().map(|fit| fit.gpu.name.as_str()).collect();
+        assert_eq!(names, ["A100 40GB", "B200"]);
+        assert!(fit.fits.iter().all(GpuFit::fits_single));
+
+        let models = ["V100".to_string()];
+        let result = ModelFit::new(&catalog, request(7.0, Precision::Fp16, Workload::Inference), &models);
+        assert!(matches!(result, Err(GpuSpecError::UnknownModel { .. })));
+    }
+
+    #[test]
+    fn test_render() {
+        let catalog = GpuCatalog::bundled();
+        let models = ["H100".to_string()];
+        let fit = ModelFit::new(&catalog, request(7.0, Precision::Bf16, Workload::Training), &models).unwrap();
+        let markdown = fit.to_markdown(Lang::Zh);
+        assert!(markdown.starts_with("### 显存估算：7B参数，bf16，训练\n"));
+        assert!(markdown.contains("（按参数量推算）"));
+        assert!(markdown.contains("| 优化器状态 | 78.23 GiB |"));
+        assert!(!markdown.contains("KV缓存"));
+        assert!(markdown.contains("| H100 | 80 GB | 2 | 否 |"));
+        assert!(fit.to_markdown(Lang::En).contains("| Optimizer states | 78.23 GiB |"));
+
+        let json = fit.to_json();
+        assert_eq!(json["mode"], "training");
+        assert_eq!(json["precision"], "bf16");
+        assert_eq!(json["memory_gib"]["optimizer_states"], 78.23);
+        assert!(json["memory_gib"].get("kv_cache").is_none());
+        assert_eq!(json["gpus"][0], json!({ "name": "H100", "memory_gb": 80.0, "gpus_needed": 2, "fits_single": false }));
+    }
+}
@@ -0,0 +1,216 @@
+//! 进程内的模型状态：记录通过LoadModel加载的模型，供ListModels、ModelStatus查询，
+//! 并在GenerateText、CreateEmbedding未指定模型时提供默认模型。
+//!
+//! 状态只反映本服务器发出的load/unload命令，服务器重启后清空。
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// 模型类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelType {
+    Llm,
+    Embedding,
+}
+
+impl ModelType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ModelType::Llm => "llm",
+            ModelType::Embedding => "embedding",
+        }
+    }
+}
+
+impl fmt::Display for ModelType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ModelType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "llm" => Ok(ModelType::Llm),
+            "embedding" | "embed" => Ok(ModelType::Embedding),
+            other => Err(format!("unknown model type {:?}, expected llm or embedding", other)),
+        }
+    }
+}
+
+/// 一个已加载的模型，时间为Unix时间戳（秒）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LoadedModel {
+    pub name: String,
+    pub model_type: ModelType,
+    pub loaded_at: u64,
+    pub last_used_at: Option<u64>,
+    // 加载顺序，同一秒内加载多个模型时用来确定最近加载的模型
+    #[serde(skip)]
+    load_seq: u64,
+}
+
+/// 已加载模型的登记表，以模型名称为键
+#[derive(Debug, Default)]
+pub struct ModelRegistry {
+    models: BTreeMap<String, LoadedModel>,
+    next_seq: u64,
+}
+
+impl ModelRegistry {
+    pub const fn new() -> Self {
+        ModelRegistry {
+            models: BTreeMap::new(),
+            next_seq: 0,
+        }
+    }
+
+    /// 记录模型已加载，重复加载会刷新加载时间
+    pub fn record_load(&mut self, name: &str, model_type: ModelType, now: u64) {
+        self.next_seq += 1;
+        self.models.insert(
+            name.to_string(),
+            LoadedModel {
+                name: name.to_string(),
+                model_type,
+                loaded_at: now,
+                last_used_at: None,
+                load_seq: self.next_seq,
+            },
+        );
+    }
+
+    /// 记录模型已卸载，返回卸载前的状态
+    pub fn record_unload(&mut self, name: &str) -> Option<LoadedModel> {
+        self.models.remove(name)
+    }
+
+    /// 记录模型被使用
+    pub fn touch(&mut self, name: &str, now: u64) {
+        if let Some(model) = self.models.get_mut(name) {
+            model.last_used_at = Some(now);
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&LoadedModel> {
+        self.models.get(name)
+    }
+
+    /// 按名称排序的已加载模型，可按类型过滤
+    pub fn list(&self, model_type: Option<ModelType>) -> Vec<LoadedModel> {
+        self.models
+            .values()
+            .filter(|model| model_type.is_none_or(|t| model.model_type == t))
+            .cloned()
+            .collect()
+    }
+
+    /// 某类型的默认模型：最近加载的那个
+    pub fn default_model(&self, model_type: ModelType) -> Option<&LoadedModel> {
+        self.models
+            .values()
+            .filter(|model| model.model_type == model_type)
+            .max_by_key(|model| model.load_seq)
+    }
+}
+
+static MODELS: RwLock<ModelRegistry> = RwLock::new(ModelRegistry::new());
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+pub fn record_load(name: &str, model_type: ModelType) {
+    if let Ok(mut models) = MODELS.write() {
+        models.record_load(name, model_type, now());
+    }
+}
+
+pub fn record_unload(name: &str) -> Option<LoadedModel> {
+    MODELS.write().ok()?.record_unload(name)
+}
+
+pub fn touch(name: &str) {
+    if let Ok(mut models) = MODELS.write() {
+        models.touch(name, now());
+    }
+}
+
+pub fn get(name: &str) -> Option<LoadedModel> {
+    MODELS.read().ok()?.get(name).cloned()
+}
+
+pub fn list(model_type: Option<ModelType>) -> Vec<LoadedModel> {
+    MODELS
+        .read()
+        .map(|models| models.list(model_type))
+        .unwrap_or_default()
+}
+
+pub fn default_model(model_type: ModelType) -> Option<String> {
+    MODELS
+        .read()
+        .ok()?
+        .default_model(model_type)
+        .map(|model| model.name.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_unload_and_list() {
+        let mut registry = ModelRegistry::new();
+        registry.record_load("qwen", ModelType::Llm, 100);
+        registry.record_load("bge-m3", ModelType::Embedding, 200);
+
+        let names: Vec<_> = registry.list(None).into_iter().map(|m| m.name).collect();
+        assert_eq!(names, vec!["bge-m3", "qwen"]);
+        assert_eq!(registry.list(Some(ModelType::Llm)).len(), 1);
+
+        registry.touch("qwen", 150);
+        assert_eq!(registry.get("qwen").unwrap().last_used_at, Some(150));
+        // 未加载的模型不会被记录
+        registry.touch("missing", 150);
+        assert!(registry.get("missing").is_none());
+
+        assert_eq!(registry.record_unload("qwen").unwrap().loaded_at, 100);
+        assert!(registry.record_unload("qwen").is_none());
+        assert!(registry.list(Some(ModelType::Llm)).is_empty());
+    }
+
+    #[test]
+    fn test_default_model_is_latest_loaded_of_type() {
+        let mut registry = ModelRegistry::new();
+        assert!(registry.default_model(ModelType::Llm).is_none());
+
+        // 同一秒内加载时按加载顺序判断
+        registry.record_load("qwen", ModelType::Llm, 100);
+        registry.record_load("llama", ModelType::Llm, 100);
+        registry.record_load("bge-m3", ModelType::Embedding, 100);
+        assert_eq!(registry.default_model(ModelType::Llm).unwrap().name, "llama");
+        assert_eq!(registry.default_model(ModelType::Embedding).unwrap().name, "bge-m3");
+
+        registry.record_unload("llama");
+        assert_eq!(registry.default_model(ModelType::Llm).unwrap().name, "qwen");
+    }
+
+    #[test]
+    fn test_parse_model_type() {
+        assert_eq!("LLM".parse::<ModelType>().unwrap(), ModelType::Llm);
+        assert_eq!("embed".parse::<ModelType>().unwrap(), ModelType::Embedding);
+        assert!("vision".parse::<ModelType>().is_err());
+    }
+}
@@ -0,0 +1,474 @@
+//! 可复用的提示词模板，通过 `prompts/list` 和 `prompts/get` 提供给客户端。
+//!
+//! 模板保存在提示词目录（`[prompts] dir`，默认 `prompts`）下，每个 `.toml` 文件一个模板：
+//!
+//! ```toml
+//! description = "总结一个供应商的信息"
+//!
+//! [[arguments]]
+//! name = "id"
+//! description = "供应商id"
+//! type = "integer"
+//! required = true
+//!
+//! [[messages]]
+//! role = "user"
+//! text = "请读取资源 supplier://{{id}}，总结这个供应商的报价和联系方式。"
+//! ```
+//!
+//! 模板名默认为文件名（不含扩展名）。消息中的 `{{参数名}}` 替换为参数值；
+//! 参数类型为string、integer、number或boolean，`choices` 限定可选值，`default` 为未传入时的值。
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use mcp_core::types::{Prompt, PromptArgument};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::server::prompts::{GetPromptResponse, PromptContent, PromptMessage, Role};
+
+/// 参数类型，客户端传入的值按类型检查后替换到消息中
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArgumentType {
+    #[default]
+    String,
+    Integer,
+    Number,
+    Boolean,
+}
+
+impl fmt::Display for ArgumentType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgumentType::String => f.write_str("string"),
+            ArgumentType::Integer => f.write_str("integer"),
+            ArgumentType::Number => f.write_str("number"),
+            ArgumentType::Boolean => f.write_str("boolean"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ArgumentSpec {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default, rename = "type")]
+    pub kind: ArgumentType,
+    #[serde(default)]
+    pub required: bool,
+    /// 未传入或为空时使用的值
+    #[serde(default)]
+    pub default: Option<Value>,
+    /// 非空时参数只能取其中之一
+    #[serde(default)]
+    pub choices: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateMessage {
+    #[serde(default = "user_role")]
+    pub role: Role,
+    pub text: String,
+}
+
+fn user_role() -> Role {
+    Role::User
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PromptTemplate {
+    /// 模板名，从文件加载时默认为文件名
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub arguments: Vec<ArgumentSpec>,
+    pub messages: Vec<TemplateMessage>,
+}
+
+/// prompts/get失败
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum PromptError {
+    #[error("Prompt not found: {0}")]
+    NotFound(String),
+    #[error("Missing required argument: {0}")]
+    MissingArgument(String),
+    #[error("Unknown argument: {0}")]
+    UnknownArgument(String),
+    #[error("Invalid argument {name}: {message}")]
+    InvalidArgument { name: String, message: String },
+}
+
+// 消息文本按占位符切分后的片段
+#[derive(Debug, PartialEq)]
+enum Segment<'a> {
+    Text(&'a str),
+    Argument(&'a str),
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+fn segments(text: &str) -> Result<Vec<Segment<'_>>, String> {
+    let mut segments = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let inner = &rest[start + 2..];
+        let end = inner
+            .find("}}")
+            .ok_or_else(|| format!("unterminated placeholder {:?}", &rest[start..]))?;
+        let name = inner[..end].trim();
+        if !is_valid_name(name) {
+            return Err(format!("invalid placeholder {:?}", &rest[start..start + end + 4]));
+        }
+        segments.push(Segment::Text(&rest[..start]));
+        segments.push(Segment::Argument(name));
+        rest = &inner[end + 2..];
+    }
+    segments.push(Segment::Text(rest));
+    Ok(segments)
+}
+
+impl ArgumentSpec {
+    // 按类型检查并规范化参数值，空值视为未传入
+    fn coerce(&self, value: &Value) -> Result<Option<String>, String> {
+        let text = match value {
+            Value::Null => return Ok(None),
+            Value::String(text) => text.clone(),
+            Value::Number(number) => number.to_string(),
+            Value::Bool(value) => value.to_string(),
+            _ => return Err(format!("expected a {}", self.kind)),
+        };
+        if text.trim().is_empty() {
+            return Ok(None);
+        }
+        let invalid = || format!("{:?} is not a valid {}", text, self.kind);
+        let text = match self.kind {
+            ArgumentType::String => text.clone(),
+            ArgumentType::Integer => text.trim().parse::<i64>().map_err(|_| invalid())?.to_string(),
+            ArgumentType::Number => text
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|number| number.is_finite())
+                .ok_or_else(invalid)?
+                .to_string(),
+            ArgumentType::Boolean => match text.trim() {
+                "true" => "true".to_string(),
+                "false" => "false".to_string(),
+                _ => return Err(invalid()),
+            },
+        };
+        if !self.choices.is_empty() && !self.choices.contains(&text) {
+            return Err(format!("{:?} is not one of {}", text, self.choices.join(", ")));
+        }
+        Ok(Some(text))
+    }
+}
+
+impl PromptTemplate {
+    /// 解析TOML格式的模板，模板中没有name时使用 `default_name`
+    pub fn from_toml(default_name: &str, contents: &str) -> Result<Self> {
+        let mut template: PromptTemplate = toml::from_str(contents)?;
+        if template.name.is_empty() {
+            template.name = default_name.to_string();
+        }
+        template.validate()?;
+        Ok(template)
+    }
+
+    /// 检查参数定义，以及消息中的占位符都对应已定义的参数
+    pub fn validate(&self) -> Result<()> {
+        if !is_valid_name(&self.name) {
+            anyhow::bail!("模板名 {:?} 只能包含字母、数字、下划线和连字符", self.name);
+        }
+        if self.messages.is_empty() {
+            anyhow::bail!("模板 {} 没有消息", self.name);
+        }
+        let mut names = HashSet::new();
+        for argument in &self.arguments {
+            if !is_valid_name(&argument.name) {
+                anyhow::bail!("参数名 {:?} 只能包含字母、数字、下划线和连字符", argument.name);
+            }
+            if !names.insert(argument.name.as_str()) {
+                anyhow::bail!("参数 {} 重复定义", argument.name);
+            }
+            for choice in &argument.choices {
+                argument
+                    .coerce(&Value::from(choice.as_str()))
+                    .map_err(|e| anyhow::anyhow!("参数 {} 的可选值不合法: {}", argument.name, e))?;
+            }
+            if let Some(default) = &argument.default {
+                argument
+                    .coerce(default)
+                    .map_err(|e| anyhow::anyhow!("参数 {} 的默认值不合法: {}", argument.name, e))?;
+            }
+        }
+        for message in &self.messages {
+            for segment in segments(&message.text).map_err(anyhow::Error::msg)? {
+                if let Segment::Argument(name) = segment {
+                    if !names.contains(name) {
+                        anyhow::bail!("消息中的占位符 {{{{{}}}}} 没有对应的参数", name);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// prompts/list中的描述
+    pub fn to_prompt(&self) -> Prompt {
+        let arguments = self
+            .arguments
+            .iter()
+            .map(|argument| PromptArgument {
+                name: argument.name.clone(),
+                description: argument_description(argument),
+                required: Some(argument.required),
+            })
+            .collect();
+        Prompt {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            arguments: Some(arguments),
+        }
+    }
+
+    /// 用参数值替换消息中的占位符
+    pub fn render(&self, arguments: &HashMap<String, Value>) -> Result<GetPromptResponse, PromptError> {
+        if let Some(name) = arguments
+            .keys()
+            .find(|name| !self.arguments.iter().any(|argument| &argument.name == *name))
+        {
+            return Err(PromptError::UnknownArgument(name.clone()));
+        }
+
+        let mut values = HashMap::new();
+        for argument in &self.arguments {
+            let invalid = |message| PromptError::InvalidArgument {
+                name: argument.name.clone(),
+                message,
+            };
+            let mut value = match arguments.get(&argument.name) {
+                Some(value) => argument.coerce(value).map_err(invalid)?,
+                None => None,
+            };
+            if value.is_none() {
+                if let Some(default) = &argument.default {
+                    value = argument.coerce(default).map_err(invalid)?;
+                }
+            }
+            if value.is_none() && argument.required {
+                return Err(PromptError::MissingArgument(argument.name.clone()));
+            }
+            values.insert(argument.name.as_str(), value.unwrap_or_default());
+        }
+
+        let messages = self
+            .messages
+            .iter()
+            .map(|message| {
+                // 占位符在加载时已经检查过
+                let text = segments(&message.text)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|segment| match segment {
+                        Segment::Text(text) => text,
+                        Segment::Argument(name) => values.get(name).map(String::as_str).unwrap_or_default(),
+                    })
+                    .collect();
+                PromptMessage {
+                    role: message.role,
+                    content: PromptContent::Text { text },
+                }
+            })
+            .collect();
+        Ok(GetPromptResponse {
+            description: self.description.clone(),
+            messages,
+        })
+    }
+}
+
+// MCP的参数描述没有类型字段，把类型、可选值和默认值写进描述里
+fn argument_description(argument: &ArgumentSpec) -> Option<String> {
+    let mut details = Vec::new();
+    if argument.kind != ArgumentType::String {
+        details.push(format!("类型: {}", argument.kind));
+    }
+    if !argument.choices.is_empty() {
+        details.push(format!("可选值: {}", argument.choices.join("、")));
+    }
+    if let Some(default) = argument.default.as_ref().and_then(|default| argument.coerce(default).ok().flatten()) {
+        details.push(format!("默认值: {}", default));
+    }
+    match (&argument.description, details.is_empty()) {
+        (description, true) => description.clone(),
+        (Some(description), false) => Some(format!("{}（{}）", description, details.join("，"))),
+        (None, false) => Some(details.join("，")),
+    }
+}
+
+/// 加载目录下的所有 `.toml` 模板，按模板名排序；目录不存在时没有模板
+pub fn load_dir(dir: &Path) -> Result<Vec<PromptTemplate>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut templates: Vec<PromptTemplate> = Vec::new();
+    let entries = fs::read_dir(dir).with_context(|| format!("无法读取提示词目录 {}", dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("toml") || !path.is_file() {
+            continue;
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("无法读取提示词模板 {}", path.display()))?;
+        let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+        let template = PromptTemplate::from_toml(stem, &contents)
+            .with_context(|| format!("提示词模板 {} 格式错误", path.display()))?;
+        if templates.iter().any(|existing| existing.name == template.name) {
+            anyhow::bail!("提示词模板 {} 与其他模板重名: {}", path.display(), template.name);
+        }
+        templates.push(template);
+    }
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(templates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const COMPARE: &str = r#"
+description = "比较两种GPU"
+
+[[arguments]]
+name = "gpu_a"
+required = true
+choices = ["H100", "A100"]
+
+[[arguments]]
+name = "gpu_b"
+required = true
+choices = ["H100", "A100"]
+
+[[arguments]]
+name = "batch_size"
+type = "integer"
+default = 8
+
+[[messages]]
+text = "比较 {{gpu_a}} 和 {{ gpu_b }}，batch size为{{batch_size}}。"
+
+[[messages]]
+role = "assistant"
+text = "好的。"
+"#;
+
+    fn text(response: &GetPromptResponse, index: usize) -> &str {
+        let PromptContent::Text { text } = &response.messages[index].content;
+        text
+    }
+
+    fn arguments(value: Value) -> HashMap<String, Value> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_render_typed_arguments() {
+        let template = PromptTemplate::from_toml("compare_gpus", COMPARE).unwrap();
+        assert_eq!(template.name, "compare_gpus");
+
+        let response = template.render(&arguments(json!({"gpu_a": "H100", "gpu_b": "A100"}))).unwrap();
+        assert_eq!(text(&response, 0), "比较 H100 和 A100，batch size为8。");
+        assert_eq!(response.messages[1].role, Role::Assistant);
+        assert_eq!(response.description.as_deref(), Some("比较两种GPU"));
+
+        // 数字可以以字符串或JSON数字传入，空字符串视为未传入
+        let response = template
+            .render(&arguments(json!({"gpu_a": "A100", "gpu_b": "H100", "batch_size": " 32 "})))
+            .unwrap();
+        assert_eq!(text(&response, 0), "比较 A100 和 H100，batch size为32。");
+        let response = template
+            .render(&arguments(json!({"gpu_a": "A100", "gpu_b": "H100", "batch_size": ""})))
+            .unwrap();
+        assert_eq!(text(&response, 0), "比较 A100 和 H100，batch size为8。");
+
+        assert_eq!(
+            template.render(&arguments(json!({"gpu_a": "H100"}))),
+            Err(PromptError::MissingArgument("gpu_b".to_string()))
+        );
+        assert!(matches!(
+            template.render(&arguments(json!({"gpu_a": "H100", "gpu_b": "V100"}))),
+            Err(PromptError::InvalidArgument { name, .. }) if name == "gpu_b"
+        ));
+        assert!(matches!(
+            template.render(&arguments(json!({"gpu_a": "H100", "gpu_b": "A100", "batch_size": "1.5"}))),
+            Err(PromptError::InvalidArgument { name, .. }) if name == "batch_size"
+        ));
+        assert_eq!(
+            template.render(&arguments(json!({"gpu_a": "H100", "gpu_b": "A100", "gpu_c": "B200"}))),
+            Err(PromptError::UnknownArgument("gpu_c".to_string()))
+        );
+
+        let prompt = template.to_prompt();
+        let arguments = prompt.arguments.unwrap();
+        assert_eq!(arguments[0].required, Some(true));
+        assert_eq!(arguments[0].description.as_deref(), Some("可选值: H100、A100"));
+        assert_eq!(arguments[2].description.as_deref(), Some("类型: integer，默认值: 8"));
+    }
+
+    #[test]
+    fn test_invalid_templates_are_rejected() {
+        let message = "[[messages]]\ntext = \"hi\"\n";
+        for invalid in [
+            // 占位符没有对应的参数
+            "[[messages]]\ntext = \"{{model}}\"\n".to_string(),
+            "[[messages]]\ntext = \"{{model\"\n".to_string(),
+            "[[messages]]\ntext = \"{{}}\"\n".to_string(),
+            // 没有消息
+            "description = \"x\"\n".to_string(),
+            "messages = []\n".to_string(),
+            format!("[[arguments]]\nname = \"a\"\n[[arguments]]\nname = \"a\"\n{}", message),
+            format!("[[arguments]]\nname = \"a\"\ntype = \"date\"\n{}", message),
+            format!("[[arguments]]\nname = \"a\"\ntype = \"integer\"\ndefault = \"many\"\n{}", message),
+            format!("[[arguments]]\nname = \"a\"\ntype = \"boolean\"\nchoices = [\"yes\"]\n{}", message),
+            format!("[[arguments]]\nname = \"a b\"\n{}", message),
+            format!("unknown = 1\n{}", message),
+        ] {
+            assert!(PromptTemplate::from_toml("test", &invalid).is_err(), "{}", invalid);
+        }
+        assert!(PromptTemplate::from_toml("bad name", message).is_err());
+    }
+
+    #[test]
+    fn test_load_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(load_dir(&dir.path().join("missing")).unwrap().is_empty());
+
+        fs::write(dir.path().join("compare_gpus.toml"), COMPARE).unwrap();
+        fs::write(dir.path().join("hello.toml"), "name = \"a_hello\"\n[[messages]]\ntext = \"hi\"\n").unwrap();
+        fs::write(dir.path().join("README.md"), "不是模板").unwrap();
+        let names: Vec<String> = load_dir(dir.path()).unwrap().into_iter().map(|t| t.name).collect();
+        assert_eq!(names, ["a_hello", "compare_gpus"]);
+
+        fs::write(dir.path().join("other.toml"), "name = \"a_hello\"\n[[messages]]\ntext = \"hi\"\n").unwrap();
+        assert!(load_dir(dir.path()).is_err());
+
+        // 随仓库提供的模板都能加载
+        let bundled = load_dir(&Path::new(env!("CARGO_MANIFEST_DIR")).join("prompts")).unwrap();
+        assert!(bundled.len() >= 3);
+    }
+}
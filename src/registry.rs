@@ -0,0 +1,331 @@
+//! 工具注册表：集中登记服务器提供的全部MCP工具，并按分组启用或禁用。
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+use mcp_core::tools::ToolHandlerFn;
+use mcp_core::types::{ResourceCapabilities, ServerCapabilities, Tool};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::resources::supplier_resources;
+use crate::server::Router;
+use crate::tools::*;
+
+/// 工具分组，每个工具只属于一个分组
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ToolGroup {
+    /// 加减法示例工具
+    Math,
+    /// 天使相关的演示工具
+    #[serde(alias = "demo")]
+    Angel,
+    /// GPU规格查询
+    GpuSpecs,
+    /// 通过wei-run调用Wei-Assistant-GPU的工具
+    WeiGpu,
+    /// 供应商信息管理
+    Suppliers,
+}
+
+impl ToolGroup {
+    pub const ALL: [ToolGroup; 5] = [
+        ToolGroup::Math,
+        ToolGroup::Angel,
+        ToolGroup::GpuSpecs,
+        ToolGroup::WeiGpu,
+        ToolGroup::Suppliers,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ToolGroup::Math => "math",
+            ToolGroup::Angel => "angel",
+            ToolGroup::GpuSpecs => "gpu-specs",
+            ToolGroup::WeiGpu => "wei-gpu",
+            ToolGroup::Suppliers => "suppliers",
+        }
+    }
+}
+
+impl fmt::Display for ToolGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ToolGroup {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "math" => Ok(ToolGroup::Math),
+            "angel" | "demo" => Ok(ToolGroup::Angel),
+            "gpu-specs" => Ok(ToolGroup::GpuSpecs),
+            "wei-gpu" => Ok(ToolGroup::WeiGpu),
+            "suppliers" => Ok(ToolGroup::Suppliers),
+            other => Err(anyhow::anyhow!(
+                "未知的工具分组: {}，可选值：math、angel(demo)、gpu-specs、wei-gpu、suppliers",
+                other
+            )),
+        }
+    }
+}
+
+/// 启用的工具分组集合，默认启用全部分组
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ToolGroups(BTreeSet<ToolGroup>);
+
+impl ToolGroups {
+    pub fn all() -> Self {
+        ToolGroups(ToolGroup::ALL.into_iter().collect())
+    }
+
+    pub fn none() -> Self {
+        ToolGroups(BTreeSet::new())
+    }
+
+    pub fn is_enabled(&self, group: ToolGroup) -> bool {
+        self.0.contains(&group)
+    }
+
+    pub fn enable(&mut self, group: ToolGroup) {
+        self.0.insert(group);
+    }
+
+    pub fn disable(&mut self, group: ToolGroup) {
+        self.0.remove(&group);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ToolGroup> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+impl Default for ToolGroups {
+    fn default() -> Self {
+        ToolGroups::all()
+    }
+}
+
+impl FromIterator<ToolGroup> for ToolGroups {
+    fn from_iter<I: IntoIterator<Item = ToolGroup>>(iter: I) -> Self {
+        ToolGroups(iter.into_iter().collect())
+    }
+}
+
+/// 解析逗号分隔的分组列表，例如 "math,wei-gpu"
+impl FromStr for ToolGroups {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .filter(|part| !part.trim().is_empty())
+            .map(ToolGroup::from_str)
+            .collect()
+    }
+}
+
+/// 注册表中的一个工具
+pub struct ToolEntry {
+    pub group: ToolGroup,
+    pub tool: Tool,
+    pub handler: ToolHandlerFn,
+}
+
+/// 服务器可提供的全部工具
+pub struct ToolRegistry {
+    entries: Vec<ToolEntry>,
+}
+
+impl ToolRegistry {
+    /// 创建包含全部内置工具的注册表
+    pub fn new() -> Self {
+        let mut registry = ToolRegistry {
+            entries: Vec::new(),
+        };
+
+        registry.register(ToolGroup::Math, AddTool::tool(), AddTool::call());
+        registry.register(ToolGroup::Math, SubTool::tool(), SubTool::call());
+
+        registry.register(ToolGroup::Angel, CheckAngel::tool(), CheckAngel::call());
+        registry.register(ToolGroup::Angel, QueryAngelType::tool(), QueryAngelType::call());
+
+        registry.register(ToolGroup::GpuSpecs, QueryGpuSpecs::tool(), QueryGpuSpecs::handler());
+        registry.register(ToolGroup::GpuSpecs, CompareGpus::tool(), CompareGpus::handler());
+        registry.register(ToolGroup::GpuSpecs, EstimateModelFit::tool(), EstimateModelFit::handler());
+
+        registry.register(ToolGroup::WeiGpu, GenerateText::tool(), GenerateText::handler());
+        registry.register(ToolGroup::WeiGpu, CreateEmbedding::tool(), CreateEmbedding::handler());
+        registry.register(ToolGroup::WeiGpu, LoadModel::tool(), LoadModel::handler());
+        registry.register(ToolGroup::WeiGpu, UnloadModel::tool(), UnloadModel::handler());
+        registry.register(ToolGroup::WeiGpu, ListModels::tool(), ListModels::handler());
+        registry.register(ToolGroup::WeiGpu, ModelStatus::tool(), ModelStatus::handler());
+        registry.register(ToolGroup::WeiGpu, SubmitGeneration::tool(), SubmitGeneration::handler());
+        registry.register(ToolGroup::WeiGpu, SubmitJob::tool(), SubmitJob::handler());
+        registry.register(ToolGroup::WeiGpu, JobStatus::tool(), JobStatus::handler());
+        registry.register(ToolGroup::WeiGpu, JobResult::tool(), JobResult::handler());
+        registry.register(ToolGroup::WeiGpu, CancelJob::tool(), CancelJob::handler());
+        registry.register(ToolGroup::WeiGpu, ListLocalGpus::tool(), ListLocalGpus::handler());
+
+        registry.register(ToolGroup::Suppliers, AddSupplier::tool(), AddSupplier::handler());
+        registry.register(ToolGroup::Suppliers, QuerySuppliers::tool(), QuerySuppliers::handler());
+        registry.register(ToolGroup::Suppliers, UpdateSupplier::tool(), UpdateSupplier::handler());
+        registry.register(ToolGroup::Suppliers, DeleteSupplier::tool(), DeleteSupplier::handler());
+        registry.register(ToolGroup::Suppliers, ImportSuppliers::tool(), ImportSuppliers::handler());
+        registry.register(ToolGroup::Suppliers, ExportSuppliersCsv::tool(), ExportSuppliersCsv::handler());
+
+        registry
+    }
+
+    pub fn register(&mut self, group: ToolGroup, tool: Tool, handler: ToolHandlerFn) {
+        self.entries.push(ToolEntry {
+            group,
+            tool,
+            handler,
+        });
+    }
+
+    pub fn entries(&self) -> &[ToolEntry] {
+        &self.entries
+    }
+
+    /// 返回属于已启用分组的工具
+    pub fn enabled<'a>(&'a self, groups: &'a ToolGroups) -> impl Iterator<Item = &'a ToolEntry> {
+        self.entries
+            .iter()
+            .filter(move |entry| groups.is_enabled(entry.group))
+    }
+
+    /// 已启用工具的名称列表
+    pub fn tool_names(&self, groups: &ToolGroups) -> Vec<String> {
+        self.enabled(groups)
+            .map(|entry| entry.tool.name.clone())
+            .collect()
+    }
+
+    /// 构建带有已启用工具的请求路由；启用suppliers分组时同时提供供应商资源
+    pub fn build_router(&self, name: &str, version: &str, groups: &ToolGroups) -> Router {
+        let suppliers = groups.is_enabled(ToolGroup::Suppliers);
+        let capabilities = ServerCapabilities {
+            tools: Some(json!({
+                "listChanged": false,
+            })),
+            resources: suppliers.then_some(ResourceCapabilities {
+                subscribe: Some(false),
+                list_changed: Some(false),
+            }),
+            ..Default::default()
+        };
+        let router = self
+            .enabled(groups)
+            .fold(Router::new(name, version, capabilities), |router, entry| {
+                router.tool(entry.tool.clone(), entry.handler)
+            });
+        if suppliers {
+            router.resources(supplier_resources())
+        } else {
+            router
+        }
+    }
+}
+
+impl Default for ToolRegistry {
+    fn default() -> Self {
+        ToolRegistry::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_contains_all_tools() {
+        let registry = ToolRegistry::new();
+        let names = registry.tool_names(&ToolGroups::all());
+
+        for name in [
+            "Add",
+            "Sub",
+            "CheckAngel",
+            "QueryAngelType",
+            "QueryGPUSpecs",
+            "CompareGPUs",
+            "EstimateModelFit",
+            "GenerateText",
+            "CreateEmbedding",
+            "LoadModel",
+            "UnloadModel",
+            "ListModels",
+            "ModelStatus",
+            "SubmitGeneration",
+            "SubmitJob",
+            "JobStatus",
+            "JobResult",
+            "CancelJob",
+            "ListLocalGpus",
+            "AddSupplier",
+            "QuerySuppliers",
+            "UpdateSupplier",
+            "DeleteSupplier",
+            "ImportSuppliers",
+            "ExportSuppliersCsv",
+        ] {
+            assert!(names.contains(&name.to_string()), "缺少工具 {}", name);
+        }
+        assert_eq!(names.len(), 25);
+    }
+
+    #[test]
+    fn test_disabled_groups_are_filtered() {
+        let registry = ToolRegistry::new();
+        let mut groups = ToolGroups::all();
+        groups.disable(ToolGroup::WeiGpu);
+        groups.disable(ToolGroup::Angel);
+        groups.disable(ToolGroup::Suppliers);
+
+        let names = registry.tool_names(&groups);
+        assert_eq!(names, vec!["Add", "Sub", "QueryGPUSpecs", "CompareGPUs", "EstimateModelFit"]);
+
+        assert!(registry.tool_names(&ToolGroups::none()).is_empty());
+    }
+
+    #[test]
+    fn test_resources_capability_follows_suppliers_group() {
+        let registry = ToolRegistry::new();
+        let mut groups = ToolGroups::all();
+        let router = registry.build_router("test", "1.0", &groups);
+        assert!(router.capabilities().resources.is_some());
+
+        groups.disable(ToolGroup::Suppliers);
+        let router = registry.build_router("test", "1.0", &groups);
+        assert!(router.capabilities().resources.is_none());
+    }
+
+    #[test]
+    fn test_parse_tool_groups() {
+        let groups: ToolGroups = "math, demo,wei-gpu".parse().unwrap();
+        assert!(groups.is_enabled(ToolGroup::Math));
+        assert!(groups.is_enabled(ToolGroup::Angel));
+        assert!(groups.is_enabled(ToolGroup::WeiGpu));
+        assert!(!groups.is_enabled(ToolGroup::GpuSpecs));
+
+        assert!("math,unknown".parse::<ToolGroups>().is_err());
+        assert_eq!("".parse::<ToolGroups>().unwrap(), ToolGroups::none());
+    }
+
+    #[test]
+    fn test_tool_groups_serde() {
+        let groups: ToolGroups = serde_json::from_str(r#"["gpu-specs", "demo"]"#).unwrap();
+        assert!(groups.is_enabled(ToolGroup::GpuSpecs));
+        assert!(groups.is_enabled(ToolGroup::Angel));
+        assert_eq!(
+            serde_json::to_string(&groups).unwrap(),
+            r#"["angel","gpu-specs"]"#
+        );
+    }
+}
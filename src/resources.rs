@@ -0,0 +1,199 @@
+//! 以MCP资源的形式提供供应商信息，客户端可以把供应商记录作为上下文附加到对话中，
+//! 而不必调用工具。
+//!
+//! - `supplier://{id}`：一条供应商记录
+//! - `supplier://query?...`：查询结果，参数为 `字段=值`（等于）或 `字段.操作符=值`，
+//!   操作符同QuerySuppliers，另外支持 `order_by`、`limit`、`offset`；默认最多返回100条
+
+use mcp_core::types::Resource;
+use serde_json::{json, Value as JsonValue};
+use url::Url;
+
+use crate::server::resources::{
+    ResourceError, ResourcePage, ResourceProvider, ResourceTemplate, TextResourceContents,
+};
+use crate::suppliers::{
+    self, field_type, FieldType, Filter, FilterOp, Supplier, SupplierError, SupplierQuery,
+};
+
+/// resources/list每页的记录数，也是查询资源默认的最大记录数
+const PAGE_SIZE: u64 = 100;
+
+const JSON_MIME_TYPE: &str = "application/json";
+
+/// 供应商资源的提供者
+pub fn supplier_resources() -> ResourceProvider {
+    ResourceProvider {
+        scheme: "supplier",
+        templates: vec![
+            ResourceTemplate {
+                uri_template: "supplier://{id}".to_string(),
+                name: "供应商记录".to_string(),
+                description: Some("按id读取一条供应商记录".to_string()),
+                mime_type: Some(JSON_MIME_TYPE.to_string()),
+            },
+            ResourceTemplate {
+                uri_template: "supplier://query{?contact,location,server_name,price.lte,quantity.gte,order_by,limit,offset}"
+                    .to_string(),
+                name: "供应商查询".to_string(),
+                description: Some(
+                    "查询供应商，参数为 字段=值 或 字段.操作符=值（操作符同QuerySuppliers，如 price.lt=10000、location.like=北京），\
+                     多个条件之间为AND；返回 {\"total\", \"count\", \"suppliers\"}，默认最多100条"
+                        .to_string(),
+                ),
+                mime_type: Some(JSON_MIME_TYPE.to_string()),
+            },
+        ],
+        list: |cursor| Box::pin(list_suppliers(cursor)),
+        read: |uri| Box::pin(read_supplier(uri)),
+    }
+}
+
+fn resource_error(uri: &Url, error: SupplierError) -> ResourceError {
+    match error {
+        SupplierError::InvalidArguments(message) => ResourceError::InvalidUri(format!("{}: {}", uri, message)),
+        SupplierError::NotFound(_) => ResourceError::NotFound(uri.to_string()),
+        other => ResourceError::Internal(other.to_string()),
+    }
+}
+
+fn supplier_uri(id: i64) -> Url {
+    Url::parse(&format!("supplier://{}", id)).expect("supplier URI is valid")
+}
+
+// 列表中的资源名为联系人，说明为服务器名称和地点
+fn to_resource(supplier: &Supplier) -> Resource {
+    let details: Vec<&str> = [&supplier.server_name, &supplier.location]
+        .into_iter()
+        .filter_map(|field| field.as_deref())
+        .collect();
+    Resource {
+        uri: supplier_uri(supplier.id.unwrap_or_default()),
+        name: supplier.contact.clone(),
+        description: (!details.is_empty()).then(|| details.join("，")),
+        mime_type: Some(JSON_MIME_TYPE.to_string()),
+    }
+}
+
+/// 按id顺序分页列出所有供应商，游标为下一页的偏移量
+async fn list_suppliers(cursor: Option<String>) -> Result<ResourcePage, ResourceError> {
+    let offset = match &cursor {
+        Some(cursor) => cursor
+            .parse::<u64>()
+            .map_err(|_| ResourceError::InvalidUri(format!("invalid cursor {:?}", cursor)))?,
+        None => 0,
+    };
+    let query = SupplierQuery::new().limit(PAGE_SIZE).offset(offset);
+    let (total, found) = suppliers::with_store(move |store| Ok((store.count(&query)?, store.query(&query)?)))
+        .await
+        .map_err(|e| ResourceError::Internal(e.to_string()))?;
+    let next = offset + found.len() as u64;
+    Ok(ResourcePage {
+        resources: found.iter().map(to_resource).collect(),
+        next_cursor: (next < total && !found.is_empty()).then(|| next.to_string()),
+    })
+}
+
+async fn read_supplier(uri: Url) -> Result<Vec<TextResourceContents>, ResourceError> {
+    if !matches!(uri.path(), "" | "/") {
+        return Err(ResourceError::NotFound(uri.to_string()));
+    }
+    let text = match uri.host_str() {
+        Some("query") => {
+            let query = parse_query(&uri).map_err(|e| resource_error(&uri, e))?;
+            let (total, found) =
+                suppliers::with_store(move |store| Ok((store.count(&query)?, store.query(&query)?)))
+                    .await
+                    .map_err(|e| resource_error(&uri, e))?;
+            json!({
+                "total": total,
+                "count": found.len(),
+                "suppliers": found,
+            })
+            .to_string()
+        }
+        Some(id) => {
+            let id: i64 = id.parse().map_err(|_| ResourceError::NotFound(uri.to_string()))?;
+            let supplier = suppliers::with_store(move |store| store.get(id)?.ok_or(SupplierError::NotFound(id)))
+                .await
+                .map_err(|e| resource_error(&uri, e))?;
+            serde_json::to_string(&supplier).unwrap_or_default()
+        }
+        None => return Err(ResourceError::NotFound(uri.to_string())),
+    };
+    Ok(vec![TextResourceContents {
+        uri: uri.to_string(),
+        mime_type: Some(JSON_MIME_TYPE.to_string()),
+        text,
+    }])
+}
+
+/// 把 `supplier://query?...` 的参数转换为查询
+pub fn parse_query(uri: &Url) -> Result<SupplierQuery, SupplierError> {
+    let mut query = SupplierQuery::new();
+    let mut limit = PAGE_SIZE;
+    for (key, value) in uri.query_pairs() {
+        let invalid =
+            |what: &str| SupplierError::InvalidArguments(format!("{} {:?} is not a valid {}", key, value, what));
+        match key.as_ref() {
+            "order_by" => query = query.order_by(&value)?,
+            "limit" => limit = value.parse().map_err(|_| invalid("number"))?,
+            "offset" => query = query.offset(value.parse().map_err(|_| invalid("number"))?),
+            _ => {
+                let (field, op) = key.split_once('.').unwrap_or((&key, "eq"));
+                let op: FilterOp = serde_json::from_value(json!(op))
+                    .map_err(|_| SupplierError::InvalidArguments(format!("unknown operator {:?} in {:?}", op, key)))?;
+                // 数值列的值按数字传入，不是数字时保留原文，由过滤条件给出错误
+                let value = match field_type(field) {
+                    Some(FieldType::Integer | FieldType::Real) => value
+                        .parse::<serde_json::Number>()
+                        .map(JsonValue::Number)
+                        .unwrap_or_else(|_| JsonValue::from(value.as_ref())),
+                    _ => JsonValue::from(value.as_ref()),
+                };
+                let value = (!matches!(op, FilterOp::IsNull | FilterOp::NotNull)).then_some(value);
+                query = query.filter(&Filter::new(field, op, value))?;
+            }
+        }
+    }
+    Ok(query.limit(limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(uri: &str) -> Result<SupplierQuery, SupplierError> {
+        parse_query(&Url::parse(uri).unwrap())
+    }
+
+    #[test]
+    fn test_parse_query_uri() {
+        let expected = SupplierQuery::new()
+            .filter(&Filter::new("location", FilterOp::Eq, json!("北京")))
+            .unwrap()
+            .filter(&Filter::new("price", FilterOp::Lt, json!(10000)))
+            .unwrap()
+            .filter(&Filter::new("wechat", FilterOp::IsNull, None))
+            .unwrap()
+            .order_by("price desc")
+            .unwrap()
+            .offset(20)
+            .limit(10);
+        let parsed = query("supplier://query?location=%E5%8C%97%E4%BA%AC&price.lt=10000&wechat.is_null&order_by=price+desc&limit=10&offset=20");
+        assert_eq!(parsed.unwrap(), expected);
+
+        // 默认最多返回一页
+        assert_eq!(query("supplier://query").unwrap(), SupplierQuery::new().limit(PAGE_SIZE));
+
+        for invalid in [
+            "supplier://query?password=x",
+            "supplier://query?price.between=1",
+            "supplier://query?price.gt=cheap",
+            "supplier://query?limit=-1",
+            "supplier://query?order_by=price;drop",
+        ] {
+            assert!(matches!(query(invalid), Err(SupplierError::InvalidArguments(_))), "{}", invalid);
+        }
+    }
+}
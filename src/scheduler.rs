@@ -0,0 +1,393 @@
+//! GPU任务调度：限制同时运行的wei-run任务数量，其余任务按优先级排队。
+//!
+//! 同一优先级内先到先得；队列已满时新任务立即返回busy错误，而不是无限等待。
+//! 等待中的任务被取消或被丢弃时会离开队列，已分配给它的名额转交给下一个任务。
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::SchedulerConfig;
+
+/// 任务优先级
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Priority::Low => "low",
+            Priority::Normal => "normal",
+            Priority::High => "high",
+        })
+    }
+}
+
+impl FromStr for Priority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "low" => Ok(Priority::Low),
+            "normal" => Ok(Priority::Normal),
+            "high" => Ok(Priority::High),
+            other => Err(format!("unknown priority {:?}, expected low, normal or high", other)),
+        }
+    }
+}
+
+/// 无法获得运行名额
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ScheduleError {
+    #[error("GPU is busy: {queued} jobs already waiting (limit {limit}), try again later")]
+    Busy { queued: usize, limit: usize },
+    #[error("job cancelled while waiting in the queue")]
+    Cancelled,
+}
+
+// 队列按 (优先级从高到低, 到达顺序) 排序
+type QueueKey = (Reverse<Priority>, u64);
+
+struct State {
+    running: usize,
+    queue: BTreeMap<QueueKey, oneshot::Sender<()>>,
+    next_seq: u64,
+}
+
+impl State {
+    // 把名额交给队首仍在等待的任务，没有等待的任务时归还名额
+    fn release(&mut self) {
+        while let Some((_, waiter)) = self.queue.pop_first() {
+            if waiter.send(()).is_ok() {
+                return;
+            }
+        }
+        self.running -= 1;
+    }
+}
+
+/// GPU任务调度器
+pub struct Scheduler {
+    max_concurrent: usize,
+    max_queue: usize,
+    state: Mutex<State>,
+}
+
+impl Scheduler {
+    pub fn new(config: &SchedulerConfig) -> Arc<Self> {
+        Arc::new(Scheduler {
+            max_concurrent: config.max_concurrent_jobs.max(1),
+            max_queue: config.max_queue_depth,
+            state: Mutex::new(State {
+                running: 0,
+                queue: BTreeMap::new(),
+                next_seq: 0,
+            }),
+        })
+    }
+
+    /// 等待一个运行名额，返回的许可在丢弃时释放名额
+    pub async fn acquire(
+        self: &Arc<Self>,
+        priority: Priority,
+        cancel: &CancellationToken,
+    ) -> Result<JobPermit, ScheduleError> {
+        let start = Instant::now();
+        let (key, rx) = {
+            let mut state = self.state.lock().unwrap();
+            if state.running < self.max_concurrent && state.queue.is_empty() {
+                state.running += 1;
+                return Ok(self.permit(Duration::ZERO));
+            }
+            if state.queue.len() >= self.max_queue {
+                return Err(ScheduleError::Busy {
+                    queued: state.queue.len(),
+                    limit: self.max_queue,
+                });
+            }
+            state.next_seq += 1;
+            let key = (Reverse(priority), state.next_seq);
+            let (tx, rx) = oneshot::channel();
+            state.queue.insert(key, tx);
+            (key, rx)
+        };
+
+        let mut waiting = Waiting {
+            scheduler: self,
+            key: Some(key),
+        };
+        tokio::select! {
+            granted = rx => match granted {
+                Ok(()) => {
+                    waiting.key = None;
+                    Ok(self.permit(start.elapsed()))
+                }
+                Err(_) => Err(ScheduleError::Cancelled),
+            },
+            _ = cancel.cancelled() => Err(ScheduleError::Cancelled),
+        }
+    }
+
+    fn permit(self: &Arc<Self>, queue_wait: Duration) -> JobPermit {
+        JobPermit {
+            scheduler: self.clone(),
+            queue_wait,
+        }
+    }
+
+    /// 正在运行的任务数量
+    pub fn running(&self) -> usize {
+        self.state.lock().unwrap().running
+    }
+
+    /// 排队等待的任务数量
+    pub fn queued(&self) -> usize {
+        self.state.lock().unwrap().queue.len()
+    }
+}
+
+// 离开队列：仍在排队时移除自己；名额已经分配给自己但还没有使用时，把它交给下一个任务
+struct Waiting<'a> {
+    scheduler: &'a Scheduler,
+    key: Option<QueueKey>,
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key {
+            let mut state = self.scheduler.state.lock().unwrap();
+            if state.queue.remove(&key).is_none() {
+                state.release();
+            }
+        }
+    }
+}
+
+/// 运行名额，丢弃时释放
+pub struct JobPermit {
+    scheduler: Arc<Scheduler>,
+    /// 在队列中等待的时间
+    pub queue_wait: Duration,
+}
+
+impl Drop for JobPermit {
+    fn drop(&mut self) {
+        self.scheduler.state.lock().unwrap().release();
+    }
+}
+
+static SCHEDULER: RwLock<Option<Arc<Scheduler>>> = RwLock::new(None);
+
+/// 按配置创建全局调度器
+pub fn configure(config: &SchedulerConfig) {
+    if let Ok(mut scheduler) = SCHEDULER.write() {
+        *scheduler = Some(Scheduler::new(config));
+    }
+}
+
+/// 全局调度器，未配置时使用默认配置
+pub fn global() -> Arc<Scheduler> {
+    if let Some(scheduler) = SCHEDULER.read().ok().and_then(|s| s.clone()) {
+        return scheduler;
+    }
+    let mut scheduler = SCHEDULER.write().unwrap();
+    scheduler
+        .get_or_insert_with(|| Scheduler::new(&SchedulerConfig::default()))
+        .clone()
+}
+
+/// 一次工具调用中所有GPU任务的排队统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JobStats {
+    pub jobs: u32,
+    pub queue_wait: Duration,
+}
+
+tokio::task_local! {
+    static STATS: Arc<Mutex<JobStats>>;
+}
+
+/// 执行 `future` 并收集其中通过 [`acquire`] 获得的任务的排队时间
+pub async fn track<F: Future>(future: F) -> (F::Output, JobStats) {
+    let stats = Arc::new(Mutex::new(JobStats::default()));
+    let output = STATS.scope(stats.clone(), future).await;
+    let stats = *stats.lock().unwrap();
+    (output, stats)
+}
+
+/// 从全局调度器获取运行名额，并记录排队时间
+pub async fn acquire(priority: Priority, cancel: &CancellationToken) -> Result<JobPermit, ScheduleError> {
+    let permit = global().acquire(priority, cancel).await?;
+    let _ = STATS.try_with(|stats| {
+        let mut stats = stats.lock().unwrap();
+        stats.jobs += 1;
+        stats.queue_wait += permit.queue_wait;
+    });
+    Ok(permit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduler(max_concurrent_jobs: usize, max_queue_depth: usize) -> Arc<Scheduler> {
+        Scheduler::new(&SchedulerConfig {
+            max_concurrent_jobs,
+            max_queue_depth,
+        })
+    }
+
+    // 等待直到队列中有n个任务
+    async fn wait_queued(scheduler: &Scheduler, n: usize) {
+        for _ in 0..200 {
+            if scheduler.queued() == n {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        panic!("队列中的任务数量没有达到 {}", n);
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_limit() {
+        let scheduler = scheduler(2, 4);
+        let cancel = CancellationToken::new();
+
+        let a = scheduler.acquire(Priority::Normal, &cancel).await.unwrap();
+        let _b = scheduler.acquire(Priority::Normal, &cancel).await.unwrap();
+        assert_eq!(a.queue_wait, Duration::ZERO);
+        assert_eq!(scheduler.running(), 2);
+
+        let waiter = {
+            let scheduler = scheduler.clone();
+            tokio::spawn(async move {
+                scheduler
+                    .acquire(Priority::Normal, &CancellationToken::new())
+                    .await
+                    .map(|permit| permit.queue_wait)
+            })
+        };
+        wait_queued(&scheduler, 1).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiter.is_finished());
+
+        // 释放一个名额后排队的任务开始运行，结束后归还名额
+        drop(a);
+        let wait = waiter.await.unwrap().unwrap();
+        assert!(wait >= Duration::from_millis(50));
+        assert_eq!(scheduler.running(), 1);
+        assert_eq!(scheduler.queued(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_priority_then_fifo_order() {
+        let scheduler = scheduler(1, 8);
+        let running = scheduler
+            .acquire(Priority::Normal, &CancellationToken::new())
+            .await
+            .unwrap();
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let jobs = [
+            ("low", Priority::Low),
+            ("normal-1", Priority::Normal),
+            ("high", Priority::High),
+            ("normal-2", Priority::Normal),
+        ];
+        for (i, (name, priority)) in jobs.into_iter().enumerate() {
+            let job_scheduler = scheduler.clone();
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                let _permit = job_scheduler.acquire(priority, &CancellationToken::new()).await.unwrap();
+                tx.send(name).unwrap();
+            });
+            // 保证到达顺序
+            wait_queued(&scheduler, i + 1).await;
+        }
+
+        drop(running);
+        let mut order = Vec::new();
+        for _ in 0..4 {
+            order.push(rx.recv().await.unwrap());
+        }
+        assert_eq!(order, vec!["high", "normal-1", "normal-2", "low"]);
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_is_rejected() {
+        let scheduler = scheduler(1, 1);
+        let _running = scheduler
+            .acquire(Priority::Normal, &CancellationToken::new())
+            .await
+            .unwrap();
+
+        let queued = {
+            let scheduler = scheduler.clone();
+            tokio::spawn(async move {
+                scheduler
+                    .acquire(Priority::Normal, &CancellationToken::new())
+                    .await
+                    .map(|_| ())
+            })
+        };
+        wait_queued(&scheduler, 1).await;
+
+        let err = scheduler
+            .acquire(Priority::High, &CancellationToken::new())
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err, ScheduleError::Busy { queued: 1, limit: 1 });
+        queued.abort();
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_waiter_leaves_queue() {
+        let scheduler = scheduler(1, 4);
+        let running = scheduler
+            .acquire(Priority::Normal, &CancellationToken::new())
+            .await
+            .unwrap();
+
+        let cancel = CancellationToken::new();
+        let waiter = {
+            let scheduler = scheduler.clone();
+            let cancel = cancel.clone();
+            tokio::spawn(async move { scheduler.acquire(Priority::High, &cancel).await.map(|_| ()) })
+        };
+        wait_queued(&scheduler, 1).await;
+        cancel.cancel();
+        assert_eq!(waiter.await.unwrap(), Err(ScheduleError::Cancelled));
+        assert_eq!(scheduler.queued(), 0);
+
+        // 被取消的任务不会占用名额
+        drop(running);
+        assert_eq!(scheduler.running(), 0);
+        let _permit = scheduler
+            .acquire(Priority::Normal, &CancellationToken::new())
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_parse_priority() {
+        assert_eq!("HIGH".parse::<Priority>().unwrap(), Priority::High);
+        assert_eq!(" low ".parse::<Priority>().unwrap(), Priority::Low);
+        assert!("urgent".parse::<Priority>().is_err());
+        assert!(Priority::High > Priority::Normal && Priority::Normal > Priority::Low);
+    }
+}
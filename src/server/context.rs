@@ -0,0 +1,88 @@
+//! 当前请求的上下文。
+//!
+//! 工具处理函数是普通的函数指针，无法捕获会话状态；传输层在执行每个请求时
+//! 通过task-local设置上下文，工具内部可以取得取消令牌等信息。
+
+use std::future::Future;
+
+use mcp_core::transport::{JsonRpcMessage, JsonRpcNotification, RequestId};
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+#[derive(Clone)]
+pub struct RequestContext {
+    pub request_id: RequestId,
+    /// 客户端发送 notifications/cancelled 或断开连接时触发
+    pub cancel: CancellationToken,
+    /// 请求 `_meta.progressToken` 中的令牌，客户端没有提供时不发送进度通知
+    pub progress_token: Option<Value>,
+    pub(crate) outgoing: mpsc::Sender<JsonRpcMessage>,
+}
+
+impl RequestContext {
+    /// 不属于任何客户端请求的上下文，供后台任务使用；不会发送进度通知
+    pub fn detached(cancel: CancellationToken) -> Self {
+        let (outgoing, _) = mpsc::channel(1);
+        RequestContext {
+            request_id: 0,
+            cancel,
+            progress_token: None,
+            outgoing,
+        }
+    }
+}
+
+tokio::task_local! {
+    static CURRENT: RequestContext;
+}
+
+/// 在给定上下文中执行请求
+pub async fn scope<F: Future>(context: RequestContext, future: F) -> F::Output {
+    CURRENT.scope(context, future).await
+}
+
+/// 当前请求的上下文，不在请求中执行时返回None
+pub fn current() -> Option<RequestContext> {
+    CURRENT.try_with(|context| context.clone()).ok()
+}
+
+/// 当前请求的取消令牌；不在请求中执行时返回一个永远不会触发的令牌
+pub fn cancellation_token() -> CancellationToken {
+    current()
+        .map(|context| context.cancel)
+        .unwrap_or_default()
+}
+
+/// 当前请求的进度令牌
+pub fn progress_token() -> Option<Value> {
+    current().and_then(|context| context.progress_token)
+}
+
+/// 向客户端发送 `notifications/progress`。
+///
+/// `message` 携带本次新增的内容；请求没有进度令牌时不发送任何消息。
+pub async fn report_progress(progress: f64, message: Option<String>) {
+    let Some(context) = current() else {
+        return;
+    };
+    let Some(token) = context.progress_token else {
+        return;
+    };
+
+    let mut params = json!({
+        "progressToken": token,
+        "progress": progress,
+    });
+    if let Some(message) = message {
+        params["message"] = json!(message);
+    }
+    let notification = JsonRpcMessage::Notification(JsonRpcNotification {
+        method: "notifications/progress".to_string(),
+        params: Some(params),
+        jsonrpc: Default::default(),
+    });
+    if context.outgoing.send(notification).await.is_err() {
+        tracing::debug!("会话已关闭，丢弃请求 {} 的进度通知", context.request_id);
+    }
+}
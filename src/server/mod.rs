@@ -0,0 +1,651 @@
+//! MCP服务器的请求分发与会话管理。
+//!
+//! 每个请求在独立的任务中执行，长时间运行的工具不会阻塞同一会话中的其他消息，
+//! 客户端可以随时通过 `notifications/cancelled` 取消正在执行的请求。
+//!
+//! 不使用mcp-core 0.1的 `Server` 和传输层，因为它们无法支持取消：
+//!
+//! - `Protocol::handle_request` 在整个请求执行期间持有处理函数表的锁，
+//!   SSE传输还持有会话表的锁，同一时间只能执行一个请求；
+//! - stdio传输处理完一个请求才读取下一行，执行期间收不到 `notifications/cancelled`；
+//! - 工具处理函数只能拿到 `CallToolRequest`，不知道JSON-RPC请求id，无法与取消通知对应，
+//!   也无法在执行过程中发送 `notifications/progress`。
+//!
+//! 这里只替换了消息分发和传输，消息和工具的类型仍然使用mcp-core的定义，
+//! `#[tool]` 生成的处理函数可以直接注册。
+
+pub mod context;
+pub mod prompts;
+pub mod resources;
+pub mod sse;
+pub mod stdio;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use mcp_core::tools::ToolHandlerFn;
+use mcp_core::transport::{JsonRpcError, JsonRpcMessage, JsonRpcRequest, JsonRpcResponse, RequestId};
+use mcp_core::types::{
+    CallToolRequest, ErrorCode, Implementation, InitializeRequest, InitializeResponse, ListRequest,
+    PromptCapabilities, PromptsListResponse, ReadResourceRequest, ResourcesListResponse, ServerCapabilities, Tool,
+    ToolsListResponse, LATEST_PROTOCOL_VERSION,
+};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::prompts::{PromptError, PromptTemplate};
+
+use self::context::RequestContext;
+use self::prompts::{GetPromptRequest, GetPromptResponse};
+use self::resources::{
+    ReadResourceResponse, ResourceError, ResourceProvider, ResourceTemplatesListResponse,
+};
+
+/// 会话发送队列的容量
+const OUTGOING_CAPACITY: usize = 100;
+
+/// 请求路由：服务器信息、能力声明、已注册的工具、资源和提示词模板
+pub struct Router {
+    server_info: Implementation,
+    capabilities: ServerCapabilities,
+    tools: Vec<(Tool, ToolHandlerFn)>,
+    tool_index: HashMap<String, usize>,
+    resources: Vec<ResourceProvider>,
+    prompts: Vec<PromptTemplate>,
+}
+
+impl Router {
+    pub fn new(name: &str, version: &str, capabilities: ServerCapabilities) -> Self {
+        Router {
+            server_info: Implementation {
+                name: name.to_string(),
+                version: version.to_string(),
+            },
+            capabilities,
+            tools: Vec::new(),
+            tool_index: HashMap::new(),
+            resources: Vec::new(),
+            prompts: Vec::new(),
+        }
+    }
+
+    /// 注册工具，同名工具会替换之前的注册
+    pub fn tool(mut self, tool: Tool, handler: ToolHandlerFn) -> Self {
+        match self.tool_index.get(&tool.name) {
+            Some(&index) => self.tools[index] = (tool, handler),
+            None => {
+                self.tool_index.insert(tool.name.clone(), self.tools.len());
+                self.tools.push((tool, handler));
+            }
+        }
+        self
+    }
+
+    pub fn tools(&self) -> impl Iterator<Item = &Tool> {
+        self.tools.iter().map(|(tool, _)| tool)
+    }
+
+    pub fn capabilities(&self) -> &ServerCapabilities {
+        &self.capabilities
+    }
+
+    /// 注册资源提供者，同一scheme的提供者会替换之前的注册。
+    ///
+    /// 没有注册任何提供者时，resources相关的方法返回MethodNotFound。
+    pub fn resources(mut self, provider: ResourceProvider) -> Self {
+        self.resources.retain(|existing| existing.scheme != provider.scheme);
+        self.resources.push(provider);
+        self
+    }
+
+    /// 注册提示词模板，同名模板会替换之前的注册；注册了模板时声明prompts能力。
+    ///
+    /// 没有任何模板时，prompts相关的方法返回MethodNotFound。
+    pub fn prompts(mut self, templates: impl IntoIterator<Item = PromptTemplate>) -> Self {
+        for template in templates {
+            self.prompts.retain(|existing| existing.name != template.name);
+            self.prompts.push(template);
+        }
+        if !self.prompts.is_empty() {
+            self.capabilities.prompts = Some(PromptCapabilities {
+                list_changed: Some(false),
+            });
+        }
+        self
+    }
+
+    fn get_prompt(&self, request: GetPromptRequest) -> Result<GetPromptResponse, PromptError> {
+        self.prompts
+            .iter()
+            .find(|template| template.name == request.name)
+            .ok_or(PromptError::NotFound(request.name.clone()))?
+            .render(&request.arguments)
+    }
+
+    // 依次列出各提供者的资源，游标记录当前的提供者
+    async fn list_resources(&self, cursor: Option<&str>) -> Result<ResourcesListResponse, ResourceError> {
+        let (index, inner) = resources::split_cursor(cursor)?;
+        let Some(provider) = self.resources.get(index) else {
+            return Err(ResourceError::InvalidUri(format!("invalid cursor {:?}", cursor.unwrap_or_default())));
+        };
+        let page = (provider.list)(inner).await?;
+        let next_cursor = match page.next_cursor {
+            Some(next) => Some(resources::join_cursor(index, Some(&next))),
+            None if index + 1 < self.resources.len() => Some(resources::join_cursor(index + 1, None)),
+            None => None,
+        };
+        Ok(ResourcesListResponse {
+            resources: page.resources,
+            next_cursor,
+            meta: None,
+        })
+    }
+
+    async fn read_resource(&self, uri: url::Url) -> Result<ReadResourceResponse, ResourceError> {
+        let provider = self
+            .resources
+            .iter()
+            .find(|provider| provider.scheme == uri.scheme())
+            .ok_or_else(|| ResourceError::NotFound(uri.to_string()))?;
+        Ok(ReadResourceResponse {
+            contents: (provider.read)(uri).await?,
+        })
+    }
+
+    async fn handle_request(&self, session: &Session, request: JsonRpcRequest) -> JsonRpcResponse {
+        let id = request.id;
+        let method = request.method.as_str();
+
+        if method != "initialize" && method != "ping" && !session.is_initialized() {
+            return error_response(
+                id,
+                ErrorCode::InvalidRequest,
+                format!("Client must be initialized before using {}", method),
+            );
+        }
+
+        let result = match method {
+            "initialize" => parse_params::<InitializeRequest>(request.params).map(|req| {
+                tracing::info!("客户端 {} {} 已连接", req.client_info.name, req.client_info.version);
+                to_value(InitializeResponse {
+                    protocol_version: LATEST_PROTOCOL_VERSION.to_string(),
+                    capabilities: self.capabilities.clone(),
+                    server_info: self.server_info.clone(),
+                })
+            }),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(to_value(ToolsListResponse {
+                tools: self.tools().cloned().collect(),
+                next_cursor: None,
+                meta: None,
+            })),
+            "tools/call" => match parse_params::<CallToolRequest>(request.params) {
+                Ok(req) => match self.tool_index.get(&req.name) {
+                    Some(&index) => Ok(to_value((self.tools[index].1)(req).await)),
+                    None => Err((ErrorCode::InvalidParams, format!("Tool not found: {}", req.name))),
+                },
+                Err(e) => Err(e),
+            },
+            "resources/list" | "resources/templates/list" | "resources/read" if self.resources.is_empty() => {
+                Err((ErrorCode::MethodNotFound, format!("Method not found: {}", method)))
+            }
+            "resources/list" => match parse_params::<ListRequest>(request.params) {
+                Ok(req) => match self.list_resources(req.cursor.as_deref()).await {
+                    Ok(response) => Ok(to_value(response)),
+                    Err(e) => return resource_error_response(id, e),
+                },
+                Err(e) => Err(e),
+            },
+            "resources/templates/list" => Ok(to_value(ResourceTemplatesListResponse {
+                resource_templates: self
+                    .resources
+                    .iter()
+                    .flat_map(|provider| provider.templates.iter().cloned())
+                    .collect(),
+                next_cursor: None,
+            })),
+            "resources/read" => match parse_params::<ReadResourceRequest>(request.params) {
+                Ok(req) => match self.read_resource(req.uri).await {
+                    Ok(response) => Ok(to_value(response)),
+                    Err(e) => return resource_error_response(id, e),
+                },
+                Err(e) => Err(e),
+            },
+            "prompts/list" | "prompts/get" if self.prompts.is_empty() => {
+                Err((ErrorCode::MethodNotFound, format!("Method not found: {}", method)))
+            }
+            "prompts/list" => Ok(to_value(PromptsListResponse {
+                prompts: self.prompts.iter().map(PromptTemplate::to_prompt).collect(),
+                next_cursor: None,
+                meta: None,
+            })),
+            // 模板或参数不合法时按MCP规范返回InvalidParams
+            "prompts/get" => parse_params::<GetPromptRequest>(request.params).and_then(|req| {
+                self.get_prompt(req)
+                    .map(to_value)
+                    .map_err(|e| (ErrorCode::InvalidParams, e.to_string()))
+            }),
+            _ => Err((ErrorCode::MethodNotFound, format!("Method not found: {}", method))),
+        };
+
+        match result {
+            Ok(result) => JsonRpcResponse {
+                id,
+                result: Some(result),
+                error: None,
+                ..Default::default()
+            },
+            Err((code, message)) => error_response(id, code, message),
+        }
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(params: Option<Value>) -> Result<T, (ErrorCode, String)> {
+    let params = match params {
+        Some(Value::Null) | None => json!({}),
+        Some(params) => params,
+    };
+    serde_json::from_value(params)
+        .map_err(|e| (ErrorCode::InvalidParams, format!("Invalid params: {}", e)))
+}
+
+fn to_value<T: serde::Serialize>(value: T) -> Value {
+    serde_json::to_value(value).unwrap_or_default()
+}
+
+fn error_response(id: RequestId, code: ErrorCode, message: String) -> JsonRpcResponse {
+    JsonRpcResponse {
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code: code as i32,
+            message,
+            data: None,
+        }),
+        ..Default::default()
+    }
+}
+
+// 资源错误使用MCP规范定义的错误码，如资源不存在为-32002
+fn resource_error_response(id: RequestId, error: ResourceError) -> JsonRpcResponse {
+    let data = match &error {
+        ResourceError::NotFound(uri) => Some(json!({ "uri": uri })),
+        _ => None,
+    };
+    JsonRpcResponse {
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code: error.code(),
+            message: error.to_string(),
+            data,
+        }),
+        ..Default::default()
+    }
+}
+
+// 请求参数中 `_meta.progressToken` 的值
+fn progress_token(request: &JsonRpcRequest) -> Option<Value> {
+    request
+        .params
+        .as_ref()?
+        .get("_meta")?
+        .get("progressToken")
+        .filter(|token| !token.is_null())
+        .cloned()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CancelledParams {
+    request_id: RequestId,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+/// 与一个客户端的会话
+pub struct Session {
+    id: String,
+    outgoing: mpsc::Sender<JsonRpcMessage>,
+    initialized: AtomicBool,
+    in_flight: Mutex<HashMap<RequestId, CancellationToken>>,
+}
+
+impl Session {
+    /// 创建会话，返回值中的接收端由传输层负责把消息发给客户端
+    pub fn new(id: String) -> (Arc<Session>, mpsc::Receiver<JsonRpcMessage>) {
+        let (outgoing, rx) = mpsc::channel(OUTGOING_CAPACITY);
+        let session = Session {
+            id,
+            outgoing,
+            initialized: AtomicBool::new(false),
+            in_flight: Mutex::new(HashMap::new()),
+        };
+        (Arc::new(session), rx)
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.initialized.load(Ordering::SeqCst)
+    }
+
+    /// 处理客户端发来的一条消息。
+    ///
+    /// 请求在新任务中执行并返回其句柄，通知在当前任务中立即处理。
+    pub fn dispatch(self: &Arc<Self>, router: &Arc<Router>, message: JsonRpcMessage) -> Option<JoinHandle<()>> {
+        match message {
+            JsonRpcMessage::Request(request) => {
+                let token = CancellationToken::new();
+                if let Ok(mut in_flight) = self.in_flight.lock() {
+                    in_flight.insert(request.id, token.clone());
+                }
+
+                let session = self.clone();
+                let router = router.clone();
+                Some(tokio::spawn(async move {
+                    let id = request.id;
+                    let context = RequestContext {
+                        request_id: id,
+                        cancel: token.clone(),
+                        progress_token: progress_token(&request),
+                        outgoing: session.outgoing.clone(),
+                    };
+                    let response = context::scope(context, router.handle_request(&session, request)).await;
+
+                    if let Ok(mut in_flight) = session.in_flight.lock() {
+                        in_flight.remove(&id);
+                    }
+                    // 已取消的请求不再发送响应
+                    if token.is_cancelled() {
+                        tracing::debug!("会话 {} 的请求 {} 已取消", session.id, id);
+                        return;
+                    }
+                    session.send(JsonRpcMessage::Response(response)).await;
+                }))
+            }
+            JsonRpcMessage::Notification(notification) => {
+                match notification.method.as_str() {
+                    "notifications/initialized" => {
+                        self.initialized.store(true, Ordering::SeqCst);
+                    }
+                    "notifications/cancelled" => {
+                        match parse_params::<CancelledParams>(notification.params) {
+                            Ok(params) => {
+                                tracing::info!(
+                                    "客户端取消请求 {}: {}",
+                                    params.request_id,
+                                    params.reason.as_deref().unwrap_or("未说明原因")
+                                );
+                                self.cancel(params.request_id);
+                            }
+                            Err((_, message)) => tracing::warn!("无效的取消通知: {}", message),
+                        }
+                    }
+                    method => tracing::debug!("忽略通知: {}", method),
+                }
+                None
+            }
+            JsonRpcMessage::Response(response) => {
+                tracing::debug!("忽略客户端响应: {:?}", response.id);
+                None
+            }
+        }
+    }
+
+    /// 取消正在执行的请求
+    pub fn cancel(&self, id: RequestId) {
+        if let Some(token) = self.in_flight.lock().ok().and_then(|in_flight| in_flight.get(&id).cloned()) {
+            token.cancel();
+        }
+    }
+
+    /// 取消会话中所有正在执行的请求，在连接断开时调用
+    pub fn cancel_all(&self) {
+        if let Ok(in_flight) = self.in_flight.lock() {
+            for token in in_flight.values() {
+                token.cancel();
+            }
+        }
+    }
+
+    async fn send(&self, message: JsonRpcMessage) {
+        if self.outgoing.send(message).await.is_err() {
+            tracing::debug!("会话 {} 已关闭，丢弃消息", self.id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_core::transport::JsonRpcNotification;
+    use mcp_core::types::{CallToolResponse, ToolResponseContent};
+    use std::time::Duration;
+
+    // 等待直到被取消的测试工具
+    fn wait_for_cancel(
+        _req: CallToolRequest,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = CallToolResponse> + Send>> {
+        Box::pin(async move {
+            context::cancellation_token().cancelled().await;
+            CallToolResponse {
+                content: vec![ToolResponseContent::Text {
+                    text: "cancelled".to_string(),
+                }],
+                is_error: Some(true),
+                meta: None,
+            }
+        })
+    }
+
+    fn router() -> Arc<Router> {
+        let tool = Tool {
+            name: "Wait".to_string(),
+            description: None,
+            input_schema: json!({"type": "object"}),
+        };
+        Arc::new(Router::new("test", "1.0", ServerCapabilities::default()).tool(tool, wait_for_cancel))
+    }
+
+    fn request(id: RequestId, method: &str, params: Value) -> JsonRpcMessage {
+        JsonRpcMessage::Request(JsonRpcRequest {
+            id,
+            method: method.to_string(),
+            params: Some(params),
+            jsonrpc: Default::default(),
+        })
+    }
+
+    fn notification(method: &str, params: Option<Value>) -> JsonRpcMessage {
+        JsonRpcMessage::Notification(JsonRpcNotification {
+            method: method.to_string(),
+            params,
+            jsonrpc: Default::default(),
+        })
+    }
+
+    async fn next_response(rx: &mut mpsc::Receiver<JsonRpcMessage>) -> JsonRpcResponse {
+        match tokio::time::timeout(Duration::from_secs(5), rx.recv()).await {
+            Ok(Some(JsonRpcMessage::Response(response))) => response,
+            other => panic!("期望收到响应，实际为 {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_requires_initialization() {
+        let router = router();
+        let (session, mut rx) = Session::new("s".to_string());
+
+        session.dispatch(&router, request(1, "tools/list", json!({})));
+        let response = next_response(&mut rx).await;
+        assert_eq!(response.error.unwrap().code, ErrorCode::InvalidRequest as i32);
+
+        session.dispatch(&router, notification("notifications/initialized", None));
+        session.dispatch(&router, request(2, "tools/list", json!({})));
+        let response = next_response(&mut rx).await;
+        assert_eq!(response.result.unwrap()["tools"][0]["name"], "Wait");
+
+        for method in ["resources/list", "prompts/list"] {
+            session.dispatch(&router, request(3, method, json!({})));
+            let response = next_response(&mut rx).await;
+            assert_eq!(response.error.unwrap().code, ErrorCode::MethodNotFound as i32);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_request_does_not_block_others() {
+        let router = router();
+        let (session, mut rx) = Session::new("s".to_string());
+        session.dispatch(&router, notification("notifications/initialized", None));
+
+        let call = session
+            .dispatch(&router, request(1, "tools/call", json!({"name": "Wait"})))
+            .unwrap();
+
+        // 长时间运行的请求不会阻塞后续请求
+        session.dispatch(&router, request(2, "ping", json!({})));
+        assert_eq!(next_response(&mut rx).await.id, 2);
+
+        session.dispatch(
+            &router,
+            notification("notifications/cancelled", Some(json!({"requestId": 1, "reason": "test"}))),
+        );
+        tokio::time::timeout(Duration::from_secs(5), call).await.unwrap().unwrap();
+
+        // 被取消的请求没有响应
+        session.dispatch(&router, request(3, "ping", json!({})));
+        assert_eq!(next_response(&mut rx).await.id, 3);
+    }
+
+    fn provider(scheme: &'static str) -> ResourceProvider {
+        use self::resources::{ResourcePage, ResourceTemplate, TextResourceContents};
+        use mcp_core::types::Resource;
+
+        // 每个提供者有两页，每页一个资源
+        fn list(cursor: Option<String>) -> resources::ResourceFuture<ResourcePage> {
+            Box::pin(async move {
+                let page: u32 = cursor.as_deref().unwrap_or("1").parse().unwrap();
+                Ok(ResourcePage {
+                    resources: vec![Resource {
+                        uri: format!("test://{}", page).parse().unwrap(),
+                        name: format!("page {}", page),
+                        description: None,
+                        mime_type: None,
+                    }],
+                    next_cursor: (page < 2).then(|| (page + 1).to_string()),
+                })
+            })
+        }
+        fn read(uri: url::Url) -> resources::ResourceFuture<Vec<TextResourceContents>> {
+            Box::pin(async move {
+                match uri.host_str() {
+                    Some("1") => Ok(vec![TextResourceContents {
+                        uri: uri.to_string(),
+                        mime_type: Some("text/plain".to_string()),
+                        text: "one".to_string(),
+                    }]),
+                    _ => Err(ResourceError::NotFound(uri.to_string())),
+                }
+            })
+        }
+        ResourceProvider {
+            scheme,
+            templates: vec![ResourceTemplate {
+                uri_template: format!("{}://{{id}}", scheme),
+                name: scheme.to_string(),
+                description: None,
+                mime_type: None,
+            }],
+            list,
+            read,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resources() {
+        let router = Arc::new(
+            Router::new("test", "1.0", ServerCapabilities::default())
+                .resources(provider("test"))
+                .resources(provider("other")),
+        );
+        let (session, mut rx) = Session::new("s".to_string());
+        session.dispatch(&router, notification("notifications/initialized", None));
+
+        // 游标依次经过每个提供者的每一页
+        let mut cursor = Value::Null;
+        let mut names = Vec::new();
+        for id in 1..10 {
+            session.dispatch(&router, request(id, "resources/list", json!({ "cursor": cursor })));
+            let result = next_response(&mut rx).await.result.unwrap();
+            names.push(result["resources"][0]["name"].as_str().unwrap().to_string());
+            cursor = result["nextCursor"].clone();
+            if cursor.is_null() {
+                break;
+            }
+        }
+        assert_eq!(names, ["page 1", "page 2", "page 1", "page 2"]);
+
+        session.dispatch(&router, request(20, "resources/templates/list", json!({})));
+        let result = next_response(&mut rx).await.result.unwrap();
+        assert_eq!(result["resourceTemplates"][1]["uriTemplate"], "other://{id}");
+
+        session.dispatch(&router, request(21, "resources/read", json!({ "uri": "other://1" })));
+        let result = next_response(&mut rx).await.result.unwrap();
+        assert_eq!(result["contents"][0], json!({ "uri": "other://1", "mimeType": "text/plain", "text": "one" }));
+
+        for uri in ["test://2", "unknown://1"] {
+            session.dispatch(&router, request(22, "resources/read", json!({ "uri": uri })));
+            let error = next_response(&mut rx).await.error.unwrap();
+            assert_eq!(error.code, resources::RESOURCE_NOT_FOUND);
+            assert_eq!(error.data.unwrap()["uri"], uri);
+        }
+        session.dispatch(&router, request(23, "resources/list", json!({ "cursor": "bogus" })));
+        let error = next_response(&mut rx).await.error.unwrap();
+        assert_eq!(error.code, ErrorCode::InvalidParams as i32);
+    }
+
+    #[tokio::test]
+    async fn test_prompts() {
+        let template = |name: &str, text: &str| {
+            let contents = format!("[[arguments]]\nname = \"model\"\nrequired = true\n[[messages]]\ntext = \"{}\"\n", text);
+            PromptTemplate::from_toml(name, &contents).unwrap()
+        };
+        let router = Router::new("test", "1.0", ServerCapabilities::default())
+            .prompts([template("generate", "old"), template("summarize", "总结 {{model}}")])
+            .prompts([template("generate", "用 {{model}} 生成")]);
+        assert_eq!(router.capabilities().prompts.as_ref().unwrap().list_changed, Some(false));
+        let router = Arc::new(router);
+        let (session, mut rx) = Session::new("s".to_string());
+        session.dispatch(&router, notification("notifications/initialized", None));
+
+        session.dispatch(&router, request(1, "prompts/list", json!({})));
+        let result = next_response(&mut rx).await.result.unwrap();
+        assert_eq!(result["prompts"][0]["name"], "summarize");
+        assert_eq!(result["prompts"][1]["name"], "generate");
+        assert_eq!(result["prompts"][1]["arguments"][0], json!({ "name": "model", "required": true }));
+
+        session.dispatch(
+            &router,
+            request(2, "prompts/get", json!({ "name": "generate", "arguments": { "model": "qwen" } })),
+        );
+        let result = next_response(&mut rx).await.result.unwrap();
+        assert_eq!(
+            result["messages"],
+            json!([{ "role": "user", "content": { "type": "text", "text": "用 qwen 生成" } }])
+        );
+
+        for params in [json!({ "name": "missing" }), json!({ "name": "generate" }), json!({})] {
+            session.dispatch(&router, request(3, "prompts/get", params));
+            let error = next_response(&mut rx).await.error.unwrap();
+            assert_eq!(error.code, ErrorCode::InvalidParams as i32);
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! MCP资源：`resources/list`、`resources/templates/list` 和 `resources/read`。
+//!
+//! 资源按URI的scheme分给不同的提供者，每个提供者负责一种scheme下的全部资源。
+//! mcp-core没有资源模板和资源内容的类型，这里按MCP规范定义。
+
+use std::future::Future;
+use std::pin::Pin;
+
+use mcp_core::types::{ErrorCode, Resource};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// MCP规范中资源不存在的错误码
+pub const RESOURCE_NOT_FOUND: i32 = -32002;
+
+/// 资源模板，`uri_template` 为RFC 6570格式
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceTemplate {
+    pub uri_template: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
+/// 文本资源的内容
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextResourceContents {
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadResourceResponse {
+    pub contents: Vec<TextResourceContents>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceTemplatesListResponse {
+    pub resource_templates: Vec<ResourceTemplate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// 资源列表的一页，`next_cursor` 为None时没有更多资源
+#[derive(Debug, Clone, Default)]
+pub struct ResourcePage {
+    pub resources: Vec<Resource>,
+    pub next_cursor: Option<String>,
+}
+
+/// 读取或列出资源失败
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ResourceError {
+    #[error("Resource not found: {0}")]
+    NotFound(String),
+    #[error("Invalid resource URI: {0}")]
+    InvalidUri(String),
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl ResourceError {
+    /// JSON-RPC错误码
+    pub fn code(&self) -> i32 {
+        match self {
+            ResourceError::NotFound(_) => RESOURCE_NOT_FOUND,
+            ResourceError::InvalidUri(_) => ErrorCode::InvalidParams as i32,
+            ResourceError::Internal(_) => ErrorCode::InternalError as i32,
+        }
+    }
+}
+
+pub type ResourceFuture<T> = Pin<Box<dyn Future<Output = Result<T, ResourceError>> + Send>>;
+
+/// 一种URI scheme下资源的提供者
+#[derive(Clone)]
+pub struct ResourceProvider {
+    /// 如 `supplier`，对应 `supplier://...`
+    pub scheme: &'static str,
+    pub templates: Vec<ResourceTemplate>,
+    /// 按游标分页列出资源，游标为None时从头开始
+    pub list: fn(Option<String>) -> ResourceFuture<ResourcePage>,
+    pub read: fn(Url) -> ResourceFuture<Vec<TextResourceContents>>,
+}
+
+// 多个提供者时，游标为 "提供者序号:提供者自己的游标"
+pub(crate) fn split_cursor(cursor: Option<&str>) -> Result<(usize, Option<String>), ResourceError> {
+    let Some(cursor) = cursor else {
+        return Ok((0, None));
+    };
+    let invalid = || ResourceError::InvalidUri(format!("invalid cursor {:?}", cursor));
+    let (index, inner) = cursor.split_once(':').ok_or_else(invalid)?;
+    let index = index.parse().map_err(|_| invalid())?;
+    Ok((index, Some(inner.to_string()).filter(|inner| !inner.is_empty())))
+}
+
+pub(crate) fn join_cursor(index: usize, inner: Option<&str>) -> String {
+    format!("{}:{}", index, inner.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        assert_eq!(split_cursor(None).unwrap(), (0, None));
+        assert_eq!(split_cursor(Some(&join_cursor(1, Some("100")))).unwrap(), (1, Some("100".to_string())));
+        assert_eq!(split_cursor(Some(&join_cursor(2, None))).unwrap(), (2, None));
+        assert!(split_cursor(Some("abc")).is_err());
+        assert!(split_cursor(Some("x:1")).is_err());
+    }
+}
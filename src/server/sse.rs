@@ -0,0 +1,128 @@
+//! SSE传输：客户端通过 GET /sse 建立事件流，通过 POST /message?sessionId=... 发送消息。
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use actix_web::web::{self, Bytes, Query};
+use actix_web::{App, HttpRequest, HttpResponse, HttpServer};
+use anyhow::Result;
+use futures::StreamExt;
+use mcp_core::transport::JsonRpcMessage;
+use serde::Deserialize;
+
+use super::{Router, Session};
+
+/// 没有消息时发送保活注释的间隔
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+type Sessions = Arc<Mutex<HashMap<String, Arc<Session>>>>;
+
+struct SseState {
+    router: Arc<Router>,
+    sessions: Sessions,
+}
+
+/// 在host:port上提供SSE服务，直到服务器停止
+pub async fn serve(host: &str, port: u16, router: Router) -> Result<()> {
+    let state = web::Data::new(SseState {
+        router: Arc::new(router),
+        sessions: Arc::new(Mutex::new(HashMap::new())),
+    });
+
+    // 先构建好服务器再等待，使返回的future可以在多线程运行时中spawn
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(state.clone())
+            .route("/sse", web::get().to(sse_handler))
+            .route("/message", web::post().to(message_handler))
+    })
+    .bind((host, port))?
+    .run();
+
+    server
+        .await
+        .map_err(|e| anyhow::anyhow!("Server error: {:?}", e))
+}
+
+// 事件流被丢弃（客户端断开）时移除会话，并取消其中正在执行的请求
+struct SessionGuard {
+    session: Arc<Session>,
+    sessions: Sessions,
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        tracing::info!("SSE会话 {} 已断开", self.session.id());
+        self.session.cancel_all();
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.remove(self.session.id());
+        }
+    }
+}
+
+async fn sse_handler(req: HttpRequest, state: web::Data<SseState>) -> HttpResponse {
+    let client_ip = req
+        .peer_addr()
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let (session, rx) = Session::new(session_id.clone());
+    if let Ok(mut sessions) = state.sessions.lock() {
+        sessions.insert(session_id.clone(), session.clone());
+    }
+    tracing::info!("来自 {} 的SSE连接，会话 {}", client_ip, session_id);
+
+    let guard = SessionGuard {
+        session,
+        sessions: state.sessions.clone(),
+    };
+    let endpoint = format!("event: endpoint\ndata: /message?sessionId={}\n\n", session_id);
+    let events = futures::stream::unfold((rx, guard), |(mut rx, guard)| async move {
+        let chunk = match tokio::time::timeout(KEEP_ALIVE_INTERVAL, rx.recv()).await {
+            Ok(Some(message)) => {
+                let json = serde_json::to_string(&message).unwrap_or_default();
+                format!("event: message\ndata: {}\n\n", json)
+            }
+            Ok(None) => return None,
+            Err(_) => ": ping\n\n".to_string(),
+        };
+        Some((Ok::<_, std::convert::Infallible>(Bytes::from(chunk)), (rx, guard)))
+    });
+    let stream = futures::stream::once(async move { Ok(Bytes::from(endpoint)) }).chain(events);
+
+    HttpResponse::Ok()
+        .append_header(("X-Session-Id", session_id))
+        .content_type("text/event-stream")
+        .streaming(stream)
+}
+
+#[derive(Deserialize)]
+struct MessageQuery {
+    #[serde(rename = "sessionId")]
+    session_id: Option<String>,
+}
+
+async fn message_handler(
+    query: Query<MessageQuery>,
+    message: web::Json<JsonRpcMessage>,
+    state: web::Data<SseState>,
+) -> HttpResponse {
+    let Some(session_id) = &query.session_id else {
+        return HttpResponse::BadRequest().body("Session ID not specified");
+    };
+    let session = state
+        .sessions
+        .lock()
+        .ok()
+        .and_then(|sessions| sessions.get(session_id).cloned());
+
+    match session {
+        Some(session) => {
+            session.dispatch(&state.router, message.into_inner());
+            HttpResponse::Accepted().finish()
+        }
+        None => HttpResponse::NotFound().body(format!("Session {} not found", session_id)),
+    }
+}
@@ -0,0 +1,96 @@
+//! stdio传输：每行一条JSON-RPC消息，从stdin读取，向stdout写出。
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use mcp_core::transport::JsonRpcMessage;
+use mcp_core::types::ErrorCode;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+
+use super::{Router, Session};
+
+/// 在标准输入输出上提供服务，stdin关闭并且所有请求处理完后返回
+pub async fn serve(router: Router) -> Result<()> {
+    serve_streams(router, tokio::io::stdin(), tokio::io::stdout()).await
+}
+
+/// 在任意读写流上提供stdio协议服务
+pub async fn serve_streams<R, W>(router: Router, input: R, mut output: W) -> Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    let router = Arc::new(router);
+    let (session, mut rx) = Session::new("stdio".to_string());
+    // 无法解析的消息没有会话中的请求id，错误响应单独发送
+    let (errors, mut errors_rx) = mpsc::unbounded_channel::<Value>();
+
+    // 所有输出都经由同一个任务写出，保证每条消息独占一行
+    let writer = tokio::spawn(async move {
+        loop {
+            let mut line = tokio::select! {
+                Some(message) = rx.recv() => serde_json::to_string(&message).unwrap_or_default(),
+                Some(error) = errors_rx.recv() => error.to_string(),
+                else => break,
+            };
+            line.push('\n');
+            if output.write_all(line.as_bytes()).await.is_err() || output.flush().await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut requests = JoinSet::new();
+    let mut lines = BufReader::new(input).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<JsonRpcMessage>(&line) {
+            Ok(message) => {
+                if let Some(handle) = session.dispatch(&router, message) {
+                    requests.spawn(async move {
+                        let _ = handle.await;
+                    });
+                }
+            }
+            Err(e) => {
+                tracing::error!("无法解析消息: {}: {}", e, line);
+                let _ = errors.send(parse_error_response(&line, &e));
+            }
+        }
+        // 回收已经完成的请求
+        while requests.try_join_next().is_some() {}
+    }
+
+    // stdin已关闭，等待剩余的请求完成后再退出
+    while requests.join_next().await.is_some() {}
+    drop(session);
+    drop(errors);
+    let _ = writer.await;
+    Ok(())
+}
+
+// 不是合法JSON时回复Parse error；是JSON但不是合法的JSON-RPC消息时回复Invalid Request，
+// 能取得请求id时沿用，否则id为null
+fn parse_error_response(line: &str, error: &serde_json::Error) -> Value {
+    let (code, message, id) = match serde_json::from_str::<Value>(line) {
+        Ok(value) => (
+            ErrorCode::InvalidRequest,
+            "Invalid Request",
+            value.get("id").cloned().unwrap_or(Value::Null),
+        ),
+        Err(_) => (ErrorCode::ParseError, "Parse error", Value::Null),
+    };
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {
+            "code": code as i32,
+            "message": format!("{}: {}", message, error),
+        },
+    })
+}
@@ -0,0 +1,1513 @@
+//! 供应商信息存储，数据保存在SQLite数据库中。
+//!
+//! 表结构与wei-assistant-gpu保持一致，见prd.txt附录，另外增加了软删除用的 `deleted_at` 列。
+//! 数据库在第一次使用时打开，文件不存在时自动创建，并应用 [`MIGRATIONS`] 中尚未应用的迁移。
+
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use csv::{ReaderBuilder, StringRecord, Terminator, WriterBuilder};
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection, OptionalExtension, Row, Transaction, TransactionBehavior};
+use serde_json::{json, Value as JsonValue};
+
+use crate::config::SuppliersConfig;
+use crate::migrations::{self, Migration, MigrationError, MigrationStatus};
+
+// 第一版表结构，与prd.txt附录相同。使用IF NOT EXISTS，
+// 迁移功能加入之前创建的数据库（没有迁移记录）也能直接升级
+const SCHEMA_V1: &str = "
+CREATE TABLE IF NOT EXISTS suppliers (
+    id INTEGER PRIMARY KEY AUTOINCREMENT, -- 主键，自增
+    contact TEXT NOT NULL,               -- 联系人
+    wechat TEXT,                         -- 微信
+    phone TEXT,                          -- 电话
+    quantity INTEGER,                    -- 数量
+    location TEXT,                       -- 地点
+    price REAL,                          -- 价格
+    bandwidth_price REAL,                -- 带宽价格
+    storage_price REAL,                  -- 存储价格
+    min_contract_period TEXT,            -- 最短合同期
+    breach_penalties TEXT,               -- 违约金
+    payment_terms TEXT,                  -- 付款方式
+    server_name TEXT,                    -- 服务器名称
+    server_config TEXT,                  -- 服务器配置
+    rental_model TEXT,                   -- 租赁模式
+    networking_category TEXT             -- 网络类型
+);
+";
+
+/// 供应商数据库的迁移，只能在末尾追加，已发布的迁移不能修改
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "创建suppliers表",
+        sql: SCHEMA_V1,
+    },
+    Migration {
+        version: 2,
+        description: "增加软删除时间deleted_at",
+        // 软删除时间（Unix时间戳），NULL表示未删除
+        sql: "ALTER TABLE suppliers ADD COLUMN deleted_at INTEGER;",
+    },
+];
+
+/// 除id外的所有列，顺序与 [`Supplier::values`] 一致
+pub const COLUMNS: [&str; 15] = [
+    "contact",
+    "wechat",
+    "phone",
+    "quantity",
+    "location",
+    "price",
+    "bandwidth_price",
+    "storage_price",
+    "min_contract_period",
+    "breach_penalties",
+    "payment_terms",
+    "server_name",
+    "server_config",
+    "rental_model",
+    "networking_category",
+];
+
+/// 列的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    Integer,
+    Real,
+    Text,
+}
+
+/// 列的类型，不是供应商表中的列时返回None
+pub fn field_type(field: &str) -> Option<FieldType> {
+    match field {
+        "id" | "quantity" => Some(FieldType::Integer),
+        "price" | "bandwidth_price" | "storage_price" => Some(FieldType::Real),
+        _ if COLUMNS.contains(&field) => Some(FieldType::Text),
+        _ => None,
+    }
+}
+
+// 把字段名换成表中列名的静态字符串，SQL中只出现这些已知的列名
+fn column_name(field: &str) -> Result<&'static str, SupplierError> {
+    let field = field.trim();
+    ["id"]
+        .into_iter()
+        .chain(COLUMNS)
+        .find(|column| *column == field)
+        .ok_or_else(|| SupplierError::InvalidArguments(format!("unknown supplier field {:?}", field)))
+}
+
+/// 部分字段：字段名到值的映射，null表示清空该字段
+pub type SupplierFields = serde_json::Map<String, JsonValue>;
+
+/// 一条供应商记录
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct Supplier {
+    /// 主键，添加时不指定则自动分配
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    /// 联系人
+    pub contact: String,
+    /// 微信
+    pub wechat: Option<String>,
+    /// 电话
+    pub phone: Option<String>,
+    /// 数量
+    pub quantity: Option<i64>,
+    /// 地点
+    pub location: Option<String>,
+    /// 价格
+    pub price: Option<f64>,
+    /// 带宽价格
+    pub bandwidth_price: Option<f64>,
+    /// 存储价格
+    pub storage_price: Option<f64>,
+    /// 最短合同期
+    pub min_contract_period: Option<String>,
+    /// 违约金
+    pub breach_penalties: Option<String>,
+    /// 付款方式
+    pub payment_terms: Option<String>,
+    /// 服务器名称
+    pub server_name: Option<String>,
+    /// 服务器配置
+    pub server_config: Option<String>,
+    /// 租赁模式
+    pub rental_model: Option<String>,
+    /// 网络类型
+    pub networking_category: Option<String>,
+}
+
+impl Supplier {
+    /// 去掉文本字段首尾的空白，空的可选字段视为未填写
+    pub fn normalize(mut self) -> Self {
+        self.contact = self.contact.trim().to_string();
+        for field in [
+            &mut self.wechat,
+            &mut self.phone,
+            &mut self.location,
+            &mut self.min_contract_period,
+            &mut self.breach_penalties,
+            &mut self.payment_terms,
+            &mut self.server_name,
+            &mut self.server_config,
+            &mut self.rental_model,
+            &mut self.networking_category,
+        ] {
+            *field = field
+                .take()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty());
+        }
+        self
+    }
+
+    /// 检查字段取值
+    pub fn validate(&self) -> Result<(), String> {
+        if self.contact.trim().is_empty() {
+            return Err("contact must not be empty".to_string());
+        }
+        if let Some(id) = self.id {
+            if id <= 0 {
+                return Err(format!("id must be positive, got {}", id));
+            }
+        }
+        if let Some(quantity) = self.quantity {
+            if quantity < 0 {
+                return Err(format!("quantity must not be negative, got {}", quantity));
+            }
+        }
+        for (name, value) in [
+            ("price", self.price),
+            ("bandwidth_price", self.bandwidth_price),
+            ("storage_price", self.storage_price),
+        ] {
+            if let Some(value) = value {
+                if !value.is_finite() || value < 0.0 {
+                    return Err(format!("{} must be a non-negative number, got {}", name, value));
+                }
+            }
+        }
+        if let Some(phone) = &self.phone {
+            let valid = phone
+                .chars()
+                .all(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | ' ' | '(' | ')'));
+            if !valid || !phone.chars().any(|c| c.is_ascii_digit()) {
+                return Err(format!("phone {:?} is not a valid phone number", phone));
+            }
+        }
+        Ok(())
+    }
+
+    /// 按 [`COLUMNS`] 的顺序返回各列的值
+    pub fn values(&self) -> Vec<Value> {
+        vec![
+            self.contact.clone().into(),
+            self.wechat.clone().into(),
+            self.phone.clone().into(),
+            self.quantity.into(),
+            self.location.clone().into(),
+            self.price.into(),
+            self.bandwidth_price.into(),
+            self.storage_price.into(),
+            self.min_contract_period.clone().into(),
+            self.breach_penalties.clone().into(),
+            self.payment_terms.clone().into(),
+            self.server_name.clone().into(),
+            self.server_config.clone().into(),
+            self.rental_model.clone().into(),
+            self.networking_category.clone().into(),
+        ]
+    }
+
+    /// 某一列的值，`column` 为id或 [`COLUMNS`] 中的列
+    pub fn value(&self, column: &str) -> Value {
+        if column == "id" {
+            return self.id.into();
+        }
+        COLUMNS
+            .iter()
+            .position(|c| *c == column)
+            .map(|i| self.values().swap_remove(i))
+            .unwrap_or(Value::Null)
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Supplier {
+            id: row.get("id")?,
+            contact: row.get("contact")?,
+            wechat: row.get("wechat")?,
+            phone: row.get("phone")?,
+            quantity: row.get("quantity")?,
+            location: row.get("location")?,
+            price: row.get("price")?,
+            bandwidth_price: row.get("bandwidth_price")?,
+            storage_price: row.get("storage_price")?,
+            min_contract_period: row.get("min_contract_period")?,
+            breach_penalties: row.get("breach_penalties")?,
+            payment_terms: row.get("payment_terms")?,
+            server_name: row.get("server_name")?,
+            server_config: row.get("server_config")?,
+            rental_model: row.get("rental_model")?,
+            networking_category: row.get("networking_category")?,
+        })
+    }
+}
+
+/// 供应商工具的错误
+#[derive(Debug, thiserror::Error)]
+pub enum SupplierError {
+    /// 工具参数或供应商字段不合法
+    #[error("Invalid arguments: {0}")]
+    InvalidArguments(String),
+    /// 已存在相同的供应商记录
+    #[error("Duplicate supplier: same as existing record {0}")]
+    Duplicate(i64),
+    /// 没有该id的供应商，或记录已被删除
+    #[error("Supplier {0} not found")]
+    NotFound(i64),
+    /// 数据库操作失败
+    #[error("Supplier database error: {0}")]
+    Database(#[from] rusqlite::Error),
+    /// 导出路径不在导出目录内
+    #[error("Export path not allowed: {0}")]
+    PathNotAllowed(String),
+    /// 数据库版本迁移失败，或数据库版本高于程序支持的版本
+    #[error("Supplier database migration error: {0}")]
+    Migration(#[from] MigrationError),
+    /// 创建数据库目录、写导出文件等IO错误
+    #[error("Supplier IO error: {0}")]
+    Io(#[from] io::Error),
+}
+
+impl SupplierError {
+    /// 错误类型的稳定名称，供客户端区分错误
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SupplierError::InvalidArguments(_) => "invalid_arguments",
+            SupplierError::Duplicate(_) => "duplicate",
+            SupplierError::NotFound(_) => "not_found",
+            SupplierError::Database(_) => "database",
+            SupplierError::PathNotAllowed(_) => "path_not_allowed",
+            SupplierError::Migration(MigrationError::TooNew { .. }) => "schema_too_new",
+            SupplierError::Migration(_) => "migration",
+            SupplierError::Io(_) => "io",
+        }
+    }
+
+    /// 结构化的错误信息，放在工具响应的 `_meta.error` 中
+    pub fn to_json(&self) -> JsonValue {
+        let mut error = json!({
+            "type": self.kind(),
+            "message": self.to_string(),
+        });
+        match self {
+            SupplierError::Duplicate(id) => error["existingId"] = json!(id),
+            SupplierError::NotFound(id) => error["id"] = json!(id),
+            _ => {}
+        }
+        error
+    }
+}
+
+/// 过滤操作符
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOp {
+    /// 等于；value为null时等价于is_null
+    #[serde(alias = "=", alias = "==")]
+    Eq,
+    /// 不等于；value为null时等价于not_null，值为NULL的记录不会匹配非null的value
+    #[serde(alias = "!=", alias = "<>")]
+    Ne,
+    #[serde(alias = ">")]
+    Gt,
+    #[serde(alias = ">=")]
+    Gte,
+    #[serde(alias = "<")]
+    Lt,
+    #[serde(alias = "<=")]
+    Lte,
+    /// 字段为NULL，不需要value
+    IsNull,
+    /// 字段不为NULL，不需要value
+    NotNull,
+    /// 模糊匹配文本字段；value不含 `%` 或 `_` 时按包含匹配
+    Like,
+}
+
+impl FilterOp {
+    fn sql(&self) -> &'static str {
+        match self {
+            FilterOp::Eq => "=",
+            FilterOp::Ne => "!=",
+            FilterOp::Gt => ">",
+            FilterOp::Gte => ">=",
+            FilterOp::Lt => "<",
+            FilterOp::Lte => "<=",
+            FilterOp::IsNull => "IS NULL",
+            FilterOp::NotNull => "IS NOT NULL",
+            FilterOp::Like => "LIKE",
+        }
+    }
+}
+
+/// 一个过滤条件，如 `{"field": "price", "op": "lt", "value": 10000}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Filter {
+    /// 供应商字段名
+    pub field: String,
+    /// eq、ne、gt、gte、lt、lte、is_null、not_null或like
+    pub op: FilterOp,
+    /// 比较的值，is_null、not_null不需要
+    #[serde(default)]
+    pub value: Option<JsonValue>,
+}
+
+impl Filter {
+    pub fn new(field: &str, op: FilterOp, value: impl Into<Option<JsonValue>>) -> Self {
+        Filter {
+            field: field.to_string(),
+            op,
+            value: value.into(),
+        }
+    }
+}
+
+// 校验过的条件，列名来自 [`COLUMNS`]
+#[derive(Debug, Clone, PartialEq)]
+struct Condition {
+    column: &'static str,
+    op: FilterOp,
+    value: Option<Value>,
+}
+
+impl Condition {
+    fn parse(filter: &Filter) -> Result<Self, SupplierError> {
+        let column = column_name(&filter.field)?;
+        let invalid = |message: String| SupplierError::InvalidArguments(format!("filter on {}: {}", column, message));
+        let value = filter.value.as_ref().filter(|value| !value.is_null());
+
+        let (op, value) = match (filter.op, value) {
+            (FilterOp::IsNull | FilterOp::NotNull, Some(_)) => {
+                return Err(invalid(format!("{:?} does not take a value", filter.op)));
+            }
+            (FilterOp::IsNull | FilterOp::NotNull, None) => (filter.op, None),
+            // 与null比较等价于NULL判断
+            (FilterOp::Eq, None) => (FilterOp::IsNull, None),
+            (FilterOp::Ne, None) => (FilterOp::NotNull, None),
+            (op, None) => return Err(invalid(format!("{:?} requires a value", op))),
+            (FilterOp::Like, Some(value)) => {
+                if field_type(column) != Some(FieldType::Text) {
+                    return Err(invalid("like only applies to text fields".to_string()));
+                }
+                let pattern = value
+                    .as_str()
+                    .ok_or_else(|| invalid(format!("like pattern must be a string, got {}", value)))?;
+                let pattern = if pattern.contains(['%', '_']) {
+                    pattern.to_string()
+                } else {
+                    format!("%{}%", pattern)
+                };
+                (FilterOp::Like, Some(Value::Text(pattern)))
+            }
+            (op, Some(value)) => (op, Some(convert_value(column, value).map_err(invalid)?)),
+        };
+        Ok(Condition { column, op, value })
+    }
+
+    fn sql(&self) -> String {
+        match self.value {
+            Some(_) => format!("{} {} ?", self.column, self.op.sql()),
+            None => format!("{} {}", self.column, self.op.sql()),
+        }
+    }
+}
+
+// 把JSON值转换为与列类型一致的SQL值
+fn convert_value(column: &str, value: &JsonValue) -> Result<Value, String> {
+    match field_type(column) {
+        Some(FieldType::Integer) => value
+            .as_i64()
+            .map(Value::Integer)
+            .ok_or_else(|| format!("expected an integer, got {}", value)),
+        Some(FieldType::Real) => value
+            .as_f64()
+            .map(Value::Real)
+            .ok_or_else(|| format!("expected a number, got {}", value)),
+        _ => match value {
+            JsonValue::String(text) => Ok(Value::Text(text.clone())),
+            JsonValue::Number(number) => Ok(Value::Text(number.to_string())),
+            other => Err(format!("expected a string, got {}", other)),
+        },
+    }
+}
+
+/// 供应商查询：过滤条件之间为AND关系，结果默认按id排序
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SupplierQuery {
+    conditions: Vec<Condition>,
+    order: Vec<(&'static str, bool)>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+}
+
+impl SupplierQuery {
+    pub fn new() -> Self {
+        SupplierQuery::default()
+    }
+
+    /// 添加一个过滤条件
+    pub fn filter(mut self, filter: &Filter) -> Result<Self, SupplierError> {
+        self.conditions.push(Condition::parse(filter)?);
+        Ok(self)
+    }
+
+    /// 排序，如 `"price"`、`"price desc, quantity"`
+    pub fn order_by(mut self, spec: &str) -> Result<Self, SupplierError> {
+        for part in spec.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            let mut words = part.split_whitespace();
+            let column = column_name(words.next().unwrap_or_default())?;
+            let descending = match words.next().map(str::to_lowercase).as_deref() {
+                None | Some("asc") => false,
+                Some("desc") => true,
+                Some(other) => {
+                    return Err(SupplierError::InvalidArguments(format!(
+                        "invalid sort direction {:?}, expected asc or desc",
+                        other
+                    )))
+                }
+            };
+            if words.next().is_some() {
+                return Err(SupplierError::InvalidArguments(format!("invalid order_by {:?}", part)));
+            }
+            self.order.push((column, descending));
+        }
+        Ok(self)
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    // 已删除的记录不参与查询
+    fn where_clause(&self) -> (String, Vec<Value>) {
+        let clauses: Vec<String> = ["deleted_at IS NULL".to_string()]
+            .into_iter()
+            .chain(self.conditions.iter().map(Condition::sql))
+            .collect();
+        let params = self
+            .conditions
+            .iter()
+            .filter_map(|condition| condition.value.clone())
+            .collect();
+        (format!(" WHERE {}", clauses.join(" AND ")), params)
+    }
+
+    /// 生成带占位符的SELECT语句和参数
+    pub fn build(&self) -> (String, Vec<Value>) {
+        let (where_clause, mut params) = self.where_clause();
+        let mut sql = format!("SELECT * FROM suppliers{}", where_clause);
+
+        let mut order: Vec<String> = self
+            .order
+            .iter()
+            .map(|(column, descending)| format!("{} {}", column, if *descending { "DESC" } else { "ASC" }))
+            .collect();
+        // id保证分页时的顺序稳定
+        if !self.order.iter().any(|(column, _)| *column == "id") {
+            order.push("id ASC".to_string());
+        }
+        sql.push_str(" ORDER BY ");
+        sql.push_str(&order.join(", "));
+
+        if self.limit.is_some() || self.offset.is_some() {
+            // LIMIT -1 表示不限制行数
+            sql.push_str(" LIMIT ? OFFSET ?");
+            params.push(self.limit.map_or(-1, |limit| limit.min(i64::MAX as u64) as i64).into());
+            params.push((self.offset.unwrap_or(0).min(i64::MAX as u64) as i64).into());
+        }
+        (sql, params)
+    }
+
+    /// 生成统计满足条件的记录数的语句，不考虑排序和分页
+    pub fn build_count(&self) -> (String, Vec<Value>) {
+        let (where_clause, params) = self.where_clause();
+        (format!("SELECT COUNT(*) AS total FROM suppliers{}", where_clause), params)
+    }
+}
+
+/// 供应商数据库
+pub struct SupplierStore {
+    db: Connection,
+}
+
+impl SupplierStore {
+    /// 打开数据库文件并应用迁移，所在目录不存在时创建
+    pub fn open(path: &Path) -> Result<Self, SupplierError> {
+        Self::init(connect(path)?)
+    }
+
+    /// 内存数据库，用于测试
+    pub fn open_in_memory() -> Result<Self, SupplierError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(mut db: Connection) -> Result<Self, SupplierError> {
+        let applied = migrations::migrate(&mut db, MIGRATIONS)?;
+        if !applied.is_empty() {
+            tracing::info!("供应商数据库已应用迁移 {:?}", applied);
+        }
+        Ok(SupplierStore { db })
+    }
+
+    /// 校验并添加一条记录，返回保存后的记录。
+    ///
+    /// 除id外所有字段都与已有记录相同时视为重复。
+    pub fn add(&self, supplier: Supplier) -> Result<Supplier, SupplierError> {
+        let mut supplier = supplier.normalize();
+        supplier.validate().map_err(SupplierError::InvalidArguments)?;
+
+        // 已删除的记录仍然占用id
+        if let Some(id) = supplier.id {
+            let exists = self
+                .db
+                .query_row("SELECT id FROM suppliers WHERE id = ?", [id], |_| Ok(()))
+                .optional()?;
+            if exists.is_some() {
+                return Err(SupplierError::Duplicate(id));
+            }
+        }
+        if let Some(id) = self.find_duplicate(&supplier)? {
+            return Err(SupplierError::Duplicate(id));
+        }
+
+        let mut columns = COLUMNS.to_vec();
+        let mut values = supplier.values();
+        if let Some(id) = supplier.id {
+            columns.insert(0, "id");
+            values.insert(0, id.into());
+        }
+        let sql = format!(
+            "INSERT INTO suppliers ({}) VALUES ({})",
+            columns.join(", "),
+            vec!["?"; columns.len()].join(", ")
+        );
+        self.db.execute(&sql, params_from_iter(values))?;
+        supplier.id = Some(self.db.last_insert_rowid());
+        Ok(supplier)
+    }
+
+    // 查找除id外所有字段都相同的未删除记录，IS 比较时NULL与NULL相等
+    fn find_duplicate(&self, supplier: &Supplier) -> Result<Option<i64>, SupplierError> {
+        let conditions: Vec<String> = COLUMNS.iter().map(|column| format!("{} IS ?", column)).collect();
+        let sql = format!(
+            "SELECT id FROM suppliers WHERE {} AND deleted_at IS NULL AND id IS NOT ? LIMIT 1",
+            conditions.join(" AND ")
+        );
+        let mut params = supplier.values();
+        params.push(supplier.id.into());
+        Ok(self
+            .db
+            .query_row(&sql, params_from_iter(params), |row| row.get("id"))
+            .optional()?)
+    }
+
+    /// 按id读取未删除的记录
+    pub fn get(&self, id: i64) -> Result<Option<Supplier>, SupplierError> {
+        Ok(self
+            .db
+            .query_row(
+                "SELECT * FROM suppliers WHERE id = ? AND deleted_at IS NULL",
+                [id],
+                Supplier::from_row,
+            )
+            .optional()?)
+    }
+
+    /// 修改部分字段，`fields` 中的null清空该字段；返回修改后的记录
+    pub fn update(&self, id: i64, fields: &SupplierFields) -> Result<Supplier, SupplierError> {
+        let existing = self.get(id)?.ok_or(SupplierError::NotFound(id))?;
+        if fields.is_empty() {
+            return Err(SupplierError::InvalidArguments("no fields to update".to_string()));
+        }
+        Ok(self.patch(existing, fields)?.0)
+    }
+
+    // 把 `fields` 合并到未删除的记录中并保存，返回修改后的记录和值有变化的列
+    fn patch(&self, existing: Supplier, fields: &SupplierFields) -> Result<(Supplier, Vec<&'static str>), SupplierError> {
+        let id = existing.id.unwrap_or_default();
+        let mut record = serde_json::to_value(&existing).unwrap_or_default();
+        for (field, value) in fields {
+            if field == "id" {
+                return Err(SupplierError::InvalidArguments("id cannot be changed".to_string()));
+            }
+            if !COLUMNS.contains(&field.as_str()) {
+                return Err(SupplierError::InvalidArguments(format!("unknown supplier field {:?}", field)));
+            }
+            record[field] = value.clone();
+        }
+        let supplier: Supplier = serde_json::from_value(record)
+            .map_err(|e| SupplierError::InvalidArguments(format!("invalid supplier fields: {}", e)))?;
+        let mut supplier = supplier.normalize();
+        supplier.id = Some(id);
+        supplier.validate().map_err(SupplierError::InvalidArguments)?;
+
+        let changed: Vec<&'static str> = COLUMNS
+            .into_iter()
+            .filter(|column| supplier.value(column) != existing.value(column))
+            .collect();
+        if changed.is_empty() {
+            return Ok((supplier, changed));
+        }
+        if let Some(duplicate) = self.find_duplicate(&supplier)? {
+            return Err(SupplierError::Duplicate(duplicate));
+        }
+
+        let assignments: Vec<String> = COLUMNS.iter().map(|column| format!("{} = ?", column)).collect();
+        let sql = format!("UPDATE suppliers SET {} WHERE id = ?", assignments.join(", "));
+        let mut params = supplier.values();
+        params.push(id.into());
+        self.db.execute(&sql, params_from_iter(params))?;
+        Ok((supplier, changed))
+    }
+
+    /// 软删除：记录保留在数据库中，之后的查询不再返回，可以用 [`SupplierStore::restore`] 恢复
+    pub fn delete(&self, id: i64) -> Result<(), SupplierError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        let changed = self.db.execute(
+            "UPDATE suppliers SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL",
+            [now, id],
+        )?;
+        if changed == 0 {
+            return Err(SupplierError::NotFound(id));
+        }
+        Ok(())
+    }
+
+    /// 恢复被删除的记录并返回它；恢复未删除的记录不做任何修改
+    pub fn restore(&self, id: i64) -> Result<Supplier, SupplierError> {
+        let (supplier, deleted_at) = self
+            .db
+            .query_row("SELECT * FROM suppliers WHERE id = ?", [id], |row| {
+                Ok((Supplier::from_row(row)?, row.get::<_, Option<i64>>("deleted_at")?))
+            })
+            .optional()?
+            .ok_or(SupplierError::NotFound(id))?;
+        if deleted_at.is_none() {
+            return Ok(supplier);
+        }
+        // 删除期间可能添加了内容相同的记录
+        if let Some(duplicate) = self.find_duplicate(&supplier)? {
+            return Err(SupplierError::Duplicate(duplicate));
+        }
+        self.db
+            .execute("UPDATE suppliers SET deleted_at = NULL WHERE id = ?", [id])?;
+        Ok(supplier)
+    }
+
+    /// 在一个事务中导入多条记录，返回逐行的结果。
+    ///
+    /// 带id且该id存在的行与 [`SupplierStore::update`] 相同，只修改行中给出的字段；
+    /// 其余行作为新记录添加，与已有记录完全相同的行不做修改。
+    /// 有任何一行不合法或 `dry_run` 时回滚整个事务，报告的内容与实际导入时相同。
+    pub fn import(
+        &self,
+        rows: Vec<Result<SupplierFields, SupplierError>>,
+        dry_run: bool,
+    ) -> Result<ImportReport, SupplierError> {
+        // 导入过程中通过 self.db 读写，事务不独占连接
+        let transaction = Transaction::new_unchecked(&self.db, TransactionBehavior::Immediate)?;
+        let result = self.import_rows(rows);
+        let commit = !dry_run && result.as_ref().is_ok_and(|report| report.invalid == 0);
+        if commit {
+            transaction.commit()?;
+        } else {
+            transaction.rollback()?;
+        }
+        let mut report = result?;
+        report.dry_run = dry_run;
+        report.committed = commit;
+        Ok(report)
+    }
+
+    fn import_rows(&self, rows: Vec<Result<SupplierFields, SupplierError>>) -> Result<ImportReport, SupplierError> {
+        let mut report = ImportReport::default();
+        for (i, row) in rows.into_iter().enumerate() {
+            let outcome = row.and_then(|fields| self.import_row(fields));
+            let row = match outcome {
+                Ok((action, id, changed_fields)) => ImportRow {
+                    row: i + 1,
+                    action,
+                    id: Some(id),
+                    changed_fields,
+                    error: None,
+                },
+                // 数据库本身出错时放弃整个导入
+                Err(e @ (SupplierError::Database(_) | SupplierError::Io(_))) => return Err(e),
+                Err(e) => ImportRow {
+                    row: i + 1,
+                    action: ImportAction::Invalid,
+                    id: None,
+                    changed_fields: Vec::new(),
+                    error: Some(e.to_json()),
+                },
+            };
+            match row.action {
+                ImportAction::Created => report.created += 1,
+                ImportAction::Updated => report.updated += 1,
+                ImportAction::Unchanged => report.unchanged += 1,
+                ImportAction::Invalid => report.invalid += 1,
+            }
+            report.rows.push(row);
+        }
+        Ok(report)
+    }
+
+    // 导入一行，返回对它的处理、记录的id和值有变化的列
+    fn import_row(&self, mut fields: SupplierFields) -> Result<(ImportAction, i64, Vec<&'static str>), SupplierError> {
+        let id = match fields.remove("id") {
+            None | Some(JsonValue::Null) => None,
+            Some(id) => Some(
+                id.as_i64()
+                    .ok_or_else(|| SupplierError::InvalidArguments(format!("id must be an integer, got {}", id)))?,
+            ),
+        };
+        if let Some(existing) = id.map(|id| self.get(id)).transpose()?.flatten() {
+            let (updated, changed) = self.patch(existing, &fields)?;
+            let action = if changed.is_empty() { ImportAction::Unchanged } else { ImportAction::Updated };
+            return Ok((action, updated.id.unwrap_or_default(), changed));
+        }
+
+        // 新记录中的null与未填写相同
+        fields.retain(|_, value| !value.is_null());
+        let mut supplier: Supplier = serde_json::from_value(JsonValue::Object(fields))
+            .map_err(|e| SupplierError::InvalidArguments(format!("invalid supplier record: {}", e)))?;
+        supplier.id = id;
+        match self.add(supplier) {
+            Ok(added) => Ok((ImportAction::Created, added.id.unwrap_or_default(), Vec::new())),
+            // 没有指定id时，与已有记录完全相同的行视为已经导入过
+            Err(SupplierError::Duplicate(existing)) if id.is_none() => Ok((ImportAction::Unchanged, existing, Vec::new())),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 执行查询
+    pub fn query(&self, query: &SupplierQuery) -> Result<Vec<Supplier>, SupplierError> {
+        let (sql, params) = query.build();
+        let mut statement = self.db.prepare(&sql)?;
+        let suppliers = statement
+            .query_map(params_from_iter(params), Supplier::from_row)?
+            .collect::<Result<_, _>>()?;
+        Ok(suppliers)
+    }
+
+    /// 满足查询条件的记录总数，不考虑分页
+    pub fn count(&self, query: &SupplierQuery) -> Result<u64, SupplierError> {
+        let (sql, params) = query.build_count();
+        let total: i64 = self.db.query_row(&sql, params_from_iter(params), |row| row.get("total"))?;
+        Ok(total as u64)
+    }
+}
+
+fn connect(path: &Path) -> Result<Connection, SupplierError> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(Connection::open(path)?)
+}
+
+/// 应用数据库文件尚未应用的迁移，返回本次应用的版本
+pub fn migrate(path: &Path) -> Result<Vec<u32>, SupplierError> {
+    Ok(migrations::migrate(&mut connect(path)?, MIGRATIONS)?)
+}
+
+/// 数据库文件的迁移状态；文件不存在时不会创建
+pub fn migration_status(path: &Path) -> Result<MigrationStatus, SupplierError> {
+    if !path.exists() {
+        return Ok(migrations::empty_status(MIGRATIONS));
+    }
+    Ok(migrations::status(&Connection::open(path)?, MIGRATIONS)?)
+}
+
+/// 导入时对一行的处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportAction {
+    Created,
+    Updated,
+    Unchanged,
+    Invalid,
+}
+
+/// 导入报告中的一行
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImportRow {
+    /// 从1开始的行号，不含CSV的表头
+    pub row: usize,
+    pub action: ImportAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    /// 修改已有记录时值有变化的列，按 [`COLUMNS`] 的顺序
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changed_fields: Vec<&'static str>,
+    /// 不合法的原因，格式同工具响应的 `_meta.error`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<JsonValue>,
+}
+
+/// 导入报告
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    /// 是否已写入数据库
+    pub committed: bool,
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub invalid: usize,
+    pub rows: Vec<ImportRow>,
+}
+
+/// 把CSV解析为待导入的记录：第一行为列名，每行只包含表头中的列，空字段为null
+pub fn import_rows_from_csv(text: &str) -> Result<Vec<Result<SupplierFields, SupplierError>>, SupplierError> {
+    let invalid_csv = |e: csv::Error| SupplierError::InvalidArguments(format!("invalid CSV: {}", e));
+    // 列数不一致的行作为不合法的行报告，而不是让整个CSV解析失败
+    let mut reader = ReaderBuilder::new().flexible(true).from_reader(text.as_bytes());
+    let header = reader.headers().map_err(invalid_csv)?.clone();
+    if header.is_empty() {
+        return Err(SupplierError::InvalidArguments("CSV is empty".to_string()));
+    }
+    let mut columns = Vec::with_capacity(header.len());
+    for name in &header {
+        let column = column_name(name)?;
+        if columns.contains(&column) {
+            return Err(SupplierError::InvalidArguments(format!("duplicate column {:?}", column)));
+        }
+        columns.push(column);
+    }
+
+    reader
+        .records()
+        .map(|record| Ok(fields_from_csv(&columns, &record.map_err(invalid_csv)?)))
+        .collect()
+}
+
+// 把CSV中的一行转换为各列的值，空字段为null
+fn fields_from_csv(columns: &[&'static str], record: &StringRecord) -> Result<SupplierFields, SupplierError> {
+    if record.len() != columns.len() {
+        return Err(SupplierError::InvalidArguments(format!(
+            "expected {} fields, got {}",
+            columns.len(),
+            record.len()
+        )));
+    }
+    let mut fields = serde_json::Map::new();
+    for (column, value) in columns.iter().zip(record) {
+        let value = value.trim();
+        if value.is_empty() {
+            fields.insert(column.to_string(), JsonValue::Null);
+            continue;
+        }
+        let parsed = match field_type(column) {
+            Some(FieldType::Integer) => value.parse::<i64>().ok().map(JsonValue::from),
+            Some(FieldType::Real) => value
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(JsonValue::Number),
+            _ => Some(JsonValue::from(value)),
+        };
+        let value = parsed
+            .ok_or_else(|| SupplierError::InvalidArguments(format!("{} {:?} is not a valid number", column, value)))?;
+        fields.insert(column.to_string(), value);
+    }
+    Ok(fields)
+}
+
+/// 把JSON数组中的每个对象解析为待导入的记录
+pub fn import_rows_from_json(records: JsonValue) -> Result<Vec<Result<SupplierFields, SupplierError>>, SupplierError> {
+    let JsonValue::Array(records) = records else {
+        return Err(SupplierError::InvalidArguments("expected a JSON array of supplier records".to_string()));
+    };
+    Ok(records
+        .into_iter()
+        .map(|record| {
+            serde_json::from_value(record)
+                .map_err(|e| SupplierError::InvalidArguments(format!("invalid supplier record: {}", e)))
+        })
+        .collect())
+}
+
+/// 导出的列，默认为id和 [`COLUMNS`] 中的全部列
+pub fn export_columns(columns: Option<&[String]>) -> Result<Vec<&'static str>, SupplierError> {
+    let Some(columns) = columns else {
+        return Ok(["id"].into_iter().chain(COLUMNS).collect());
+    };
+    if columns.is_empty() {
+        return Err(SupplierError::InvalidArguments("columns must not be empty".to_string()));
+    }
+    let mut selected = Vec::with_capacity(columns.len());
+    for column in columns {
+        let column = column_name(column)?;
+        if selected.contains(&column) {
+            return Err(SupplierError::InvalidArguments(format!("duplicate column {:?}", column)));
+        }
+        selected.push(column);
+    }
+    Ok(selected)
+}
+
+/// 按RFC 4180生成CSV：第一行为列名，行尾为CRLF，空值为空字段
+pub fn to_csv(suppliers: &[Supplier], columns: &[&str]) -> Result<String, SupplierError> {
+    let mut writer = WriterBuilder::new().terminator(Terminator::CRLF).from_writer(Vec::new());
+    writer.write_record(columns).map_err(io::Error::from)?;
+    for supplier in suppliers {
+        let fields = columns.iter().map(|column| match supplier.value(column) {
+            Value::Integer(value) => value.to_string(),
+            Value::Real(value) => value.to_string(),
+            Value::Text(value) => value,
+            Value::Null | Value::Blob(_) => String::new(),
+        });
+        writer.write_record(fields).map_err(io::Error::from)?;
+    }
+    let bytes = writer.into_inner().map_err(|e| e.into_error())?;
+    // 写入的都是UTF-8字符串
+    Ok(String::from_utf8(bytes).unwrap_or_default())
+}
+
+/// 把导出文件的相对路径解析到导出目录中，返回文件的绝对路径。
+///
+/// 只接受不含 `..`、根目录或盘符的相对路径；没有扩展名时补上 `.csv`。
+/// 导出目录和中间目录不存在时创建，并检查符号链接解析后仍在导出目录内。
+pub fn resolve_export_path(export_dir: &Path, requested: &str) -> Result<PathBuf, SupplierError> {
+    let not_allowed = |reason: &str| SupplierError::PathNotAllowed(format!("{:?} {}", requested, reason));
+    let mut relative = PathBuf::from(requested.trim());
+    if relative.as_os_str().is_empty() {
+        return Err(not_allowed("is empty"));
+    }
+    if !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(not_allowed("must be a relative path inside the export directory"));
+    }
+    match relative.extension().and_then(|ext| ext.to_str()) {
+        None => {
+            relative.set_extension("csv");
+        }
+        Some(ext) if ext.eq_ignore_ascii_case("csv") => {}
+        Some(_) => return Err(not_allowed("must have a .csv extension")),
+    }
+
+    fs::create_dir_all(export_dir)?;
+    let export_dir = export_dir.canonicalize()?;
+    let target = export_dir.join(&relative);
+    let parent = target.parent().unwrap_or(&export_dir);
+    fs::create_dir_all(parent)?;
+    let parent = parent.canonicalize()?;
+    if !parent.starts_with(&export_dir) {
+        return Err(not_allowed("resolves outside the export directory"));
+    }
+    let target = parent.join(target.file_name().unwrap_or_default());
+    if fs::symlink_metadata(&target).is_ok_and(|meta| !meta.is_file()) {
+        return Err(not_allowed("exists and is not a regular file"));
+    }
+    Ok(target)
+}
+
+struct State {
+    path: Option<PathBuf>,
+    export_dir: Option<PathBuf>,
+    store: Option<SupplierStore>,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    path: None,
+    export_dir: None,
+    store: None,
+});
+
+/// 设置数据库路径和导出目录，之后第一次使用时按新路径打开数据库
+pub fn configure(config: &SuppliersConfig) {
+    if let Ok(mut state) = STATE.lock() {
+        state.path = Some(config.db_path.clone());
+        state.export_dir = Some(config.export_dir.clone());
+        state.store = None;
+    }
+}
+
+/// 导出文件所在的目录
+pub fn export_dir() -> PathBuf {
+    STATE
+        .lock()
+        .ok()
+        .and_then(|state| state.export_dir.clone())
+        .unwrap_or_else(|| SuppliersConfig::default().export_dir)
+}
+
+/// 在阻塞线程中使用全局数据库，第一次使用时打开
+pub async fn with_store<T, F>(f: F) -> Result<T, SupplierError>
+where
+    T: Send + 'static,
+    F: FnOnce(&SupplierStore) -> Result<T, SupplierError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || {
+        let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+        if state.store.is_none() {
+            let path = state
+                .path
+                .clone()
+                .unwrap_or_else(|| SuppliersConfig::default().db_path);
+            tracing::info!("打开供应商数据库: {}", path.display());
+            state.store = Some(SupplierStore::open(&path)?);
+        }
+        f(state.store.as_ref().unwrap())
+    })
+    .await
+    .map_err(|e| SupplierError::Io(io::Error::other(e)))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn supplier(contact: &str) -> Supplier {
+        Supplier {
+            contact: contact.to_string(),
+            phone: Some("138-0000-0000".to_string()),
+            quantity: Some(8),
+            location: Some("深圳".to_string()),
+            price: Some(12000.0),
+            server_name: Some("H100 x8".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_add_and_get() {
+        let store = SupplierStore::open_in_memory().unwrap();
+        let added = store.add(supplier(" 张三 ")).unwrap();
+        assert_eq!(added.id, Some(1));
+        assert_eq!(added.contact, "张三");
+
+        let loaded = store.get(1).unwrap().unwrap();
+        assert_eq!(loaded, added);
+        assert!(store.get(2).unwrap().is_none());
+
+        // 指定id
+        let added = store
+            .add(Supplier {
+                id: Some(10),
+                ..supplier("李四")
+            })
+            .unwrap();
+        assert_eq!(added.id, Some(10));
+    }
+
+    #[test]
+    fn test_rejects_invalid_and_duplicate_records() {
+        let store = SupplierStore::open_in_memory().unwrap();
+        store.add(supplier("张三")).unwrap();
+
+        let err = store.add(supplier("张三")).unwrap_err();
+        assert!(matches!(err, SupplierError::Duplicate(1)), "{}", err);
+        // 空字符串与未填写相同
+        let err = store
+            .add(Supplier {
+                wechat: Some(" ".to_string()),
+                ..supplier("张三")
+            })
+            .unwrap_err();
+        assert!(matches!(err, SupplierError::Duplicate(1)), "{}", err);
+        let err = store
+            .add(Supplier {
+                id: Some(1),
+                ..supplier("李四")
+            })
+            .unwrap_err();
+        assert!(matches!(err, SupplierError::Duplicate(1)), "{}", err);
+
+        for invalid in [
+            supplier(" "),
+            Supplier {
+                quantity: Some(-1),
+                ..supplier("王五")
+            },
+            Supplier {
+                price: Some(f64::NAN),
+                ..supplier("王五")
+            },
+            Supplier {
+                phone: Some("call me".to_string()),
+                ..supplier("王五")
+            },
+        ] {
+            let err = store.add(invalid).unwrap_err();
+            assert_eq!(err.kind(), "invalid_arguments", "{}", err);
+        }
+    }
+
+    // 测试用的数据：三个供应商，部分字段为空
+    fn query_store() -> SupplierStore {
+        let store = SupplierStore::open_in_memory().unwrap();
+        store.add(supplier("张三")).unwrap();
+        store
+            .add(Supplier {
+                location: Some("北京".to_string()),
+                price: Some(8000.0),
+                quantity: Some(2),
+                server_name: Some("A100 x4".to_string()),
+                ..supplier("李四")
+            })
+            .unwrap();
+        store
+            .add(Supplier {
+                contact: "王五".to_string(),
+                price: Some(20000.0),
+                server_name: Some("H100 SXM x8".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        store
+    }
+
+    fn contacts(store: &SupplierStore, filters: &[Filter]) -> Vec<String> {
+        let query = filters
+            .iter()
+            .try_fold(SupplierQuery::new(), |query, filter| query.filter(filter))
+            .unwrap();
+        store.query(&query).unwrap().into_iter().map(|s| s.contact).collect()
+    }
+
+    #[test]
+    fn test_filter_eq_and_ne() {
+        let store = query_store();
+        assert_eq!(contacts(&store, &[Filter::new("location", FilterOp::Eq, json!("北京"))]), ["李四"]);
+        assert_eq!(contacts(&store, &[Filter::new("id", FilterOp::Eq, json!(1))]), ["张三"]);
+        // 值为NULL的记录不匹配ne
+        assert_eq!(contacts(&store, &[Filter::new("location", FilterOp::Ne, json!("北京"))]), ["张三"]);
+    }
+
+    #[test]
+    fn test_filter_comparisons() {
+        let store = query_store();
+        let price = |op, value: JsonValue| contacts(&store, &[Filter::new("price", op, value)]);
+        assert_eq!(price(FilterOp::Gt, json!(12000)), ["王五"]);
+        assert_eq!(price(FilterOp::Gte, json!(12000)), ["张三", "王五"]);
+        assert_eq!(price(FilterOp::Lt, json!(12000)), ["李四"]);
+        assert_eq!(price(FilterOp::Lte, json!(12000.0)), ["张三", "李四"]);
+        // 多个条件为AND
+        let both = contacts(
+            &store,
+            &[
+                Filter::new("quantity", FilterOp::Gte, json!(2)),
+                Filter::new("quantity", FilterOp::Lt, json!(8)),
+            ],
+        );
+        assert_eq!(both, ["李四"]);
+    }
+
+    #[test]
+    fn test_filter_null_handling() {
+        let store = query_store();
+        assert_eq!(contacts(&store, &[Filter::new("location", FilterOp::IsNull, None)]), ["王五"]);
+        assert_eq!(contacts(&store, &[Filter::new("location", FilterOp::NotNull, None)]), ["张三", "李四"]);
+        assert_eq!(contacts(&store, &[Filter::new("location", FilterOp::Eq, json!(null))]), ["王五"]);
+        assert_eq!(contacts(&store, &[Filter::new("quantity", FilterOp::Ne, None)]), ["张三", "李四"]);
+    }
+
+    #[test]
+    fn test_filter_like() {
+        let store = query_store();
+        assert_eq!(contacts(&store, &[Filter::new("server_name", FilterOp::Like, json!("h100"))]), ["张三", "王五"]);
+        assert_eq!(contacts(&store, &[Filter::new("server_name", FilterOp::Like, json!("H100 %"))]), ["张三", "王五"]);
+        assert_eq!(contacts(&store, &[Filter::new("server_name", FilterOp::Like, json!("%x4"))]), ["李四"]);
+    }
+
+    #[test]
+    fn test_invalid_filters_are_rejected() {
+        for filter in [
+            Filter::new("contact; DROP TABLE suppliers", FilterOp::Eq, json!("x")),
+            Filter::new("price", FilterOp::Gt, json!("cheap")),
+            Filter::new("quantity", FilterOp::Eq, json!(1.5)),
+            Filter::new("price", FilterOp::Like, json!("1%")),
+            Filter::new("price", FilterOp::Lt, None),
+            Filter::new("location", FilterOp::IsNull, json!("北京")),
+        ] {
+            let err = SupplierQuery::new().filter(&filter).unwrap_err();
+            assert_eq!(err.kind(), "invalid_arguments", "{:?}", filter);
+        }
+        assert!(SupplierQuery::new().order_by("price sideways").is_err());
+        assert!(SupplierQuery::new().order_by("price; DROP TABLE suppliers").is_err());
+
+        let op: FilterOp = serde_json::from_value(json!(">=")).unwrap();
+        assert_eq!(op, FilterOp::Gte);
+        assert!(serde_json::from_value::<FilterOp>(json!("between")).is_err());
+    }
+
+    #[test]
+    fn test_values_are_bound_not_interpolated() {
+        let store = query_store();
+        let injection = "北京' OR '1'='1";
+        let query = SupplierQuery::new()
+            .filter(&Filter::new("location", FilterOp::Eq, json!(injection)))
+            .unwrap();
+        let (sql, params) = query.build();
+        assert!(!sql.contains(injection), "{}", sql);
+        assert_eq!(params, vec![Value::Text(injection.to_string())]);
+        assert!(store.query(&query).unwrap().is_empty());
+        assert_eq!(store.count(&SupplierQuery::new()).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_order_and_pagination() {
+        let store = query_store();
+        let query = SupplierQuery::new().order_by("price desc").unwrap();
+        let names: Vec<_> = store.query(&query).unwrap().into_iter().map(|s| s.contact).collect();
+        assert_eq!(names, ["王五", "张三", "李四"]);
+
+        let page = store.query(&query.clone().limit(1).offset(1)).unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].contact, "张三");
+        let rest = store.query(&query.clone().offset(2)).unwrap();
+        assert_eq!(rest.len(), 1);
+        assert_eq!(store.count(&query.limit(1)).unwrap(), 3);
+
+        // NULL排在升序的最前面，相同值按id排序
+        let query = SupplierQuery::new().order_by("location, contact desc").unwrap();
+        let names: Vec<_> = store.query(&query).unwrap().into_iter().map(|s| s.contact).collect();
+        assert_eq!(names, ["王五", "李四", "张三"]);
+    }
+
+    #[test]
+    fn test_csv_escaping() {
+        let suppliers = vec![
+            Supplier {
+                id: Some(1),
+                contact: "张三, 李四".to_string(),
+                price: Some(12000.5),
+                server_config: Some("8 x \"H100\"\n2TB".to_string()),
+                ..Default::default()
+            },
+            Supplier {
+                id: Some(2),
+                contact: "王五".to_string(),
+                quantity: Some(8),
+                ..Default::default()
+            },
+        ];
+        let columns = ["contact", "price", "server_config", "quantity"];
+        assert_eq!(
+            to_csv(&suppliers, &columns).unwrap(),
+            "contact,price,server_config,quantity\r\n\
+             \"张三, 李四\",12000.5,\"8 x \"\"H100\"\"\n2TB\",\r\n\
+             王五,,,8\r\n"
+        );
+        assert_eq!(to_csv(&[], &["id"]).unwrap(), "id\r\n");
+    }
+
+    #[test]
+    fn test_export_columns() {
+        let all = export_columns(None).unwrap();
+        assert_eq!(all.len(), 16);
+        assert_eq!(all[0], "id");
+
+        let columns = ["price".to_string(), " id ".to_string()];
+        assert_eq!(export_columns(Some(&columns)).unwrap(), ["price", "id"]);
+        for invalid in [vec![], vec!["password".to_string()], vec!["id".to_string(), "id".to_string()]] {
+            assert!(matches!(export_columns(Some(&invalid)), Err(SupplierError::InvalidArguments(_))));
+        }
+    }
+
+    #[test]
+    fn test_export_path_is_confined() {
+        let dir = tempfile::tempdir().unwrap();
+        let export_dir = dir.path().join("exports");
+
+        let path = resolve_export_path(&export_dir, "2024/h100").unwrap();
+        let export_dir = export_dir.canonicalize().unwrap();
+        assert_eq!(path, export_dir.join("2024").join("h100.csv"));
+        assert!(export_dir.join("2024").is_dir());
+        assert_eq!(resolve_export_path(&export_dir, "a.CSV").unwrap(), export_dir.join("a.CSV"));
+
+        let outside = dir.path().join("outside.csv");
+        for invalid in ["", "../outside.csv", "a/../../outside.csv", outside.to_str().unwrap(), "./a.csv", "a.sh"] {
+            let result = resolve_export_path(&export_dir, invalid);
+            assert!(matches!(result, Err(SupplierError::PathNotAllowed(_))), "{:?}", invalid);
+        }
+        assert!(!outside.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_export_path_rejects_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let export_dir = dir.path().join("exports");
+        fs::create_dir(&export_dir).unwrap();
+        std::os::unix::fs::symlink(dir.path(), export_dir.join("link")).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("target.csv"), export_dir.join("file.csv")).unwrap();
+
+        for invalid in ["link/outside.csv", "file.csv"] {
+            let result = resolve_export_path(&export_dir, invalid);
+            assert!(matches!(result, Err(SupplierError::PathNotAllowed(_))), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_update_delete_and_restore() {
+        let store = query_store();
+        let fields = |value: JsonValue| value.as_object().unwrap().clone();
+        let updated = store
+            .update(2, &fields(json!({ "price": 9000, "wechat": "lisi", "location": null })))
+            .unwrap();
+        assert_eq!(updated.price, Some(9000.0));
+        assert_eq!(updated.wechat.as_deref(), Some("lisi"));
+        assert_eq!(updated.location, None);
+        assert_eq!(updated.contact, "李四");
+        assert_eq!(store.get(2).unwrap().unwrap(), updated);
+
+        for invalid in [
+            json!({}),
+            json!({ "id": 5 }),
+            json!({ "password": "x" }),
+            json!({ "contact": null }),
+            json!({ "quantity": -1 }),
+        ] {
+            let result = store.update(2, &fields(invalid.clone()));
+            assert!(matches!(result, Err(SupplierError::InvalidArguments(_))), "{}", invalid);
+        }
+        // 修改后与其他记录相同
+        let mut same = fields(serde_json::to_value(store.get(1).unwrap().unwrap()).unwrap());
+        same.remove("id");
+        assert!(matches!(store.update(2, &same), Err(SupplierError::Duplicate(1))));
+        assert!(matches!(store.update(99, &fields(json!({ "price": 1 }))), Err(SupplierError::NotFound(99))));
+
+        store.delete(2).unwrap();
+        assert!(store.get(2).unwrap().is_none());
+        assert_eq!(store.count(&SupplierQuery::new()).unwrap(), 2);
+        assert!(matches!(store.delete(2), Err(SupplierError::NotFound(2))));
+        assert!(matches!(store.update(2, &fields(json!({ "price": 1 }))), Err(SupplierError::NotFound(2))));
+        // 已删除的记录仍占用id
+        let reused = Supplier {
+            id: Some(2),
+            ..supplier("赵六")
+        };
+        assert!(matches!(store.add(reused), Err(SupplierError::Duplicate(2))));
+
+        assert_eq!(store.restore(2).unwrap(), updated);
+        assert_eq!(store.restore(2).unwrap(), updated);
+        assert_eq!(store.count(&SupplierQuery::new()).unwrap(), 3);
+        assert!(matches!(store.restore(99), Err(SupplierError::NotFound(99))));
+    }
+
+    #[test]
+    fn test_import_csv_quoting() {
+        let text = "\u{feff}contact,server_config\r\n张三,\"8 x \"\"H100\"\", 2TB\"\r\n\r\n李四,\"多行\n配置\"\n王五,";
+        let rows: Vec<JsonValue> = import_rows_from_csv(text)
+            .unwrap()
+            .into_iter()
+            .map(|row| JsonValue::Object(row.unwrap()))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                json!({ "contact": "张三", "server_config": "8 x \"H100\", 2TB" }),
+                json!({ "contact": "李四", "server_config": "多行\n配置" }),
+                json!({ "contact": "王五", "server_config": null }),
+            ]
+        );
+
+        // 导出的CSV可以原样导入
+        let store = query_store();
+        let exported = to_csv(&store.query(&SupplierQuery::new()).unwrap(), &export_columns(None).unwrap()).unwrap();
+        let rows: Vec<Supplier> = import_rows_from_csv(&exported)
+            .unwrap()
+            .into_iter()
+            .map(|row| serde_json::from_value(JsonValue::Object(row.unwrap())).unwrap())
+            .collect();
+        assert_eq!(rows, store.query(&SupplierQuery::new()).unwrap());
+    }
+
+    #[test]
+    fn test_import_rows() {
+        let store = query_store();
+        let csv = "id,contact,price,quantity\n\
+                   2,李四,7000,2\n\
+                   ,赵六,,\n\
+                   ,王五,cheap,\n";
+        let rows = import_rows_from_csv(csv).unwrap();
+        assert_eq!(rows.len(), 3);
+
+        // 试运行不写入数据库
+        let report = store.import(import_rows_from_csv(csv).unwrap(), true).unwrap();
+        assert!(report.dry_run && !report.committed);
+        assert_eq!((report.created, report.updated, report.unchanged, report.invalid), (1, 1, 0, 1));
+        let actions: Vec<_> = report.rows.iter().map(|row| row.action).collect();
+        assert_eq!(actions, [ImportAction::Updated, ImportAction::Created, ImportAction::Invalid]);
+        assert_eq!(report.rows[0].changed_fields, ["price"]);
+        assert_eq!(report.rows[2].error.as_ref().unwrap()["type"], "invalid_arguments");
+        assert_eq!(store.get(2).unwrap().unwrap().price, Some(8000.0));
+        assert_eq!(store.count(&SupplierQuery::new()).unwrap(), 3);
+
+        // 有不合法的行时整个导入回滚
+        let report = store.import(rows, false).unwrap();
+        assert!(!report.committed);
+        assert_eq!(store.get(2).unwrap().unwrap().price, Some(8000.0));
+        assert_eq!(store.count(&SupplierQuery::new()).unwrap(), 3);
+
+        let json = json!([
+            { "id": 2, "contact": "李四", "price": 7000, "quantity": 2 },
+            { "contact": "赵六" },
+            { "contact": "王五", "price": 20000.0, "server_name": "H100 SXM x8" },
+        ]);
+        let report = store.import(import_rows_from_json(json).unwrap(), false).unwrap();
+        assert!(report.committed);
+        assert_eq!((report.created, report.updated, report.unchanged, report.invalid), (1, 1, 1, 0));
+        assert_eq!(report.rows[1].id, Some(4));
+        assert_eq!(report.rows[2].id, Some(3));
+        assert_eq!(store.get(2).unwrap().unwrap().price, Some(7000.0));
+        // 只修改给出的字段
+        assert_eq!(store.get(2).unwrap().unwrap().location.as_deref(), Some("北京"));
+        assert_eq!(store.count(&SupplierQuery::new()).unwrap(), 4);
+
+        // 只有部分列的表格：空字段清空该字段，表头中没有的列保持不变
+        let report = store.import(import_rows_from_csv("id,location,price\n2,,7500\n1,深圳,\n").unwrap(), true).unwrap();
+        assert_eq!((report.updated, report.unchanged), (2, 0));
+        assert_eq!(report.rows[0].changed_fields, ["location", "price"]);
+        assert_eq!(report.rows[1].changed_fields, ["price"]);
+        let report = store.import(import_rows_from_csv("id,price\n2,7500\n3,20000").unwrap(), false).unwrap();
+        assert_eq!((report.updated, report.unchanged), (1, 1));
+        let updated = store.get(2).unwrap().unwrap();
+        assert_eq!((updated.price, updated.quantity, updated.contact.as_str()), (Some(7500.0), Some(2), "李四"));
+        let serialized = serde_json::to_value(&report).unwrap();
+        assert_eq!(serialized["rows"][0]["changed_fields"], json!(["price"]));
+        assert!(serialized["rows"][1].get("changed_fields").is_none());
+        // 修改后的记录仍需合法
+        let report = store.import(import_rows_from_csv("id,contact\n2,\n").unwrap(), false).unwrap();
+        assert_eq!(report.invalid, 1);
+
+        for csv in ["", "contact,password\n张三,x", "contact,contact\n张三,李四"] {
+            assert!(matches!(import_rows_from_csv(csv), Err(SupplierError::InvalidArguments(_))), "{:?}", csv);
+        }
+        let rows = import_rows_from_csv("contact,price\n张三\n李四,cheap").unwrap();
+        assert!(rows.iter().all(Result::is_err));
+        assert!(import_rows_from_json(json!({ "contact": "张三" })).is_err());
+    }
+}
@@ -1,10 +1,39 @@
 use anyhow::Result;
 use mcp_core::tool_text_content;
-use mcp_core::types::ToolResponseContent;
+use mcp_core::tools::ToolHandlerFn;
+use mcp_core::types::{CallToolRequest, CallToolResponse, ToolResponseContent};
 use mcp_core_macros::tool;
-use std::process::Command;
-use std::path::Path;
-use std::io::{Error as IoError, ErrorKind};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::embedding::{self, BatchEmbeddingResult, EmbeddingResult};
+use crate::gpu_specs::{self, Comparison, GpuSpecError, Lang, OutputFormat};
+use crate::jobs::{self, JobError, JobState};
+use crate::local_gpus;
+use crate::model_fit::{FitRequest, ModelFit, Precision, Workload, DEFAULT_CONTEXT_LENGTH};
+use crate::models::{self, ModelType};
+use crate::scheduler::{self, Priority};
+use crate::server::context;
+use crate::suppliers::{self, Filter, Supplier, SupplierError, SupplierQuery};
+use crate::wei_run::{self, WeiRunError, WeiRunLocation};
+
+/// 为 `#[tool]` 生成的工具类型添加 `handler()`，用 [`call_tool`] 执行工具函数，
+/// 失败时返回带类型化错误的响应。
+///
+/// 参数依次为工具类型、`#[tool]` 生成的参数结构体，以及工具函数和按顺序传入的参数字段。
+macro_rules! typed_handler {
+    ($tool:ident, $params:ident, $f:ident($($field:ident),* $(,)?)) => {
+        impl $tool {
+            pub fn handler() -> ToolHandlerFn {
+                |req| Box::pin(call_tool(req, |p: $params| $f($(p.$field),*)))
+            }
+        }
+    };
+}
 
 #[tool(
     name = "Add",
@@ -56,97 +85,334 @@ pub async fn query_angel_type(angel_type: String) -> Result<ToolResponseContent>
 
 #[tool(
     name = "QueryGPUSpecs",
-    description = "查询NVIDIA H100和A100 GPU的详细配置信息",
-    params(gpu_model = "GPU型号，可选值：H100或A100")
+    description = "查询NVIDIA GPU的详细配置信息：架构、CUDA/Tensor核心、显存容量和带宽、FP8/FP16/FP32算力、NVLink、TDP和MIG支持。format为json时返回结构化的规格，没有数据的字段为null；型号未知时返回unknown_model错误，suggestions为最接近的型号，knownModels列出已知型号",
+    params(
+        gpu_model = "GPU型号，如H100、A100、H200、B200、L40S、RTX 4090；不同版本可以带外形规格或显存容量，如H100 PCIe、A100 40GB",
+        format = "输出格式：text、json或markdown，默认为text",
+        lang = "text和markdown输出的语言：zh或en，默认为zh"
+    )
 )]
-pub async fn query_gpu_specs(gpu_model: String) -> Result<ToolResponseContent> {
-    let specs = match gpu_model.to_uppercase().as_str() {
-        "H100" => "NVIDIA H100 GPU规格：
+pub async fn query_gpu_specs(
+    gpu_model: String,
+    format: Option<String>,
+    lang: Option<String>,
+) -> Result<ToolResponseContent, GpuSpecError> {
+    let format: OutputFormat = parse_option(format.as_deref())?;
+    let lang: Lang = parse_option(lang.as_deref())?;
+    let catalog = gpu_specs::catalog();
+    let spec = catalog.lookup(&gpu_model)?;
+    Ok(tool_text_content!(spec.render(format, lang)))
+}
 
-            - 架构：Hopper架构
+#[tool(
+    name = "CompareGPUs",
+    description = "对比两款或更多GPU的规格，以第一款为基准给出各项指标的比值（如H100/A100的FP16算力），没有数据的字段明确标为无数据。format为markdown时返回对比表和比值表，为json时返回 {\"models\", \"baseline\", \"gpus\", \"metrics\": [{\"field\", \"values\", \"ratios\"}], \"missing\"}",
+    params(
+        gpu_models = "要对比的GPU型号数组，至少两款，如 [\"A100\", \"H100\"]；第一款为比值的基准",
+        format = "输出格式：markdown或json，默认为markdown",
+        lang = "markdown输出的语言：zh或en，默认为zh"
+    )
+)]
+pub async fn compare_gpus(
+    gpu_models: Vec<String>,
+    format: Option<String>,
+    lang: Option<String>,
+) -> Result<ToolResponseContent, GpuSpecError> {
+    // 对比结果没有纯文本形式，text按markdown输出
+    let format = match parse_option(format.as_deref())? {
+        OutputFormat::Text => OutputFormat::Markdown,
+        format => format,
+    };
+    let lang: Lang = parse_option(lang.as_deref())?;
+    let catalog = gpu_specs::catalog();
+    let comparison = Comparison::new(&catalog, &gpu_models)?;
+    let output = match format {
+        OutputFormat::Json => comparison.to_json().to_string(),
+        _ => comparison.to_markdown(lang),
+    };
+    Ok(tool_text_content!(output))
+}
 
-            - CUDA核心：14,592个
+typed_handler!(CompareGpus, CompareGpusParameters, compare_gpus(gpu_models, format, lang));
 
-            - Tensor核心：第四代，456个
+#[tool(
+    name = "EstimateModelFit",
+    description = "估算大模型推理或训练所需的显存（权重、KV缓存，训练时另加梯度、优化器状态和激活值），并根据GPU规格目录列出每款GPU需要几张、单卡能否容纳。未提供模型结构时按参数量推算层数和hidden size，结果为粗略估算。format为json时返回 {\"shape\", \"memory_gib\", \"gpus\": [{\"name\", \"memory_gb\", \"gpus_needed\", \"fits_single\"}], \"unknown\"}",
+    params(
+        parameters_b = "模型参数量，单位为十亿，如70表示70B",
+        precision = "权重精度：fp32、fp16、bf16、fp8、int8或int4，默认为fp16；训练不支持int8和int4",
+        context_length = "上下文长度（token数），默认为4096",
+        batch_size = "batch size，默认为1",
+        mode = "inference（推理）或training（训练），默认为inference",
+        num_layers = "可选，模型层数",
+        hidden_size = "可选，hidden size",
+        num_attention_heads = "可选，注意力头数，与num_kv_heads一起用于计算分组查询注意力的KV缓存",
+        num_kv_heads = "可选，KV头数，未提供时按多头注意力计算",
+        gpu_models = "可选，只考虑这些GPU型号，默认为目录中的所有GPU",
+        format = "输出格式：markdown或json，默认为markdown",
+        lang = "markdown输出的语言：zh或en，默认为zh"
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn estimate_model_fit(
+    parameters_b: f64,
+    precision: Option<String>,
+    context_length: Option<u64>,
+    batch_size: Option<u64>,
+    mode: Option<String>,
+    num_layers: Option<u32>,
+    hidden_size: Option<u32>,
+    num_attention_heads: Option<u32>,
+    num_kv_heads: Option<u32>,
+    gpu_models: Option<Vec<String>>,
+    format: Option<String>,
+    lang: Option<String>,
+) -> Result<ToolResponseContent, GpuSpecError> {
+    let request = FitRequest {
+        parameters_b,
+        precision: parse_option::<Precision>(precision.as_deref())?,
+        context_length: context_length.unwrap_or(DEFAULT_CONTEXT_LENGTH),
+        batch_size: batch_size.unwrap_or(1),
+        workload: parse_option::<Workload>(mode.as_deref())?,
+        num_layers,
+        hidden_size,
+        num_attention_heads,
+        num_kv_heads,
+    };
+    let format = match parse_option(format.as_deref())? {
+        OutputFormat::Text => OutputFormat::Markdown,
+        format => format,
+    };
+    let lang: Lang = parse_option(lang.as_deref())?;
+    let catalog = gpu_specs::catalog();
+    let fit = ModelFit::new(&catalog, request, gpu_models.as_deref().unwrap_or_default())?;
+    let output = match format {
+        OutputFormat::Json => fit.to_json().to_string(),
+        _ => fit.to_markdown(lang),
+    };
+    Ok(tool_text_content!(output))
+}
 
-            - 性能特点：
+typed_handler!(
+    EstimateModelFit,
+    EstimateModelFitParameters,
+    estimate_model_fit(
+        parameters_b,
+        precision,
+        context_length,
+        batch_size,
+        mode,
+        num_layers,
+        hidden_size,
+        num_attention_heads,
+        num_kv_heads,
+        gpu_models,
+        format,
+        lang
+    )
+);
 
-              · 相比A100提供高达30倍的推理性能提升
+// 解析可选的枚举参数，未指定或为空时使用默认值
+fn parse_value<T>(value: Option<&str>) -> Result<T, String>
+where
+    T: std::str::FromStr<Err = String> + Default,
+{
+    match value.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(value) => value.parse(),
+        None => Ok(T::default()),
+    }
+}
 
-              · GPT-3训练性能提升4倍
+fn parse_option<T>(value: Option<&str>) -> Result<T, GpuSpecError>
+where
+    T: std::str::FromStr<Err = String> + Default,
+{
+    parse_value(value).map_err(GpuSpecError::InvalidArguments)
+}
 
-              · 新增Transformer引擎，专为大语言模型优化
+typed_handler!(QueryGpuSpecs, QueryGpuSpecsParameters, query_gpu_specs(gpu_model, format, lang));
 
-              · NVLink带宽提升至900 GB/s
+/// 执行Wei-Assistant-GPU命令的通用函数
+///
+/// 命令先在GPU任务队列中等待运行名额，队列已满时返回 `Busy`。
+/// 子进程受配置的超时时间限制，客户端取消当前请求时会被终止。
+async fn run_wei_command(command: &str, args: &[&str], priority: Priority) -> Result<String, WeiRunError> {
+    // 按配置、环境变量、PATH等顺序查找wei-run
+    let wei_run_path = wei_run::locate()?.path;
+    let cancel = context::cancellation_token();
+    let _permit = scheduler::acquire(priority, &cancel).await?;
 
-            - 主要优势：
+    wei_run::run(&wei_run_path, command, args, wei_run::timeout(), &cancel).await
+}
 
-              · 适用于下一代AI工作负载
+/// 在同一个GPU任务名额内依次执行多次命令。
+///
+/// 批量调用只排队一次，不会在处理了一部分之后因队列已满而失败。
+async fn run_wei_batch(command: &str, batch: &[Vec<&str>], priority: Priority) -> Result<Vec<String>, WeiRunError> {
+    let wei_run_path = wei_run::locate()?.path;
+    let cancel = context::cancellation_token();
+    let _permit = scheduler::acquire(priority, &cancel).await?;
 
-              · 支持FP8精度
+    let mut outputs = Vec::with_capacity(batch.len());
+    for args in batch {
+        outputs.push(wei_run::run(&wei_run_path, command, args, wei_run::timeout(), &cancel).await?);
+    }
+    Ok(outputs)
+}
 
-              · 增强的MIG功能，支持更灵活的资源分配
+/// 执行命令，客户端提供了进度令牌时把输出逐段作为进度通知发送
+async fn run_wei_command_streaming(command: &str, args: &[&str], priority: Priority) -> Result<String, WeiRunError> {
+    if context::progress_token().is_none() {
+        return run_wei_command(command, args, priority).await;
+    }
+    let wei_run_path = wei_run::locate()?.path;
+    let cancel = context::cancellation_token();
+    let _permit = scheduler::acquire(priority, &cancel).await?;
 
-              · 配备专用Transformer引擎，可处理万亿参数级语言模型",
-        "A100" => "NVIDIA A100 GPU规格：
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let run = wei_run::run_streaming(&wei_run_path, command, args, wei_run::timeout(), &cancel, tx);
+    // 子进程的输出读完后发送端被丢弃，转发随之结束
+    let forward = async {
+        let mut chunks = 0u64;
+        while let Some(chunk) = rx.recv().await {
+            chunks += 1;
+            context::report_progress(chunks as f64, Some(chunk)).await;
+        }
+    };
 
-            - 架构：Ampere架构
+    let (result, ()) = tokio::join!(run, forward);
+    result
+}
 
-            - CUDA核心：6,912个
+// 启动自检时执行version的最长时间
+const SELF_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
 
-            - Tensor核心：第三代，432个
+/// 启动自检：查找wei-run并执行 `version`，返回找到的位置和版本输出
+pub async fn wei_run_self_check() -> Result<(WeiRunLocation, String), WeiRunError> {
+    let location = wei_run::locate()?;
+    // 自检不应长时间阻塞启动
+    let timeout = wei_run::timeout().min(SELF_CHECK_TIMEOUT);
+    let version = wei_run::run(&location.path, "version", &[], timeout, &context::cancellation_token()).await?;
+    Ok((location, version.trim().to_string()))
+}
 
-            - 性能特点：
+fn parse_model_type(value: &str) -> Result<ModelType, WeiRunError> {
+    value.parse().map_err(WeiRunError::InvalidArguments)
+}
 
-              · 相比上一代Volta架构提供20倍性能提升
+fn parse_priority(value: Option<&str>) -> Result<Priority, WeiRunError> {
+    value
+        .map(|value| value.parse().map_err(WeiRunError::InvalidArguments))
+        .unwrap_or(Ok(Priority::Normal))
+}
 
-              · 优秀的通用AI计算性能
+// 参数不能为空字符串
+fn require_non_empty(name: &str, value: &str) -> Result<(), WeiRunError> {
+    if value.trim().is_empty() {
+        return Err(WeiRunError::InvalidArguments(format!("{} must not be empty", name)));
+    }
+    Ok(())
+}
 
-              · 支持多实例GPU(MIG)技术
+fn require_job_id(job_id: &str) -> Result<(), JobError> {
+    if job_id.trim().is_empty() {
+        return Err(JobError::InvalidArguments("job_id must not be empty".to_string()));
+    }
+    Ok(())
+}
 
-            - 主要优势：
+/// 带类型的工具错误，失败时在 `_meta.error` 中给出错误类型和详情
+pub trait ToolError: fmt::Display {
+    /// 参数无法解析时的错误
+    fn invalid_arguments(message: String) -> Self;
+    fn to_json(&self) -> Value;
+}
 
-              · 适用于各类高性能计算和AI训练场景
+impl ToolError for WeiRunError {
+    fn invalid_arguments(message: String) -> Self {
+        WeiRunError::InvalidArguments(message)
+    }
 
-              · 优秀的性价比
+    fn to_json(&self) -> Value {
+        WeiRunError::to_json(self)
+    }
+}
 
-              · 成熟稳定的架构
+impl ToolError for JobError {
+    fn invalid_arguments(message: String) -> Self {
+        JobError::InvalidArguments(message)
+    }
 
-              · 广泛应用于数据中心和云计算",
-        _ => "未知的GPU型号。目前支持查询的GPU型号包括：H100和A100。"
-    };
-    Ok(tool_text_content!(specs.to_string()))
+    fn to_json(&self) -> Value {
+        JobError::to_json(self)
+    }
 }
 
-/// 执行Wei-Assistant-GPU命令的通用函数
-async fn run_wei_command(command: &str, args: &[&str]) -> Result<String, IoError> {
-    // 检查wei-run是否存在于上级目录
-    let wei_run_path = Path::new("..").join("wei-run");
-    if !wei_run_path.exists() {
-        return Err(IoError::new(
-            ErrorKind::NotFound,
-            format!("Wei-run executable not found at {:?}", wei_run_path)
-        ));
+impl ToolError for GpuSpecError {
+    fn invalid_arguments(message: String) -> Self {
+        GpuSpecError::InvalidArguments(message)
     }
 
-    // 执行命令
-    let output = Command::new(wei_run_path)
-        .arg(command)
-        .args(args)
-        .output()?;
-    
-    // 检查命令是否成功执行
-    if !output.status.success() {
-        let error_message = String::from_utf8_lossy(&output.stderr).to_string();
-        return Err(IoError::new(
-            ErrorKind::Other,
-            format!("Wei command failed: {}", error_message)
-        ));
+    fn to_json(&self) -> Value {
+        GpuSpecError::to_json(self)
     }
-    
-    // 返回命令的输出
-    let output_text = String::from_utf8_lossy(&output.stdout).to_string();
-    Ok(output_text)
+}
+
+impl ToolError for SupplierError {
+    fn invalid_arguments(message: String) -> Self {
+        SupplierError::InvalidArguments(message)
+    }
+
+    fn to_json(&self) -> Value {
+        SupplierError::to_json(self)
+    }
+}
+
+/// 执行工具并转换为MCP响应。
+///
+/// 与 `#[tool]` 生成的 `call()` 不同，失败时除了 `isError` 外，
+/// 还会在 `_meta.error` 中附带 [`ToolError::to_json`] 给出的错误类型和详情。
+/// 执行过GPU任务时，`_meta.queueWaitMs` 为这些任务在队列中等待的总时间。
+async fn call_tool<P, E, F, Fut>(req: CallToolRequest, f: F) -> CallToolResponse
+where
+    P: DeserializeOwned,
+    E: ToolError,
+    F: FnOnce(P) -> Fut,
+    Fut: Future<Output = Result<ToolResponseContent, E>>,
+{
+    let arguments = serde_json::to_value(req.arguments.unwrap_or_default()).unwrap_or_default();
+    let (result, stats) = scheduler::track(async {
+        match serde_json::from_value::<P>(arguments) {
+            Ok(params) => f(params).await,
+            Err(e) => Err(E::invalid_arguments(e.to_string())),
+        }
+    })
+    .await;
+
+    let mut meta = json!({});
+    if stats.jobs > 0 {
+        meta["queueWaitMs"] = json!(stats.queue_wait.as_millis() as u64);
+    }
+    let mut response = match result {
+        Ok(content) => CallToolResponse {
+            content: vec![content],
+            is_error: None,
+            meta: None,
+        },
+        Err(e) => {
+            tracing::warn!("工具 {} 执行失败: {}", req.name, e);
+            meta["error"] = e.to_json();
+            CallToolResponse {
+                content: vec![ToolResponseContent::Text { text: e.to_string() }],
+                is_error: Some(true),
+                meta: None,
+            }
+        }
+    };
+    if meta.as_object().is_some_and(|meta| !meta.is_empty()) {
+        response.meta = Some(meta);
+    }
+    response
 }
 
 #[tool(
@@ -155,65 +421,219 @@ async fn run_wei_command(command: &str, args: &[&str]) -> Result<String, IoError
     params(
         prompt = "生成文本的提示词",
         model = "要使用的模型名称，默认为当前加载的模型",
-        max_tokens = "生成的最大token数量，默认为1024"
+        max_tokens = "生成的最大token数量，默认为1024",
+        priority = "任务优先级：low、normal或high，默认为normal"
     )
 )]
-pub async fn generate_text(prompt: String, model: Option<String>, max_tokens: Option<i32>) -> Result<ToolResponseContent> {
+pub async fn generate_text(
+    prompt: String,
+    model: Option<String>,
+    max_tokens: Option<i32>,
+    priority: Option<String>,
+) -> Result<ToolResponseContent, WeiRunError> {
+    let generation = prepare_generation(prompt, model, max_tokens, priority)?;
+    // 生成过程中以进度通知推送已生成的内容，最后返回完整文本
+    let result = run_generation(generation).await?;
+    Ok(tool_text_content!(result))
+}
+
+/// 未指定 `max_tokens` 时生成的最大token数量
+pub const DEFAULT_MAX_TOKENS: i32 = 1024;
+
+// 校验过参数的一次文本生成
+struct Generation {
+    args: Vec<String>,
+    model: Option<String>,
+    priority: Priority,
+}
+
+// 校验生成参数并准备wei-run参数，GenerateText和SubmitGeneration共用
+fn prepare_generation(
+    prompt: String,
+    model: Option<String>,
+    max_tokens: Option<i32>,
+    priority: Option<String>,
+) -> Result<Generation, WeiRunError> {
+    require_non_empty("prompt", &prompt)?;
+    let priority = parse_priority(priority.as_deref())?;
+
     // 准备参数
-    let mut args = vec!["--prompt", &prompt];
-    
-    // 添加可选参数
-    let model_arg;
-    if let Some(m) = model {
-        model_arg = format!("--model={}", m);
-        args.push(&model_arg);
+    let mut args = vec!["--prompt".to_string(), prompt];
+
+    // 添加可选参数，未指定模型时使用最近加载的llm模型
+    let model = model.or_else(|| models::default_model(ModelType::Llm));
+    if let Some(m) = &model {
+        args.push(format!("--model={}", m));
     }
-    
-    let max_tokens_arg;
-    if let Some(tokens) = max_tokens {
-        max_tokens_arg = format!("--max-tokens={}", tokens);
-        args.push(&max_tokens_arg);
+
+    let tokens = max_tokens.unwrap_or(DEFAULT_MAX_TOKENS);
+    if tokens <= 0 {
+        return Err(WeiRunError::InvalidArguments(format!(
+            "max_tokens must be positive, got {}",
+            tokens
+        )));
     }
-    
-    // 执行命令
-    match run_wei_command("generate", &args).await {
-        Ok(result) => Ok(tool_text_content!(result)),
-        Err(e) => {
-            let error_msg = format!("Failed to generate text: {}", e);
-            Ok(tool_text_content!(error_msg))
-        }
+    args.push(format!("--max-tokens={}", tokens));
+
+    Ok(Generation { args, model, priority })
+}
+
+async fn run_generation(generation: Generation) -> Result<String, WeiRunError> {
+    let args: Vec<&str> = generation.args.iter().map(String::as_str).collect();
+    let result = run_wei_command_streaming("generate", &args, generation.priority).await?;
+    if let Some(m) = &generation.model {
+        models::touch(m);
     }
+    Ok(result)
 }
 
+typed_handler!(GenerateText, GenerateTextParameters, generate_text(prompt, model, max_tokens, priority));
+
 #[tool(
     name = "CreateEmbedding",
-    description = "使用Wei-Assistant-GPU创建文本嵌入向量",
+    description = "使用Wei-Assistant-GPU创建文本嵌入向量，返回JSON：{\"model\", \"dimensions\", \"embedding\"}，批量时为 \"embeddings\"",
     params(
-        text = "要嵌入的文本",
-        model = "要使用的嵌入模型，默认为当前加载的嵌入模型"
+        text = "要嵌入的文本，与texts二选一",
+        texts = "批量嵌入的文本列表，与text二选一",
+        model = "要使用的嵌入模型，默认为当前加载的嵌入模型",
+        dimensions = "期望的向量维度，不一致时返回错误",
+        normalize = "是否对向量做L2归一化，默认为false",
+        priority = "任务优先级：low、normal或high，默认为normal"
     )
 )]
-pub async fn create_embedding(text: String, model: Option<String>) -> Result<ToolResponseContent> {
-    // 准备参数
-    let mut args = vec!["--text", &text];
-    
-    // 添加可选参数
-    let model_arg;
-    if let Some(m) = model {
-        model_arg = format!("--model={}", m);
-        args.push(&model_arg);
-    }
-    
-    // 执行命令
-    match run_wei_command("embed", &args).await {
-        Ok(result) => Ok(tool_text_content!(result)),
-        Err(e) => {
-            let error_msg = format!("Failed to create embedding: {}", e);
-            Ok(tool_text_content!(error_msg))
+pub async fn create_embedding(
+    text: Option<String>,
+    texts: Option<Vec<String>>,
+    model: Option<String>,
+    dimensions: Option<usize>,
+    normalize: Option<bool>,
+    priority: Option<String>,
+) -> Result<ToolResponseContent, WeiRunError> {
+    let embedding = prepare_embedding(text, texts, model, dimensions, normalize, priority)?;
+    Ok(tool_text_content!(run_embedding(embedding).await?))
+}
+
+// 校验过参数的一次嵌入，CreateEmbedding和SubmitJob共用
+struct Embedding {
+    inputs: Vec<String>,
+    batch: bool,
+    model: Option<String>,
+    dimensions: Option<usize>,
+    normalize: bool,
+    priority: Priority,
+}
+
+fn prepare_embedding(
+    text: Option<String>,
+    texts: Option<Vec<String>>,
+    model: Option<String>,
+    dimensions: Option<usize>,
+    normalize: Option<bool>,
+    priority: Option<String>,
+) -> Result<Embedding, WeiRunError> {
+    let (inputs, batch) = match (text, texts) {
+        (Some(text), None) => (vec![text], false),
+        (None, Some(texts)) if !texts.is_empty() => (texts, true),
+        (None, Some(_)) => return Err(WeiRunError::InvalidArguments("texts must not be empty".to_string())),
+        _ => {
+            return Err(WeiRunError::InvalidArguments(
+                "exactly one of text or texts is required".to_string(),
+            ))
         }
+    };
+    for (index, input) in inputs.iter().enumerate() {
+        require_non_empty(&format!("texts[{}]", index), input)?;
+    }
+    if dimensions == Some(0) {
+        return Err(WeiRunError::InvalidArguments("dimensions must be positive".to_string()));
     }
+    let priority = parse_priority(priority.as_deref())?;
+
+    // 未指定模型时使用最近加载的embedding模型
+    let model = model.or_else(|| models::default_model(ModelType::Embedding));
+    Ok(Embedding {
+        inputs,
+        batch,
+        model,
+        dimensions,
+        normalize: normalize.unwrap_or(false),
+        priority,
+    })
 }
 
+// 执行嵌入并返回JSON文本
+async fn run_embedding(embedding: Embedding) -> Result<String, WeiRunError> {
+    let Embedding {
+        inputs,
+        batch,
+        model,
+        dimensions,
+        normalize,
+        priority,
+    } = embedding;
+    let model_arg = model.as_ref().map(|m| format!("--model={}", m));
+
+    // 逐条执行命令，整个批次只占用一个GPU任务名额
+    let commands: Vec<Vec<&str>> = inputs
+        .iter()
+        .map(|input| {
+            let mut args = vec!["--text", input.as_str()];
+            if let Some(model_arg) = &model_arg {
+                args.push(model_arg);
+            }
+            args
+        })
+        .collect();
+    let outputs = run_wei_batch("embed", &commands, priority).await?;
+
+    let mut reported_model = None;
+    let mut vectors = Vec::with_capacity(inputs.len());
+    for output in &outputs {
+        let parsed = embedding::parse_embedding(output).map_err(WeiRunError::InvalidOutput)?;
+        reported_model = reported_model.or(parsed.model);
+        vectors.push(parsed.vector);
+    }
+
+    if let Some(m) = &model {
+        models::touch(m);
+    }
+
+    let dimensions = embedding::check_dimensions(&vectors, dimensions).map_err(WeiRunError::InvalidOutput)?;
+    if normalize {
+        vectors.iter_mut().for_each(|vector| embedding::l2_normalize(vector));
+    }
+
+    // 优先使用请求中的模型名称，其次是wei-run报告的名称
+    let model = model.or(reported_model).unwrap_or_else(|| "default".to_string());
+    let json = if batch {
+        serde_json::to_string(&BatchEmbeddingResult {
+            model,
+            dimensions,
+            embeddings: vectors,
+        })
+    } else {
+        serde_json::to_string(&EmbeddingResult {
+            model,
+            dimensions,
+            embedding: vectors.remove(0),
+        })
+    };
+    Ok(json.unwrap_or_default())
+}
+
+typed_handler!(
+    CreateEmbedding,
+    CreateEmbeddingParameters,
+    create_embedding(
+        text,
+        texts,
+        model,
+        dimensions,
+        normalize,
+        priority
+    )
+);
+
 #[tool(
     name = "LoadModel",
     description = "加载Wei-Assistant-GPU模型",
@@ -222,20 +642,39 @@ pub async fn create_embedding(text: String, model: Option<String>) -> Result<Too
         model_type = "模型类型，如'llm'或'embedding'"
     )
 )]
-pub async fn load_model(model_name: String, model_type: String) -> Result<ToolResponseContent> {
+pub async fn load_model(model_name: String, model_type: String) -> Result<ToolResponseContent, WeiRunError> {
+    let load = prepare_load(model_name, model_type, None)?;
+    Ok(tool_text_content!(run_load(load).await?))
+}
+
+// 校验过参数的一次模型加载，LoadModel和SubmitJob共用
+struct Load {
+    model_name: String,
+    model_type: ModelType,
+    priority: Priority,
+}
+
+fn prepare_load(model_name: String, model_type: String, priority: Option<String>) -> Result<Load, WeiRunError> {
+    require_non_empty("model_name", &model_name)?;
+    Ok(Load {
+        model_type: parse_model_type(&model_type)?,
+        model_name,
+        priority: parse_priority(priority.as_deref())?,
+    })
+}
+
+async fn run_load(load: Load) -> Result<String, WeiRunError> {
     // 准备参数
-    let args = vec!["--name", &model_name, "--type", &model_type];
-    
-    // 执行命令
-    match run_wei_command("load", &args).await {
-        Ok(result) => Ok(tool_text_content!(result)),
-        Err(e) => {
-            let error_msg = format!("Failed to load model: {}", e);
-            Ok(tool_text_content!(error_msg))
-        }
-    }
+    let args = vec!["--name", &load.model_name, "--type", load.model_type.name()];
+
+    // 执行命令，成功后记录到模型状态中
+    let result = run_wei_command("load", &args, load.priority).await?;
+    models::record_load(&load.model_name, load.model_type);
+    Ok(result)
 }
 
+typed_handler!(LoadModel, LoadModelParameters, load_model(model_name, model_type));
+
 #[tool(
     name = "UnloadModel",
     description = "卸载Wei-Assistant-GPU模型",
@@ -244,20 +683,548 @@ pub async fn load_model(model_name: String, model_type: String) -> Result<ToolRe
         model_type = "模型类型，如'llm'或'embedding'"
     )
 )]
-pub async fn unload_model(model_name: String, model_type: String) -> Result<ToolResponseContent> {
+pub async fn unload_model(model_name: String, model_type: String) -> Result<ToolResponseContent, WeiRunError> {
+    require_non_empty("model_name", &model_name)?;
+    let parsed_type = parse_model_type(&model_type)?;
+
     // 准备参数
-    let args = vec!["--name", &model_name, "--type", &model_type];
+    let args = vec!["--name", &model_name, "--type", parsed_type.name()];
     
     // 执行命令
-    match run_wei_command("unload", &args).await {
-        Ok(result) => Ok(tool_text_content!(result)),
-        Err(e) => {
-            let error_msg = format!("Failed to unload model: {}", e);
-            Ok(tool_text_content!(error_msg))
+    let result = run_wei_command("unload", &args, Priority::Normal).await?;
+    models::record_unload(&model_name);
+    Ok(tool_text_content!(result))
+}
+
+typed_handler!(UnloadModel, UnloadModelParameters, unload_model(model_name, model_type));
+
+#[tool(
+    name = "ListModels",
+    description = "列出通过本服务器加载的Wei-Assistant-GPU模型，以及各类型的默认模型",
+    params(model_type = "只列出该类型的模型，'llm'或'embedding'，默认列出全部")
+)]
+pub async fn list_models(model_type: Option<String>) -> Result<ToolResponseContent, WeiRunError> {
+    let model_type = model_type.as_deref().map(parse_model_type).transpose()?;
+    let result = json!({
+        "models": models::list(model_type),
+        "defaults": {
+            "llm": models::default_model(ModelType::Llm),
+            "embedding": models::default_model(ModelType::Embedding),
+        },
+    });
+    Ok(tool_text_content!(result.to_string()))
+}
+
+typed_handler!(ListModels, ListModelsParameters, list_models(model_type));
+
+#[tool(
+    name = "ModelStatus",
+    description = "查询模型是否已通过本服务器加载，以及加载时间、最近使用时间",
+    params(model_name = "要查询的模型名称")
+)]
+pub async fn model_status(model_name: String) -> Result<ToolResponseContent, WeiRunError> {
+    require_non_empty("model_name", &model_name)?;
+    let result = match models::get(&model_name) {
+        Some(model) => {
+            let is_default = models::default_model(model.model_type).as_deref() == Some(model_name.as_str());
+            json!({
+                "name": model.name,
+                "loaded": true,
+                "model_type": model.model_type,
+                "loaded_at": model.loaded_at,
+                "last_used_at": model.last_used_at,
+                "is_default": is_default,
+            })
+        }
+        None => json!({
+            "name": model_name,
+            "loaded": false,
+        }),
+    };
+    Ok(tool_text_content!(result.to_string()))
+}
+
+typed_handler!(ModelStatus, ModelStatusParameters, model_status(model_name));
+
+#[tool(
+    name = "SubmitGeneration",
+    description = "在后台使用Wei-Assistant-GPU生成文本，立即返回任务ID；用JobStatus查询进度，JobResult取得结果，CancelJob取消",
+    params(
+        prompt = "生成文本的提示词",
+        model = "要使用的模型名称，默认为当前加载的模型",
+        max_tokens = "生成的最大token数量，默认为1024",
+        priority = "任务优先级：low、normal或high，默认为normal"
+    )
+)]
+pub async fn submit_generation(
+    prompt: String,
+    model: Option<String>,
+    max_tokens: Option<i32>,
+    priority: Option<String>,
+) -> Result<ToolResponseContent, WeiRunError> {
+    // 参数在提交前校验，错误直接返回而不是变成失败的任务
+    let generation = prepare_generation(prompt, model, max_tokens, priority)?;
+    let info = jobs::submit("generate", run_generation(generation));
+    Ok(tool_text_content!(serde_json::to_string(&info).unwrap_or_default()))
+}
+
+typed_handler!(
+    SubmitGeneration,
+    SubmitGenerationParameters,
+    submit_generation(prompt, model, max_tokens, priority)
+);
+
+#[tool(
+    name = "SubmitJob",
+    description = "在后台执行Wei-Assistant-GPU任务，立即返回任务ID；kind为generate（生成文本）、load（加载模型）或embed（创建嵌入向量），其余参数与GenerateText、LoadModel、CreateEmbedding相同。用JobStatus查询进度，JobResult取得结果，CancelJob取消",
+    params(
+        kind = "任务类型：generate、load或embed",
+        prompt = "generate：生成文本的提示词",
+        model = "generate、embed：要使用的模型名称，默认为当前加载的对应类型的模型",
+        max_tokens = "generate：生成的最大token数量，默认为1024",
+        model_name = "load：要加载的模型名称",
+        model_type = "load：模型类型，如'llm'或'embedding'",
+        text = "embed：要嵌入的文本，与texts二选一",
+        texts = "embed：批量嵌入的文本列表，与text二选一",
+        dimensions = "embed：期望的向量维度，不一致时任务失败",
+        normalize = "embed：是否对向量做L2归一化，默认为false",
+        priority = "任务优先级：low、normal或high，默认为normal"
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn submit_job(
+    kind: String,
+    prompt: Option<String>,
+    model: Option<String>,
+    max_tokens: Option<i32>,
+    model_name: Option<String>,
+    model_type: Option<String>,
+    text: Option<String>,
+    texts: Option<Vec<String>>,
+    dimensions: Option<usize>,
+    normalize: Option<bool>,
+    priority: Option<String>,
+) -> Result<ToolResponseContent, WeiRunError> {
+    let required = |name: &str, value: Option<String>| {
+        value.ok_or_else(|| WeiRunError::InvalidArguments(format!("{} is required for {} jobs", name, kind.trim())))
+    };
+    // 参数在提交前校验，错误直接返回而不是变成失败的任务
+    let info = match kind.trim() {
+        "generate" => {
+            let generation = prepare_generation(required("prompt", prompt)?, model, max_tokens, priority)?;
+            jobs::submit("generate", run_generation(generation))
+        }
+        "load" => {
+            let load = prepare_load(required("model_name", model_name)?, required("model_type", model_type)?, priority)?;
+            jobs::submit("load", run_load(load))
+        }
+        "embed" => {
+            let embedding = prepare_embedding(text, texts, model, dimensions, normalize, priority)?;
+            jobs::submit("embed", run_embedding(embedding))
         }
+        other => {
+            return Err(WeiRunError::InvalidArguments(format!(
+                "unknown job kind {:?}, expected generate, load or embed",
+                other
+            )))
+        }
+    };
+    Ok(tool_text_content!(serde_json::to_string(&info).unwrap_or_default()))
+}
+
+typed_handler!(
+    SubmitJob,
+    SubmitJobParameters,
+    submit_job(
+        kind,
+        prompt,
+        model,
+        max_tokens,
+        model_name,
+        model_type,
+        text,
+        texts,
+        dimensions,
+        normalize,
+        priority
+    )
+);
+
+#[tool(
+    name = "JobStatus",
+    description = "查询后台任务的状态：running、succeeded、failed或cancelled",
+    params(job_id = "SubmitJob或SubmitGeneration返回的任务ID")
+)]
+pub async fn job_status(job_id: String) -> Result<ToolResponseContent, JobError> {
+    require_job_id(&job_id)?;
+    let info = jobs::status(&job_id).ok_or(JobError::NotFound(job_id))?;
+    Ok(tool_text_content!(serde_json::to_string(&info).unwrap_or_default()))
+}
+
+typed_handler!(JobStatus, JobStatusParameters, job_status(job_id));
+
+#[tool(
+    name = "JobResult",
+    description = "取得已完成的后台任务的输出；任务仍在运行时返回job_not_finished错误，失败、超时或取消时返回job_failed错误",
+    params(job_id = "SubmitJob或SubmitGeneration返回的任务ID")
+)]
+pub async fn job_result(job_id: String) -> Result<ToolResponseContent, JobError> {
+    require_job_id(&job_id)?;
+    let (info, output) = jobs::output(&job_id).ok_or_else(|| JobError::NotFound(job_id.clone()))?;
+    match info.status {
+        JobState::Succeeded => Ok(tool_text_content!(output.unwrap_or_default())),
+        JobState::Running => Err(JobError::NotFinished {
+            job_id,
+            status: info.status.name().to_string(),
+        }),
+        JobState::Failed | JobState::Cancelled => Err(JobError::Failed {
+            job_id,
+            error: info.error.unwrap_or_default(),
+        }),
     }
 }
 
+typed_handler!(JobResult, JobResultParameters, job_result(job_id));
+
+#[tool(
+    name = "CancelJob",
+    description = "取消后台任务并终止对应的wei-run进程，返回取消后的任务状态；已结束的任务不受影响",
+    params(job_id = "SubmitJob或SubmitGeneration返回的任务ID")
+)]
+pub async fn cancel_job(job_id: String) -> Result<ToolResponseContent, JobError> {
+    require_job_id(&job_id)?;
+    let info = jobs::cancel(&job_id).await.ok_or(JobError::NotFound(job_id))?;
+    Ok(tool_text_content!(serde_json::to_string(&info).unwrap_or_default()))
+}
+
+typed_handler!(CancelJob, CancelJobParameters, cancel_job(job_id));
+
+#[tool(
+    name = "ListLocalGpus",
+    description = "列出本机的GPU：编号、型号、显存总量和已用显存（MiB）、利用率和驱动版本，数据来自nvidia-smi。本机没有GPU或没有安装nvidia-smi时返回空的清单而不是错误。format为json时返回 {\"count\", \"gpus\": [{\"index\", \"name\", \"memory_total_mib\", \"memory_used_mib\", \"utilization_percent\", \"driver_version\"}]}，不支持的字段为null",
+    params(
+        format = "输出格式：markdown或json，默认为markdown",
+        lang = "markdown输出的语言：zh或en，默认为zh"
+    )
+)]
+pub async fn list_local_gpus(format: Option<String>, lang: Option<String>) -> Result<ToolResponseContent, WeiRunError> {
+    let format = match parse_value(format.as_deref()).map_err(WeiRunError::InvalidArguments)? {
+        OutputFormat::Text => OutputFormat::Markdown,
+        format => format,
+    };
+    let lang: Lang = parse_value(lang.as_deref()).map_err(WeiRunError::InvalidArguments)?;
+    // nvidia-smi只读取状态，不占用GPU任务队列的名额
+    let gpus = local_gpus::list(wei_run::timeout(), &context::cancellation_token()).await?;
+    let output = match format {
+        OutputFormat::Json => local_gpus::to_json(&gpus).to_string(),
+        _ => local_gpus::to_markdown(&gpus, lang),
+    };
+    Ok(tool_text_content!(output))
+}
+
+typed_handler!(ListLocalGpus, ListLocalGpusParameters, list_local_gpus(format, lang));
+
+#[tool(
+    name = "AddSupplier",
+    description = "添加供应商信息，可以传入完整的JSON记录，也可以单独指定各个字段；与已有记录完全相同时拒绝添加。成功时返回保存后的记录（含id）",
+    params(
+        json = "JSON格式的完整供应商记录（对象或字符串），与单独的字段二选一",
+        contact = "联系人，必填",
+        wechat = "微信",
+        phone = "电话",
+        quantity = "数量",
+        location = "地点",
+        price = "价格",
+        bandwidth_price = "带宽价格",
+        storage_price = "存储价格",
+        min_contract_period = "最短合同期",
+        breach_penalties = "违约金",
+        payment_terms = "付款方式",
+        server_name = "服务器名称",
+        server_config = "服务器配置",
+        rental_model = "租赁模式",
+        networking_category = "网络类型"
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn add_supplier(
+    json: Option<Value>,
+    contact: Option<String>,
+    wechat: Option<String>,
+    phone: Option<String>,
+    quantity: Option<i64>,
+    location: Option<String>,
+    price: Option<f64>,
+    bandwidth_price: Option<f64>,
+    storage_price: Option<f64>,
+    min_contract_period: Option<String>,
+    breach_penalties: Option<String>,
+    payment_terms: Option<String>,
+    server_name: Option<String>,
+    server_config: Option<String>,
+    rental_model: Option<String>,
+    networking_category: Option<String>,
+) -> Result<ToolResponseContent, SupplierError> {
+    let fields = Supplier {
+        id: None,
+        contact: contact.unwrap_or_default(),
+        wechat,
+        phone,
+        quantity,
+        location,
+        price,
+        bandwidth_price,
+        storage_price,
+        min_contract_period,
+        breach_penalties,
+        payment_terms,
+        server_name,
+        server_config,
+        rental_model,
+        networking_category,
+    };
+    let supplier = match json {
+        Some(record) => {
+            if fields != Supplier::default() {
+                return Err(SupplierError::InvalidArguments(
+                    "json and individual fields are mutually exclusive".to_string(),
+                ));
+            }
+            parse_supplier_json(record)?
+        }
+        None => fields,
+    };
+
+    let added = suppliers::with_store(move |store| store.add(supplier)).await?;
+    Ok(tool_text_content!(serde_json::to_string(&added).unwrap_or_default()))
+}
+
+// json参数可以是JSON对象，也可以是内容为JSON对象的字符串
+fn parse_supplier_json(record: Value) -> Result<Supplier, SupplierError> {
+    let record = match record {
+        Value::String(text) => serde_json::from_str(&text)
+            .map_err(|e| SupplierError::InvalidArguments(format!("json is not valid JSON: {}", e)))?,
+        other => other,
+    };
+    serde_json::from_value(record)
+        .map_err(|e| SupplierError::InvalidArguments(format!("invalid supplier record: {}", e)))
+}
+
+typed_handler!(
+    AddSupplier,
+    AddSupplierParameters,
+    add_supplier(
+        json,
+        contact,
+        wechat,
+        phone,
+        quantity,
+        location,
+        price,
+        bandwidth_price,
+        storage_price,
+        min_contract_period,
+        breach_penalties,
+        payment_terms,
+        server_name,
+        server_config,
+        rental_model,
+        networking_category
+    )
+);
+
+#[tool(
+    name = "QuerySuppliers",
+    description = "查询供应商信息。filters为条件数组，条件之间为AND，如 [{\"field\": \"price\", \"op\": \"lt\", \"value\": 10000}]；op可选eq、ne、gt、gte、lt、lte、is_null、not_null、like。返回JSON：{\"total\", \"count\", \"suppliers\"}，total为不分页时的总数",
+    params(
+        filters = "过滤条件数组，每项为 {field, op, value}；is_null、not_null不需要value，like的value不含%或_时按包含匹配",
+        order_by = "排序字段，可加asc或desc，多个字段用逗号分隔，如 \"price desc, quantity\"；默认按id排序",
+        limit = "最多返回的记录数",
+        offset = "跳过的记录数，用于分页"
+    )
+)]
+pub async fn query_suppliers(
+    filters: Option<Vec<Filter>>,
+    order_by: Option<String>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+) -> Result<ToolResponseContent, SupplierError> {
+    let query = supplier_query(filters, order_by, limit, offset)?;
+    let (total, found) = suppliers::with_store(move |store| Ok((store.count(&query)?, store.query(&query)?))).await?;
+    let result = json!({
+        "total": total,
+        "count": found.len(),
+        "suppliers": found,
+    });
+    Ok(tool_text_content!(result.to_string()))
+}
+
+// QuerySuppliers和ExportSuppliersCsv共用的查询参数
+fn supplier_query(
+    filters: Option<Vec<Filter>>,
+    order_by: Option<String>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+) -> Result<SupplierQuery, SupplierError> {
+    let mut query = filters
+        .unwrap_or_default()
+        .iter()
+        .try_fold(SupplierQuery::new(), |query, filter| query.filter(filter))?;
+    if let Some(order_by) = &order_by {
+        query = query.order_by(order_by)?;
+    }
+    if let Some(limit) = limit {
+        query = query.limit(limit);
+    }
+    if let Some(offset) = offset {
+        query = query.offset(offset);
+    }
+    Ok(query)
+}
+
+typed_handler!(QuerySuppliers, QuerySuppliersParameters, query_suppliers(filters, order_by, limit, offset));
+
+#[tool(
+    name = "UpdateSupplier",
+    description = "按id修改供应商的部分字段，未指定的字段保持不变。成功时返回修改后的记录",
+    params(
+        id = "供应商id",
+        fields = "要修改的字段（对象或JSON字符串），如 {\"price\": 9000, \"wechat\": null}；值为null时清空该字段"
+    )
+)]
+pub async fn update_supplier(id: i64, fields: Value) -> Result<ToolResponseContent, SupplierError> {
+    let fields = match fields {
+        Value::String(text) => serde_json::from_str(&text)
+            .map_err(|e| SupplierError::InvalidArguments(format!("fields is not valid JSON: {}", e)))?,
+        other => other,
+    };
+    let Value::Object(fields) = fields else {
+        return Err(SupplierError::InvalidArguments("fields must be a JSON object".to_string()));
+    };
+    let updated = suppliers::with_store(move |store| store.update(id, &fields)).await?;
+    Ok(tool_text_content!(serde_json::to_string(&updated).unwrap_or_default()))
+}
+
+typed_handler!(UpdateSupplier, UpdateSupplierParameters, update_supplier(id, fields));
+
+#[tool(
+    name = "DeleteSupplier",
+    description = "删除供应商。删除的记录不再出现在查询结果中，但仍保存在数据库里，可以用restore=true恢复。删除时返回 {\"id\", \"deleted\": true}，恢复时返回恢复后的记录",
+    params(
+        id = "供应商id",
+        restore = "为true时恢复已删除的记录，默认为false"
+    )
+)]
+pub async fn delete_supplier(id: i64, restore: Option<bool>) -> Result<ToolResponseContent, SupplierError> {
+    let result = if restore.unwrap_or(false) {
+        let restored = suppliers::with_store(move |store| store.restore(id)).await?;
+        serde_json::to_value(restored).unwrap_or_default()
+    } else {
+        suppliers::with_store(move |store| store.delete(id)).await?;
+        json!({ "id": id, "deleted": true })
+    };
+    Ok(tool_text_content!(result.to_string()))
+}
+
+typed_handler!(DeleteSupplier, DeleteSupplierParameters, delete_supplier(id, restore));
+
+#[tool(
+    name = "ImportSuppliers",
+    description = "批量导入供应商，所有行在一个事务中处理：任何一行不合法时不导入任何记录。带id且该id存在的行同UpdateSupplier，只修改行中给出的字段（CSV表头中的列或JSON对象中的键，空字段或null清空该字段）；其余行作为新记录添加，与已有记录完全相同的行跳过。返回逐行报告：{\"dry_run\", \"committed\", \"created\", \"updated\", \"unchanged\", \"invalid\", \"rows\": [{\"row\", \"action\", \"id\", \"changed_fields\", \"error\"}]}，changed_fields为修改已有记录时值有变化的字段",
+    params(
+        data = "CSV文本（第一行为列名，列名同AddSupplier的字段）、JSON数组文本或JSON数组",
+        format = "data的格式：csv或json，默认根据内容判断",
+        dry_run = "为true时只报告将要进行的修改，不写入数据库"
+    )
+)]
+pub async fn import_suppliers(
+    data: Value,
+    format: Option<String>,
+    dry_run: Option<bool>,
+) -> Result<ToolResponseContent, SupplierError> {
+    let rows = match (data, format.as_deref().map(str::trim)) {
+        (Value::String(text), Some("csv")) => suppliers::import_rows_from_csv(&text)?,
+        (Value::String(text), Some("json")) => suppliers::import_rows_from_json(parse_import_json(&text)?)?,
+        (Value::String(text), None) if text.trim_start().starts_with('[') => {
+            suppliers::import_rows_from_json(parse_import_json(&text)?)?
+        }
+        (Value::String(text), None) => suppliers::import_rows_from_csv(&text)?,
+        (records @ Value::Array(_), None | Some("json")) => suppliers::import_rows_from_json(records)?,
+        (_, Some(other)) if other != "csv" && other != "json" => {
+            return Err(SupplierError::InvalidArguments(format!(
+                "unknown format {:?}, expected csv or json",
+                other
+            )))
+        }
+        _ => {
+            return Err(SupplierError::InvalidArguments(
+                "data must be CSV text, JSON text or a JSON array".to_string(),
+            ))
+        }
+    };
+    let dry_run = dry_run.unwrap_or(false);
+    let report = suppliers::with_store(move |store| store.import(rows, dry_run)).await?;
+    Ok(tool_text_content!(serde_json::to_string(&report).unwrap_or_default()))
+}
+
+fn parse_import_json(text: &str) -> Result<Value, SupplierError> {
+    serde_json::from_str(text).map_err(|e| SupplierError::InvalidArguments(format!("data is not valid JSON: {}", e)))
+}
+
+typed_handler!(ImportSuppliers, ImportSuppliersParameters, import_suppliers(data, format, dry_run));
+
+#[tool(
+    name = "ExportSuppliersCsv",
+    description = "按与QuerySuppliers相同的条件导出供应商为CSV（RFC 4180，UTF-8，CRLF换行）。指定path时写入服务器导出目录中的文件，返回JSON：{\"rows\", \"path\"}；不指定path时直接返回CSV内容：{\"rows\", \"csv\"}",
+    params(
+        filters = "过滤条件数组，格式同QuerySuppliers",
+        order_by = "排序字段，格式同QuerySuppliers；默认按id排序",
+        limit = "最多导出的记录数",
+        offset = "跳过的记录数",
+        columns = "导出的列及其顺序，如 [\"contact\", \"phone\", \"price\"]；默认导出id和全部列",
+        path = "导出文件相对于导出目录的路径，如 \"2024/h100.csv\"，不能包含..；没有扩展名时补上.csv"
+    )
+)]
+pub async fn export_suppliers_csv(
+    filters: Option<Vec<Filter>>,
+    order_by: Option<String>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+    columns: Option<Vec<String>>,
+    path: Option<String>,
+) -> Result<ToolResponseContent, SupplierError> {
+    let query = supplier_query(filters, order_by, limit, offset)?;
+    let columns = suppliers::export_columns(columns.as_deref())?;
+    let export_dir = suppliers::export_dir();
+
+    let result = suppliers::with_store(move |store| {
+        let found = store.query(&query)?;
+        let csv = suppliers::to_csv(&found, &columns)?;
+        let Some(path) = path else {
+            return Ok(json!({ "rows": found.len(), "csv": csv }));
+        };
+        let path = suppliers::resolve_export_path(&export_dir, &path)?;
+        std::fs::write(&path, csv)?;
+        tracing::info!("导出 {} 条供应商记录到 {}", found.len(), path.display());
+        Ok(json!({ "rows": found.len(), "path": path.display().to_string() }))
+    })
+    .await?;
+    Ok(tool_text_content!(result.to_string()))
+}
+
+typed_handler!(
+    ExportSuppliersCsv,
+    ExportSuppliersCsvParameters,
+    export_suppliers_csv(
+        filters,
+        order_by,
+        limit,
+        offset,
+        columns,
+        path
+    )
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -338,91 +1305,194 @@ mod tests {
     
     #[tokio::test]
     async fn test_query_gpu_specs() {
+        let specs = |model: &str, format: Option<&str>, lang: Option<&str>| {
+            query_gpu_specs(model.to_string(), format.map(str::to_string), lang.map(str::to_string))
+        };
+
         // 测试H100 GPU规格
-        let result = query_gpu_specs("H100".to_string()).await;
+        let result = specs("H100", None, None).await.map_err(Into::into);
         let content = get_text_content(result).await;
         assert!(content.contains("NVIDIA H100 GPU规格"));
         assert!(content.contains("Hopper架构"));
         
         // 测试大小写不敏感
-        let result = query_gpu_specs("h100".to_string()).await;
+        let result = specs("h100", None, None).await.map_err(Into::into);
         let content = get_text_content(result).await;
         assert!(content.contains("NVIDIA H100 GPU规格"));
         
         // 测试A100 GPU规格
-        let result = query_gpu_specs("A100".to_string()).await;
+        let result = specs("A100", None, None).await.map_err(Into::into);
         let content = get_text_content(result).await;
         assert!(content.contains("NVIDIA A100 GPU规格"));
         assert!(content.contains("Ampere架构"));
-        
+
+        // 测试目录中新增的型号
+        let result = specs("RTX 4090", Some("text"), Some("zh")).await.map_err(Into::into);
+        let content = get_text_content(result).await;
+        assert!(content.contains("NVIDIA RTX 4090 GPU规格"));
+        assert!(content.contains("显存：24 GB GDDR6X"));
+
+        // 测试JSON和英文输出
+        let result = specs("B200", Some("JSON"), None).await.map_err(Into::into);
+        let spec: Value = serde_json::from_str(&get_text_content(result).await).unwrap();
+        assert_eq!(spec["architecture"], "Blackwell");
+        assert_eq!(spec["memory_gb"], 180.0);
+        assert!(spec["cuda_cores"].is_null());
+        let result = specs("L40S", Some("markdown"), Some("en")).await.map_err(Into::into);
+        let content = get_text_content(result).await;
+        assert!(content.starts_with("### NVIDIA L40S\n\n| Spec | Value |\n| --- | --- |\n| Architecture | Ada Lovelace |\n| Form factor | PCIe |\n"));
+        assert!(content.contains("| NVLink | not supported |"));
+
         // 测试未知GPU型号
-        let result = query_gpu_specs("未知型号".to_string()).await;
+        match specs("未知型号", None, None).await {
+            Err(GpuSpecError::UnknownModel { model, suggestions, known }) => {
+                assert_eq!(model, "未知型号");
+                assert!(suggestions.is_empty());
+                assert!(known.iter().any(|name| name == "L40S"));
+            }
+            other => panic!("期望unknown_model错误，实际为 {:?}", other.map(|_| ())),
+        }
+
+        // 测试别名和版本匹配
+        let result = specs("英伟达H100 PCIe", None, None).await.map_err(Into::into);
+        let content = get_text_content(result).await;
+        assert!(content.starts_with("NVIDIA H100 PCIe GPU规格"));
+        match specs("H10", None, None).await {
+            Err(GpuSpecError::UnknownModel { suggestions, .. }) => assert_eq!(suggestions[0], "H100"),
+            other => panic!("期望unknown_model错误，实际为 {:?}", other.map(|_| ())),
+        }
+        assert!(matches!(
+            specs("H100", Some("yaml"), None).await,
+            Err(GpuSpecError::InvalidArguments(_))
+        ));
+        assert!(matches!(
+            specs("H100", None, Some("fr")).await,
+            Err(GpuSpecError::InvalidArguments(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_compare_gpus() {
+        let models = vec!["A100".to_string(), "H100".to_string()];
+        let result = compare_gpus(models.clone(), None, None).await.map_err(Into::into);
         let content = get_text_content(result).await;
-        assert!(content.contains("未知的GPU型号"));
+        assert!(content.starts_with("### GPU对比：A100 vs H100\n"));
+        assert!(content.contains("| FP16算力 | 3.17x |"));
+
+        let result = compare_gpus(models, Some("json".to_string()), None).await.map_err(Into::into);
+        let comparison: Value = serde_json::from_str(&get_text_content(result).await).unwrap();
+        assert_eq!(comparison["baseline"], "A100");
+        assert_eq!(comparison["missing"]["A100"], json!(["fp8_tflops"]));
+
+        let result = compare_gpus(vec!["H100".to_string(), "V100".to_string()], None, None).await;
+        assert!(matches!(result, Err(GpuSpecError::UnknownModel { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_estimate_model_fit() {
+        let fit = |models: Option<Vec<String>>, format: Option<&str>| {
+            estimate_model_fit(70.0, None, None, None, None, None, None, None, None, models, format.map(str::to_string), None)
+        };
+        let content = get_text_content(fit(None, None).await.map_err(Into::into)).await;
+        assert!(content.starts_with("### 显存估算：70B参数，fp16，推理\n"));
+        assert!(content.contains("| B200 | 180 GB | 1 | 是 |"));
+
+        let result = fit(Some(vec!["H100".to_string()]), Some("json")).await.map_err(Into::into);
+        let estimate: Value = serde_json::from_str(&get_text_content(result).await).unwrap();
+        assert_eq!(estimate["context_length"], 4096);
+        assert_eq!(estimate["gpus"][0]["gpus_needed"], 2);
+
+        let result = fit(Some(vec!["V100".to_string()]), None).await;
+        assert!(matches!(result, Err(GpuSpecError::UnknownModel { .. })));
     }
 }
 
 #[cfg(test)]
 mod wei_gpu_tests {
     use super::*;
-    use mcp_core::types::ToolResponseContent;
-    use std::path::Path;
     
-    // 辅助函数：从ToolResponseContent提取文本内容
-    async fn get_text_content(response: Result<ToolResponseContent>) -> String {
-        match response {
-            Ok(ToolResponseContent::Text { text }) => text,
-            _ => "Failed to get text content".to_string(),
-        }
+    fn call_request(name: &str, arguments: serde_json::Value) -> CallToolRequest {
+        serde_json::from_value(json!({ "name": name, "arguments": arguments })).unwrap()
     }
     
     #[tokio::test]
     async fn test_wei_run_path_check() {
-        // 此测试检查wei-run路径是否存在，如果不存在则跳过测试
+        // 此测试检查wei-run是否能找到，如果找不到则跳过测试
         // 注意：这是一个集成测试，依赖于实际系统环境
-        let wei_run_path = Path::new("..").join("wei-run");
-        if !wei_run_path.exists() {
-            println!("Skipping test_generate_text as wei-run not found at {:?}", wei_run_path);
+        if let Err(e) = wei_run::locate() {
+            println!("Skipping test_wei_run_path_check: {}", e);
             return;
         }
         
-        // 如果存在wei-run，执行一个简单的命令测试
-        let result = run_wei_command("version", &[]).await;
+        // 如果存在wei-run，执行启动自检
+        let result = wei_run_self_check().await;
         assert!(result.is_ok(), "Wei-run version command failed: {:?}", result);
     }
     
+    #[tokio::test]
+    async fn test_invalid_arguments_are_typed_errors() {
+        // 参数校验在查找wei-run之前进行，不依赖系统环境
+        let cases = [
+            ("GenerateText", json!({ "prompt": "" }), GenerateText::handler()),
+            ("GenerateText", json!({ "prompt": "hi", "max_tokens": 0 }), GenerateText::handler()),
+            ("GenerateText", json!({ "max_tokens": 10 }), GenerateText::handler()),
+            ("GenerateText", json!({ "prompt": "hi", "priority": "urgent" }), GenerateText::handler()),
+            ("CreateEmbedding", json!({ "text": " " }), CreateEmbedding::handler()),
+            ("CreateEmbedding", json!({}), CreateEmbedding::handler()),
+            ("CreateEmbedding", json!({ "text": "a", "texts": ["b"] }), CreateEmbedding::handler()),
+            ("CreateEmbedding", json!({ "texts": [] }), CreateEmbedding::handler()),
+            ("CreateEmbedding", json!({ "text": "a", "dimensions": 0 }), CreateEmbedding::handler()),
+            ("LoadModel", json!({ "model_name": "qwen", "model_type": "" }), LoadModel::handler()),
+            ("UnloadModel", json!({ "model_type": "llm" }), UnloadModel::handler()),
+            ("LoadModel", json!({ "model_name": "qwen", "model_type": "vision" }), LoadModel::handler()),
+            ("ListModels", json!({ "model_type": "vision" }), ListModels::handler()),
+            ("ModelStatus", json!({ "model_name": "" }), ModelStatus::handler()),
+            ("SubmitGeneration", json!({ "prompt": "" }), SubmitGeneration::handler()),
+            ("SubmitGeneration", json!({ "prompt": "hi", "priority": "urgent" }), SubmitGeneration::handler()),
+            ("SubmitJob", json!({ "kind": "train" }), SubmitJob::handler()),
+            ("SubmitJob", json!({ "kind": "generate" }), SubmitJob::handler()),
+            ("SubmitJob", json!({ "kind": "load", "model_name": "qwen" }), SubmitJob::handler()),
+            ("SubmitJob", json!({ "kind": "embed", "texts": [] }), SubmitJob::handler()),
+            ("JobStatus", json!({ "job_id": " " }), JobStatus::handler()),
+            ("QueryGPUSpecs", json!({ "gpu_model": "H100", "format": "yaml" }), QueryGpuSpecs::handler()),
+            ("QueryGPUSpecs", json!({}), QueryGpuSpecs::handler()),
+            ("CompareGPUs", json!({ "gpu_models": ["H100"] }), CompareGpus::handler()),
+            ("CompareGPUs", json!({ "gpu_models": ["H100", "A100"], "lang": "de" }), CompareGpus::handler()),
+            ("EstimateModelFit", json!({ "parameters_b": -1 }), EstimateModelFit::handler()),
+            ("ListLocalGpus", json!({ "format": "yaml" }), ListLocalGpus::handler()),
+            ("EstimateModelFit", json!({ "parameters_b": 7, "precision": "int4", "mode": "training" }), EstimateModelFit::handler()),
+        ];
+        for (name, arguments, handler) in cases {
+            let response = handler(call_request(name, arguments.clone())).await;
+            assert_eq!(response.is_error, Some(true), "{} {}", name, arguments);
+            let meta = response.meta.unwrap();
+            assert_eq!(meta["error"]["type"], "invalid_arguments", "{} {}", name, arguments);
+        }
+    }
+    
     // 注意：以下测试需要Wei-Assistant-GPU环境才能通过
     // 在没有环境的情况下这些测试会被跳过
     
     #[tokio::test]
     async fn test_generate_text() {
-        let wei_run_path = Path::new("..").join("wei-run");
-        if !wei_run_path.exists() {
-            println!("Skipping test_generate_text as wei-run not found");
+        if let Err(e) = wei_run::locate() {
+            println!("Skipping test_generate_text: {}", e);
             return;
         }
         
-        let result = generate_text("Hello, world!".to_string(), None, Some(50)).await;
-        let content = get_text_content(result).await;
-        
-        // 我们不能确定具体的生成内容，但可以检查是否有错误消息
-        assert!(!content.contains("Failed to generate text"), 
-                "Generation failed: {}", content);
+        // 我们不能确定具体的生成内容，但可以检查是否执行成功
+        let result = generate_text("Hello, world!".to_string(), None, Some(50), None).await;
+        assert!(result.is_ok(), "Generation failed: {:?}", result);
     }
     
     #[tokio::test]
     async fn test_create_embedding() {
-        let wei_run_path = Path::new("..").join("wei-run");
-        if !wei_run_path.exists() {
-            println!("Skipping test_create_embedding as wei-run not found");
+        if let Err(e) = wei_run::locate() {
+            println!("Skipping test_create_embedding: {}", e);
             return;
         }
         
-        let result = create_embedding("Test embedding".to_string(), None).await;
-        let content = get_text_content(result).await;
-        
-        // 嵌入向量应该是一串数字
-        assert!(!content.contains("Failed to create embedding"), 
-                "Embedding failed: {}", content);
+        let result = create_embedding(Some("Test embedding".to_string()), None, None, None, None, None).await;
+        assert!(result.is_ok(), "Embedding failed: {:?}", result);
     }
-}
\ No newline at end of file
+}
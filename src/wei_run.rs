@@ -0,0 +1,756 @@
+//! wei-run可执行文件的定位与执行。
+//!
+//! 查找顺序：配置（配置文件、`WEI_MCP_WEI_RUN` 或 `--wei-run`）、`WEI_RUN` 环境变量、
+//! PATH、服务器可执行文件所在目录，最后兼容旧版本的 `../wei-run`。
+//! 显式指定的路径不存在时直接报错，不再继续向后查找。
+//!
+//! 子进程以非阻塞方式执行，超时或请求被取消时连同其子进程一起终止。
+
+use std::env;
+use std::ffi::OsString;
+use std::fmt;
+use std::io::{Error as IoError, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::{Child, Command};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::WeiRunConfig;
+use crate::scheduler::ScheduleError;
+
+/// 指定wei-run路径的环境变量
+pub const WEI_RUN_ENV: &str = "WEI_RUN";
+
+// 启动时设置的wei-run配置
+static SETTINGS: RwLock<Option<WeiRunConfig>> = RwLock::new(None);
+
+/// 设置wei-run的路径和超时时间
+pub fn configure(config: &WeiRunConfig) {
+    if let Ok(mut settings) = SETTINGS.write() {
+        *settings = Some(config.clone());
+    }
+}
+
+fn settings() -> WeiRunConfig {
+    SETTINGS
+        .read()
+        .ok()
+        .and_then(|settings| settings.clone())
+        .unwrap_or_default()
+}
+
+/// 单次调用的超时时间
+pub fn timeout() -> Duration {
+    Duration::from_secs(settings().timeout_secs)
+}
+
+/// 查找或执行wei-run时的错误
+#[derive(Debug, thiserror::Error)]
+pub enum WeiRunError {
+    /// 找不到wei-run可执行文件
+    #[error("{0}")]
+    NotFound(String),
+    /// wei-run以非零状态退出
+    #[error("Wei command failed (exit code {}): {}", exit_code_text(.exit_code), .stderr.trim())]
+    NonZeroExit {
+        exit_code: Option<i32>,
+        stderr: String,
+    },
+    /// 超过了允许的执行时间
+    #[error("Wei command timed out after {}s", .0.as_secs_f64())]
+    Timeout(Duration),
+    /// 客户端取消了请求
+    #[error("Wei command cancelled")]
+    Cancelled,
+    /// GPU任务队列已满，没有执行wei-run
+    #[error("GPU is busy: {queued} jobs already waiting (limit {limit}), try again later")]
+    Busy { queued: usize, limit: usize },
+    /// 工具参数不合法，没有执行wei-run
+    #[error("Invalid arguments: {0}")]
+    InvalidArguments(String),
+    /// wei-run的输出无法解析或不符合要求
+    #[error("Invalid wei-run output: {0}")]
+    InvalidOutput(String),
+    /// 启动进程或读取输出时的其他IO错误
+    #[error("Failed to run wei-run: {0}")]
+    Io(IoError),
+}
+
+fn exit_code_text(code: &Option<i32>) -> String {
+    code.map(|code| code.to_string())
+        .unwrap_or_else(|| "none, killed by signal".to_string())
+}
+
+impl WeiRunError {
+    /// 错误类型的稳定名称，供客户端区分错误
+    pub fn kind(&self) -> &'static str {
+        match self {
+            WeiRunError::NotFound(_) => "not_found",
+            WeiRunError::NonZeroExit { .. } => "non_zero_exit",
+            WeiRunError::Timeout(_) => "timeout",
+            WeiRunError::Cancelled => "cancelled",
+            WeiRunError::Busy { .. } => "busy",
+            WeiRunError::InvalidArguments(_) => "invalid_arguments",
+            WeiRunError::InvalidOutput(_) => "invalid_output",
+            WeiRunError::Io(_) => "io",
+        }
+    }
+
+    /// 结构化的错误信息，放在工具响应的 `_meta.error` 中
+    pub fn to_json(&self) -> Value {
+        let mut error = json!({
+            "type": self.kind(),
+            "message": self.to_string(),
+        });
+        match self {
+            WeiRunError::NonZeroExit { exit_code, stderr } => {
+                error["exitCode"] = json!(exit_code);
+                error["stderr"] = json!(stderr);
+            }
+            WeiRunError::Timeout(timeout) => {
+                error["timeoutSecs"] = json!(timeout.as_secs_f64());
+            }
+            WeiRunError::Busy { queued, limit } => {
+                error["queued"] = json!(queued);
+                error["maxQueueDepth"] = json!(limit);
+            }
+            _ => {}
+        }
+        error
+    }
+}
+
+impl From<IoError> for WeiRunError {
+    fn from(e: IoError) -> Self {
+        match e.kind() {
+            ErrorKind::NotFound => WeiRunError::NotFound(e.to_string()),
+            _ => WeiRunError::Io(e),
+        }
+    }
+}
+
+impl From<ScheduleError> for WeiRunError {
+    fn from(e: ScheduleError) -> Self {
+        match e {
+            ScheduleError::Busy { queued, limit } => WeiRunError::Busy { queued, limit },
+            ScheduleError::Cancelled => WeiRunError::Cancelled,
+        }
+    }
+}
+
+/// wei-run路径的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeiRunSource {
+    Config,
+    Env,
+    Path,
+    ExeDir,
+    Legacy,
+}
+
+impl fmt::Display for WeiRunSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            WeiRunSource::Config => "配置",
+            WeiRunSource::Env => "WEI_RUN环境变量",
+            WeiRunSource::Path => "PATH",
+            WeiRunSource::ExeDir => "服务器程序所在目录",
+            WeiRunSource::Legacy => "上级目录",
+        };
+        f.write_str(name)
+    }
+}
+
+/// 找到的wei-run及其来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeiRunLocation {
+    pub path: PathBuf,
+    pub source: WeiRunSource,
+}
+
+/// 查找wei-run所需的全部输入，便于在测试中替换
+#[derive(Debug, Clone, Default)]
+pub struct WeiRunLocator {
+    pub configured: Option<PathBuf>,
+    pub env: Option<PathBuf>,
+    pub path_var: Option<OsString>,
+    pub exe_dir: Option<PathBuf>,
+    pub legacy: Option<PathBuf>,
+}
+
+impl WeiRunLocator {
+    /// 使用当前进程的配置和环境
+    pub fn from_env() -> Self {
+        WeiRunLocator {
+            configured: settings().path,
+            env: env::var_os(WEI_RUN_ENV)
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from),
+            path_var: env::var_os("PATH"),
+            exe_dir: env::current_exe()
+                .ok()
+                .and_then(|exe| exe.parent().map(Path::to_path_buf)),
+            legacy: Some(Path::new("..").join(executable_name())),
+        }
+    }
+
+    pub fn locate(&self) -> Result<WeiRunLocation, WeiRunError> {
+        // 显式指定的路径必须存在
+        if let Some(path) = &self.configured {
+            return explicit(path, WeiRunSource::Config);
+        }
+        if let Some(path) = &self.env {
+            return explicit(path, WeiRunSource::Env);
+        }
+
+        let name = executable_name();
+        let mut searched = Vec::new();
+
+        if let Some(path_var) = &self.path_var {
+            for dir in env::split_paths(path_var) {
+                let candidate = dir.join(&name);
+                if is_executable(&candidate) {
+                    return Ok(WeiRunLocation {
+                        path: candidate,
+                        source: WeiRunSource::Path,
+                    });
+                }
+            }
+            searched.push("PATH".to_string());
+        }
+
+        let fallbacks = [
+            (self.exe_dir.as_ref().map(|dir| dir.join(&name)), WeiRunSource::ExeDir),
+            (self.legacy.clone(), WeiRunSource::Legacy),
+        ];
+        for (candidate, source) in fallbacks {
+            if let Some(candidate) = candidate {
+                if is_executable(&candidate) {
+                    return Ok(WeiRunLocation {
+                        path: candidate,
+                        source,
+                    });
+                }
+                searched.push(candidate.display().to_string());
+            }
+        }
+
+        Err(WeiRunError::NotFound(format!(
+            "Wei-run executable not found, searched: {}",
+            searched.join(", ")
+        )))
+    }
+}
+
+fn explicit(path: &Path, source: WeiRunSource) -> Result<WeiRunLocation, WeiRunError> {
+    if is_executable(path) {
+        Ok(WeiRunLocation {
+            path: path.to_path_buf(),
+            source,
+        })
+    } else {
+        Err(WeiRunError::NotFound(format!(
+            "Wei-run executable not found at {:?} (from {})",
+            path, source
+        )))
+    }
+}
+
+/// 当前平台上的wei-run文件名
+pub fn executable_name() -> String {
+    format!("wei-run{}", env::consts::EXE_SUFFIX)
+}
+
+#[cfg(unix)]
+pub(crate) fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// 按当前配置和环境查找wei-run
+pub fn locate() -> Result<WeiRunLocation, WeiRunError> {
+    WeiRunLocator::from_env().locate()
+}
+
+/// 执行 `wei-run <command> <args...>` 并返回标准输出。
+///
+/// 超过 `timeout` 时返回 `Timeout`，`cancel` 触发时返回 `Cancelled`，
+/// 两种情况下子进程都会被终止并回收。
+pub async fn run(
+    path: &Path,
+    command: &str,
+    args: &[&str],
+    timeout: Duration,
+    cancel: &CancellationToken,
+) -> Result<String, WeiRunError> {
+    execute(path, command, args, timeout, cancel, None).await
+}
+
+/// 与 [`run`] 相同，并在子进程运行期间把标准输出的新内容逐段发送到 `output`。
+///
+/// 发送的片段总是完整的UTF-8字符，拼接起来与返回的完整输出相同。
+pub async fn run_streaming(
+    path: &Path,
+    command: &str,
+    args: &[&str],
+    timeout: Duration,
+    cancel: &CancellationToken,
+    output: mpsc::UnboundedSender<String>,
+) -> Result<String, WeiRunError> {
+    execute(path, command, args, timeout, cancel, Some(output)).await
+}
+
+async fn execute(
+    path: &Path,
+    command: &str,
+    args: &[&str],
+    timeout: Duration,
+    cancel: &CancellationToken,
+    output: Option<mpsc::UnboundedSender<String>>,
+) -> Result<String, WeiRunError> {
+    let mut cmd = Command::new(path);
+    cmd.arg(command)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    // 放进独立的进程组，终止时可以一并结束wei-run启动的子进程
+    #[cfg(unix)]
+    cmd.process_group(0);
+
+    let mut child = cmd.spawn()?;
+    // 子进程被回收后 `Child::id()` 返回None，先记下进程组ID（与wei-run的pid相同）
+    let pgid = child.id();
+    let stdout = read_to_end(child.stdout.take(), output);
+    let stderr = read_to_end(child.stderr.take(), None);
+
+    let exited = tokio::select! {
+        status = child.wait() => status.map_err(WeiRunError::from),
+        _ = tokio::time::sleep(timeout) => Err(WeiRunError::Timeout(timeout)),
+        _ = cancel.cancelled() => Err(WeiRunError::Cancelled),
+    };
+    let status = match exited {
+        Ok(status) => status,
+        Err(e) => {
+            terminate(&mut child, pgid, &[&stdout, &stderr]).await;
+            return Err(e);
+        }
+    };
+
+    // wei-run已经退出，结束它留在进程组中的后台进程，继承的输出管道随之关闭
+    kill_group(pgid);
+    let (stdout, stderr) = tokio::join!(drain(stdout), drain(stderr));
+
+    // 检查命令是否成功执行
+    if !status.success() {
+        return Err(WeiRunError::NonZeroExit {
+            exit_code: status.code(),
+            stderr: String::from_utf8_lossy(&stderr).to_string(),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&stdout).to_string())
+}
+
+// 在后台读取子进程的输出，提供 `output` 时同时逐段转发
+fn read_to_end<R>(stream: Option<R>, output: Option<mpsc::UnboundedSender<String>>) -> JoinHandle<Vec<u8>>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut buf = Vec::new();
+        let Some(mut stream) = stream else {
+            return buf;
+        };
+        let Some(output) = output else {
+            let _ = stream.read_to_end(&mut buf).await;
+            return buf;
+        };
+
+        let mut decoder = Utf8Decoder::default();
+        let mut chunk = [0u8; 4096];
+        loop {
+            match stream.read(&mut chunk).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    buf.extend_from_slice(&chunk[..n]);
+                    let text = decoder.push(&chunk[..n]);
+                    if !text.is_empty() {
+                        let _ = output.send(text);
+                    }
+                }
+            }
+        }
+        let rest = decoder.finish();
+        if !rest.is_empty() {
+            let _ = output.send(rest);
+        }
+        buf
+    })
+}
+
+// 增量UTF-8解码：多字节字符被拆到两次读取中时，把不完整的部分留到下一次
+#[derive(Default)]
+struct Utf8Decoder {
+    pending: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    fn push(&mut self, bytes: &[u8]) -> String {
+        self.pending.extend_from_slice(bytes);
+        let complete = match std::str::from_utf8(&self.pending) {
+            Ok(_) => self.pending.len(),
+            // 结尾是不完整的字符
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            // 含有非法字节，按lossy方式整体输出
+            Err(_) => self.pending.len(),
+        };
+        let rest = self.pending.split_off(complete);
+        let text = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending = rest;
+        text
+    }
+
+    fn finish(self) -> String {
+        String::from_utf8_lossy(&self.pending).into_owned()
+    }
+}
+
+// wei-run退出后等待读完输出的时间；进程组之外的进程仍可能持有输出管道
+const OUTPUT_GRACE: Duration = Duration::from_millis(500);
+
+// 在宽限时间内读完输出，超过时放弃读取
+async fn drain(mut reader: JoinHandle<Vec<u8>>) -> Vec<u8> {
+    match tokio::time::timeout(OUTPUT_GRACE, &mut reader).await {
+        Ok(output) => output.unwrap_or_default(),
+        Err(_) => {
+            reader.abort();
+            Vec::new()
+        }
+    }
+}
+
+// 向wei-run所在的进程组发送SIGKILL，进程组已经不存在时没有影响
+fn kill_group(pgid: Option<u32>) {
+    #[cfg(unix)]
+    if let Some(pgid) = pgid {
+        // SAFETY: killpg只向该进程组发送信号，不涉及内存访问
+        unsafe {
+            libc::killpg(pgid as libc::pid_t, libc::SIGKILL);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = pgid;
+}
+
+// 终止子进程所在的进程组并回收子进程，停止读取输出
+async fn terminate(child: &mut Child, pgid: Option<u32>, readers: &[&JoinHandle<Vec<u8>>]) {
+    kill_group(pgid);
+    if let Err(e) = child.kill().await {
+        tracing::warn!("无法终止wei-run子进程: {}", e);
+    }
+    // 孙进程可能仍持有输出管道，不再等待读取
+    for reader in readers {
+        reader.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    // 在目录中创建一个可执行的假wei-run
+    fn fake_wei_run(dir: &Path) -> PathBuf {
+        let path = dir.join(executable_name());
+        fs::write(&path, "#!/bin/sh\necho wei-run 1.0\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        path
+    }
+
+    #[test]
+    fn test_search_order() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let env_dir = tempfile::tempdir().unwrap();
+        let path_dir = tempfile::tempdir().unwrap();
+        let exe_dir = tempfile::tempdir().unwrap();
+
+        let mut locator = WeiRunLocator {
+            configured: Some(fake_wei_run(config_dir.path())),
+            env: Some(fake_wei_run(env_dir.path())),
+            path_var: Some(env::join_paths([path_dir.path()]).unwrap()),
+            exe_dir: Some(exe_dir.path().to_path_buf()),
+            legacy: None,
+        };
+        fake_wei_run(path_dir.path());
+        fake_wei_run(exe_dir.path());
+
+        assert_eq!(locator.locate().unwrap().source, WeiRunSource::Config);
+
+        locator.configured = None;
+        assert_eq!(locator.locate().unwrap().source, WeiRunSource::Env);
+
+        locator.env = None;
+        let location = locator.locate().unwrap();
+        assert_eq!(location.source, WeiRunSource::Path);
+        assert_eq!(location.path, path_dir.path().join(executable_name()));
+
+        locator.path_var = None;
+        let location = locator.locate().unwrap();
+        assert_eq!(location.source, WeiRunSource::ExeDir);
+        assert_eq!(location.path, exe_dir.path().join(executable_name()));
+    }
+
+    #[test]
+    fn test_missing_explicit_path_is_an_error() {
+        let exe_dir = tempfile::tempdir().unwrap();
+        fake_wei_run(exe_dir.path());
+
+        // 配置的路径不存在时不会退回到其他位置
+        let locator = WeiRunLocator {
+            configured: Some(PathBuf::from("/nonexistent/wei-run")),
+            exe_dir: Some(exe_dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let err = locator.locate().unwrap_err();
+        assert!(matches!(err, WeiRunError::NotFound(_)));
+        assert!(err.to_string().contains("/nonexistent/wei-run"));
+    }
+
+    #[test]
+    fn test_not_found_lists_searched_locations() {
+        let empty = tempfile::tempdir().unwrap();
+        let locator = WeiRunLocator {
+            path_var: Some(env::join_paths([empty.path()]).unwrap()),
+            exe_dir: Some(empty.path().to_path_buf()),
+            ..Default::default()
+        };
+        let err = locator.locate().unwrap_err();
+        assert!(matches!(err, WeiRunError::NotFound(_)));
+        assert!(err.to_string().contains("PATH"));
+        assert!(err.to_string().contains(&empty.path().display().to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_executable_file_is_skipped() {
+        let path_dir = tempfile::tempdir().unwrap();
+        fs::write(path_dir.path().join(executable_name()), "not a program").unwrap();
+        let locator = WeiRunLocator {
+            path_var: Some(env::join_paths([path_dir.path()]).unwrap()),
+            ..Default::default()
+        };
+        assert!(locator.locate().is_err());
+    }
+
+    // 启动一个后台sleep并一直等待的假wei-run，把自身和子进程的pid写入dir
+    #[cfg(unix)]
+    fn sleeping_wei_run(dir: &Path) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join(executable_name());
+        let script = format!(
+            "#!/bin/sh\necho $$ > {dir}/wei-run.pid\nsleep 30 &\necho $! > {dir}/sleep.pid\nwait\n",
+            dir = dir.display()
+        );
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    // 进程不存在或已成为僵尸进程
+    #[cfg(target_os = "linux")]
+    fn is_dead(pid: &str) -> bool {
+        match fs::read_to_string(format!("/proc/{}/stat", pid.trim())) {
+            Ok(stat) => stat
+                .rsplit(')')
+                .next()
+                .map(|rest| rest.trim_start().starts_with('Z'))
+                .unwrap_or(false),
+            Err(_) => true,
+        }
+    }
+
+    // 等待假wei-run写出pid文件后返回 (wei-run, sleep) 的pid
+    #[cfg(target_os = "linux")]
+    async fn wait_for_pids(dir: &Path) -> (String, String) {
+        for _ in 0..100 {
+            let shell = fs::read_to_string(dir.join("wei-run.pid")).unwrap_or_default();
+            let sleep = fs::read_to_string(dir.join("sleep.pid")).unwrap_or_default();
+            if !shell.trim().is_empty() && !sleep.trim().is_empty() {
+                return (shell, sleep);
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("假wei-run没有启动");
+    }
+
+    #[tokio::test]
+    async fn test_run_returns_stdout() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = fake_wei_run(dir.path());
+        let output = run(&path, "version", &[], Duration::from_secs(5), &CancellationToken::new())
+            .await
+            .unwrap();
+        assert_eq!(output.trim(), "wei-run 1.0");
+    }
+
+    #[test]
+    fn test_utf8_decoder_keeps_split_characters() {
+        let bytes = "生成文本".as_bytes();
+        let mut decoder = Utf8Decoder::default();
+        // 在第一个字符的中间切开
+        assert_eq!(decoder.push(&bytes[..2]), "");
+        assert_eq!(decoder.push(&bytes[2..7]), "生成");
+        assert_eq!(decoder.push(&bytes[7..]), "文本");
+        assert_eq!(decoder.finish(), "");
+
+        let mut decoder = Utf8Decoder::default();
+        assert_eq!(decoder.push(&bytes[..4]), "生");
+        assert_eq!(decoder.finish(), "\u{FFFD}");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_streaming_forwards_output_while_running() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(executable_name());
+        fs::write(&path, "#!/bin/sh\nprintf Hello\nsleep 0.3\nprintf ' world'\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let task = tokio::spawn(async move {
+            run_streaming(&path, "generate", &[], Duration::from_secs(5), &CancellationToken::new(), tx).await
+        });
+
+        // 第一段输出在进程结束前到达
+        let first = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap().unwrap();
+        assert_eq!(first, "Hello");
+        assert!(!task.is_finished());
+
+        let output = task.await.unwrap().unwrap();
+        let mut streamed = first;
+        while let Some(chunk) = rx.recv().await {
+            streamed.push_str(&chunk);
+        }
+        assert_eq!(output, "Hello world");
+        assert_eq!(streamed, output);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_non_zero_exit_reports_code_and_stderr() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(executable_name());
+        fs::write(&path, "#!/bin/sh\necho model not loaded >&2\nexit 3\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let err = run(&path, "generate", &[], Duration::from_secs(5), &CancellationToken::new())
+            .await
+            .unwrap_err();
+        match &err {
+            WeiRunError::NonZeroExit { exit_code, stderr } => {
+                assert_eq!(*exit_code, Some(3));
+                assert_eq!(stderr.trim(), "model not loaded");
+            }
+            other => panic!("期望NonZeroExit，实际为 {:?}", other),
+        }
+        let json = err.to_json();
+        assert_eq!(json["type"], "non_zero_exit");
+        assert_eq!(json["exitCode"], 3);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_timeout_kills_process_group() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = sleeping_wei_run(dir.path());
+
+        let err = run(&path, "generate", &[], Duration::from_millis(500), &CancellationToken::new())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, WeiRunError::Timeout(_)));
+        assert_eq!(err.to_json()["type"], "timeout");
+
+        let (shell, sleep) = wait_for_pids(dir.path()).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(is_dead(&shell), "wei-run进程仍在运行");
+        assert!(is_dead(&sleep), "wei-run启动的子进程仍在运行");
+    }
+
+    // wei-run正常退出时，继承了输出管道的后台进程不影响返回结果，并且会被结束
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_background_children_do_not_hold_output() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(executable_name());
+        let script = format!(
+            "#!/bin/sh\necho loaded\nsleep 30 &\necho $! > {dir}/sleep.pid\nexit 0\n",
+            dir = dir.path().display()
+        );
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let output = tokio::time::timeout(
+            Duration::from_secs(5),
+            run(&path, "load", &[], Duration::from_secs(30), &CancellationToken::new()),
+        )
+        .await
+        .expect("wei-run退出后run没有及时返回")
+        .unwrap();
+        assert_eq!(output, "loaded\n");
+
+        let sleep = fs::read_to_string(dir.path().join("sleep.pid")).unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(is_dead(&sleep), "wei-run启动的后台进程仍在运行");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_cancel_kills_process_group() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = sleeping_wei_run(dir.path());
+        let cancel = CancellationToken::new();
+
+        let task = {
+            let cancel = cancel.clone();
+            tokio::spawn(async move {
+                run(&path, "generate", &[], Duration::from_secs(30), &cancel).await
+            })
+        };
+        let (shell, sleep) = wait_for_pids(dir.path()).await;
+        cancel.cancel();
+
+        let err = tokio::time::timeout(Duration::from_secs(5), task)
+            .await
+            .expect("取消后run没有及时返回")
+            .unwrap()
+            .unwrap_err();
+        assert!(matches!(err, WeiRunError::Cancelled));
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(is_dead(&shell), "wei-run进程仍在运行");
+        assert!(is_dead(&sleep), "wei-run启动的子进程仍在运行");
+    }
+}
@@ -0,0 +1,120 @@
+//! 通过stdio传输取消正在执行的wei-run调用，验证子进程被终止且其他请求不受影响
+#![cfg(target_os = "linux")]
+
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+// 除version外一直sleep的假wei-run，启动后把pid写入dir
+fn sleeping_wei_run(dir: &Path) -> PathBuf {
+    let path = dir.join("wei-run");
+    let script = format!(
+        "#!/bin/sh\n[ \"$1\" = version ] && echo wei-run 1.0 && exit 0\necho $$ > {dir}/wei-run.pid\nsleep 30 &\necho $! > {dir}/sleep.pid\nwait\n",
+        dir = dir.display()
+    );
+    fs::write(&path, script).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+// 进程不存在或已成为僵尸进程
+fn is_dead(pid: &str) -> bool {
+    match fs::read_to_string(format!("/proc/{}/stat", pid.trim())) {
+        Ok(stat) => stat
+            .rsplit(')')
+            .next()
+            .map(|rest| rest.trim_start().starts_with('Z'))
+            .unwrap_or(false),
+        Err(_) => true,
+    }
+}
+
+fn wait_for_pid(file: &Path) -> String {
+    for _ in 0..250 {
+        if let Ok(pid) = fs::read_to_string(file) {
+            if !pid.trim().is_empty() {
+                return pid;
+            }
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    panic!("假wei-run没有启动");
+}
+
+#[test]
+fn test_cancel_notification_kills_wei_run() {
+    let dir = tempfile::tempdir().unwrap();
+    let wei_run = sleeping_wei_run(dir.path());
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rig-mcp-server"))
+        .args(["--transport", "stdio", "--wei-run"])
+        .arg(&wei_run)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    // 在单独的线程中读取响应
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    let (tx, rx) = mpsc::channel::<Value>();
+    let reader = thread::spawn(move || {
+        for line in stdout.lines() {
+            let message = serde_json::from_str(&line.unwrap()).unwrap();
+            if tx.send(message).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut stdin = child.stdin.take().unwrap();
+    let mut send = |message: Value| writeln!(stdin, "{}", message).unwrap();
+    send(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {
+            "protocolVersion": "2024-11-05",
+            "capabilities": {},
+            "clientInfo": { "name": "cancel-test", "version": "0.1.0" }
+        }
+    }));
+    send(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }));
+    send(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "tools/call",
+        "params": { "name": "GenerateText", "arguments": { "prompt": "hello" } }
+    }));
+
+    let shell = wait_for_pid(&dir.path().join("wei-run.pid"));
+    let sleep = wait_for_pid(&dir.path().join("sleep.pid"));
+
+    send(json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/cancelled",
+        "params": { "requestId": 2, "reason": "test" }
+    }));
+    send(json!({ "jsonrpc": "2.0", "id": 3, "method": "ping" }));
+    drop(stdin);
+
+    // stdin关闭后服务器会在所有请求结束后退出，取消的调用不能让它一直等下去
+    let mut ids = Vec::new();
+    while let Ok(message) = rx.recv_timeout(Duration::from_secs(10)) {
+        ids.push(message["id"].as_u64().unwrap());
+    }
+    reader.join().unwrap();
+    assert!(child.wait().unwrap().success());
+
+    ids.sort();
+    assert_eq!(ids, vec![1, 3], "被取消的请求不应当有响应");
+    assert!(is_dead(&shell), "wei-run进程仍在运行");
+    assert!(is_dead(&sleep), "wei-run启动的子进程仍在运行");
+}
@@ -0,0 +1,92 @@
+//! 集成测试共用的辅助函数
+// 每个测试文件只用到其中一部分
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use mcp_core::tools::ToolHandlerFn;
+use mcp_core::types::{CallToolRequest, CallToolResponse, ToolResponseContent};
+use serde_json::{json, Value};
+
+/// 在 `dir` 中以stdio模式启动服务器（`args` 追加在 `--transport stdio` 之后），
+/// 初始化后依次发送请求，返回按id排列的响应，第一个为initialize的响应
+pub fn exchange(dir: &Path, args: &[&str], requests: &[(&str, Value)]) -> Vec<Value> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rig-mcp-server"))
+        .args(["--transport", "stdio"])
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    {
+        let stdin = child.stdin.as_mut().unwrap();
+        let initialize = json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": "2024-11-05",
+                "capabilities": {},
+                "clientInfo": { "name": "integration-test", "version": "0.1.0" }
+            }
+        });
+        writeln!(stdin, "{}", initialize).unwrap();
+        writeln!(stdin, "{}", json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })).unwrap();
+        for (id, (method, params)) in requests.iter().enumerate() {
+            let request = json!({ "jsonrpc": "2.0", "id": id + 1, "method": method, "params": params });
+            writeln!(stdin, "{}", request).unwrap();
+        }
+    }
+    drop(child.stdin.take());
+
+    let mut responses: Vec<Value> = BufReader::new(child.stdout.take().unwrap())
+        .lines()
+        .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+        .collect();
+    assert!(child.wait().unwrap().success());
+    responses.sort_by_key(|response| response["id"].as_u64());
+    assert_eq!(responses.len(), requests.len() + 1);
+    responses
+}
+
+/// 直接调用工具的处理函数
+pub async fn call(handler: ToolHandlerFn, name: &str, arguments: Value) -> CallToolResponse {
+    let arguments: HashMap<String, Value> = serde_json::from_value(arguments).unwrap();
+    handler(CallToolRequest {
+        name: name.to_string(),
+        arguments: Some(arguments),
+        meta: None,
+    })
+    .await
+}
+
+/// 成功响应的文本内容
+pub fn text(response: &CallToolResponse) -> String {
+    assert_eq!(response.is_error, None, "{:?}", response);
+    match &response.content[0] {
+        ToolResponseContent::Text { text } => text.clone(),
+        other => panic!("期望文本内容，实际为 {:?}", other),
+    }
+}
+
+/// 把成功响应的文本内容解析为JSON
+pub fn result_json(response: &CallToolResponse) -> Value {
+    serde_json::from_str(&text(response)).unwrap()
+}
+
+/// 失败响应中 `_meta.error` 的内容
+pub fn error(response: &CallToolResponse) -> Value {
+    assert_eq!(response.is_error, Some(true), "{:?}", response);
+    response.meta.as_ref().unwrap()["error"].clone()
+}
+
+/// 失败响应的错误类型
+pub fn error_type(response: &CallToolResponse) -> Value {
+    error(response)["type"].clone()
+}
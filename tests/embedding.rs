@@ -0,0 +1,86 @@
+//! 使用假wei-run调用CreateEmbedding，验证结构化的嵌入向量输出
+#![cfg(unix)]
+
+mod common;
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::sync::OnceLock;
+
+use common::{error_type, result_json};
+use mcp_core::types::CallToolResponse;
+use rig_mcp_server::config::WeiRunConfig;
+use rig_mcp_server::tools::CreateEmbedding;
+use rig_mcp_server::wei_run;
+use serde_json::{json, Value};
+
+// 文本为 short 时输出二维向量，否则输出带模型名称的三维向量。
+// wei-run配置是全局的，所有测试共用同一个假wei-run
+fn configure_fake_wei_run() {
+    static DIR: OnceLock<tempfile::TempDir> = OnceLock::new();
+    let dir = DIR.get_or_init(|| {
+        let dir = tempfile::tempdir().unwrap();
+        let script = "#!/bin/sh\n\
+            case \"$3\" in\n\
+            short) echo '[1, 2]' ;;\n\
+            garbage) echo 'model not loaded' ;;\n\
+            *) echo '{\"model\": \"fake-embed\", \"embedding\": [3, 4, 0]}' ;;\n\
+            esac\n";
+        let path = dir.path().join("wei-run");
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        dir
+    });
+    wei_run::configure(&WeiRunConfig {
+        path: Some(dir.path().join("wei-run")),
+        ..Default::default()
+    });
+}
+
+async fn call(arguments: Value) -> CallToolResponse {
+    common::call(CreateEmbedding::handler(), "CreateEmbedding", arguments).await
+}
+
+#[tokio::test]
+async fn test_create_embedding_outputs() {
+    configure_fake_wei_run();
+
+    // 单条文本，使用wei-run报告的模型名称
+    let result = result_json(&call(json!({ "text": "hello" })).await);
+    assert_eq!(
+        result,
+        json!({ "model": "fake-embed", "dimensions": 3, "embedding": [3.0, 4.0, 0.0] })
+    );
+
+    // 请求中的模型名称优先，并做L2归一化
+    let result = result_json(&call(json!({ "text": "hello", "model": "bge-m3", "normalize": true })).await);
+    assert_eq!(result["model"], "bge-m3");
+    assert_eq!(result["embedding"], json!([0.6, 0.8, 0.0]));
+
+    // 批量输入按顺序返回
+    let result = result_json(&call(json!({ "texts": ["hello", "world"], "dimensions": 3 })).await);
+    assert_eq!(result["dimensions"], 3);
+    assert_eq!(result["embeddings"].as_array().unwrap().len(), 2);
+    assert!(result.get("embedding").is_none());
+
+    let result = result_json(&call(json!({ "texts": ["short"] })).await);
+    assert_eq!(result["model"], "default");
+    assert_eq!(result["embeddings"], json!([[1.0, 2.0]]));
+}
+
+#[tokio::test]
+async fn test_create_embedding_validates_output() {
+    configure_fake_wei_run();
+
+    // 批量中维度不一致
+    let response = call(json!({ "texts": ["hello", "short"] })).await;
+    assert_eq!(error_type(&response), "invalid_output");
+
+    // 与期望的维度不一致
+    let response = call(json!({ "text": "hello", "dimensions": 1024 })).await;
+    assert_eq!(error_type(&response), "invalid_output");
+
+    // 无法解析的输出
+    let response = call(json!({ "text": "garbage" })).await;
+    assert_eq!(error_type(&response), "invalid_output");
+}
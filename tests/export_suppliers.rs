@@ -0,0 +1,84 @@
+//! 通过ExportSuppliersCsv导出供应商，导出文件只能写入配置的导出目录
+
+mod common;
+
+use common::{call, result_json};
+use mcp_core::types::CallToolResponse;
+use rig_mcp_server::config::SuppliersConfig;
+use rig_mcp_server::suppliers;
+use rig_mcp_server::tools::{AddSupplier, ExportSuppliersCsv};
+use serde_json::{json, Value};
+
+async fn export(arguments: Value) -> CallToolResponse {
+    call(
+        ExportSuppliersCsv::handler(),
+        "ExportSuppliersCsv",
+        arguments,
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_export_suppliers_csv() {
+    let dir = tempfile::tempdir().unwrap();
+    let export_dir = dir.path().join("exports");
+    suppliers::configure(&SuppliersConfig {
+        db_path: dir.path().join("suppliers.db"),
+        export_dir: export_dir.clone(),
+    });
+    for supplier in [
+        json!({ "contact": "张三", "location": "深圳", "price": 12000.5, "server_config": "8 x H100, 2TB" }),
+        json!({ "contact": "李四", "location": "北京", "price": 9000 }),
+        json!({ "contact": "王五", "location": "北京" }),
+    ] {
+        result_json(&call(AddSupplier::handler(), "AddSupplier", supplier).await);
+    }
+
+    // 不指定path时直接返回CSV
+    let inline = result_json(
+        &export(json!({
+            "filters": [{ "field": "price", "op": "not_null" }],
+            "order_by": "price",
+            "columns": ["contact", "price", "server_config"]
+        }))
+        .await,
+    );
+    assert_eq!(inline["rows"], 2);
+    assert_eq!(
+        inline["csv"],
+        "contact,price,server_config\r\n李四,9000,\r\n张三,12000.5,\"8 x H100, 2TB\"\r\n"
+    );
+
+    // 写入导出目录中的文件
+    let written = result_json(
+        &export(json!({
+            "filters": [{ "field": "location", "op": "eq", "value": "北京" }],
+            "path": "beijing/suppliers"
+        }))
+        .await,
+    );
+    assert_eq!(written["rows"], 2);
+    let path = export_dir
+        .canonicalize()
+        .unwrap()
+        .join("beijing")
+        .join("suppliers.csv");
+    assert_eq!(written["path"], path.display().to_string());
+    let csv = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = csv.split_terminator("\r\n").collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("id,contact,wechat,phone,quantity,location,price,"));
+    assert!(lines[1].starts_with("2,李四,,,,北京,9000,"));
+
+    // 导出目录之外的路径被拒绝
+    let outside = dir.path().join("outside.csv");
+    for path in ["../outside.csv", outside.to_str().unwrap()] {
+        let response = export(json!({ "path": path })).await;
+        assert_eq!(response.is_error, Some(true), "{:?}", response);
+        assert_eq!(response.meta.unwrap()["error"]["type"], "path_not_allowed");
+    }
+    assert!(!outside.exists());
+
+    let response = export(json!({ "columns": ["password"] })).await;
+    assert_eq!(response.meta.unwrap()["error"]["type"], "invalid_arguments");
+}
@@ -0,0 +1,33 @@
+-- 生成 suppliers_v1.db 的脚本：版本1的表结构、迁移记录和几条示例数据。
+-- sqlite3 tests/fixtures/suppliers_v1.db < tests/fixtures/suppliers_v1.sql
+CREATE TABLE IF NOT EXISTS suppliers (
+    id INTEGER PRIMARY KEY AUTOINCREMENT, -- 主键，自增
+    contact TEXT NOT NULL,               -- 联系人
+    wechat TEXT,                         -- 微信
+    phone TEXT,                          -- 电话
+    quantity INTEGER,                    -- 数量
+    location TEXT,                       -- 地点
+    price REAL,                          -- 价格
+    bandwidth_price REAL,                -- 带宽价格
+    storage_price REAL,                  -- 存储价格
+    min_contract_period TEXT,            -- 最短合同期
+    breach_penalties TEXT,               -- 违约金
+    payment_terms TEXT,                  -- 付款方式
+    server_name TEXT,                    -- 服务器名称
+    server_config TEXT,                  -- 服务器配置
+    rental_model TEXT,                   -- 租赁模式
+    networking_category TEXT             -- 网络类型
+);
+
+CREATE TABLE IF NOT EXISTS schema_migrations (
+    version INTEGER PRIMARY KEY,    -- 迁移版本
+    description TEXT NOT NULL,      -- 迁移说明
+    applied_at INTEGER NOT NULL     -- 应用时间（Unix时间戳）
+);
+
+INSERT INTO schema_migrations (version, description, applied_at) VALUES (1, '创建suppliers表', 1735689600);
+
+INSERT INTO suppliers (contact, phone, quantity, location, price, server_name)
+VALUES ('张三', '13800000000', 8, '深圳', 12000.5, 'H100 x8');
+INSERT INTO suppliers (contact, wechat, location, price, rental_model)
+VALUES ('李四', 'lisi', '北京', 8000, '按月');
@@ -0,0 +1,108 @@
+//! 使用假wei-run验证后台任务的提交、轮询、取消和结果
+#![cfg(unix)]
+
+mod common;
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::time::{Duration, Instant};
+
+use common::{call, error, text};
+use rig_mcp_server::config::WeiRunConfig;
+use rig_mcp_server::tools::{CancelJob, JobResult, JobStatus, SubmitGeneration, SubmitJob};
+use rig_mcp_server::wei_run;
+use serde_json::{json, Value};
+
+async fn submit(prompt: &str) -> String {
+    let response = call(SubmitGeneration::handler(), "SubmitGeneration", json!({ "prompt": prompt })).await;
+    let info: Value = serde_json::from_str(&text(&response)).unwrap();
+    assert_eq!(info["status"], "running");
+    assert_eq!(info["kind"], "generate");
+    info["job_id"].as_str().unwrap().to_string()
+}
+
+async fn submit_job(arguments: Value) -> String {
+    let kind = arguments["kind"].clone();
+    let response = call(SubmitJob::handler(), "SubmitJob", arguments).await;
+    let info: Value = serde_json::from_str(&text(&response)).unwrap();
+    assert_eq!(info["kind"], kind);
+    info["job_id"].as_str().unwrap().to_string()
+}
+
+// 轮询直到任务结束
+async fn wait_finished(job_id: &str) -> Value {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        let info = status(job_id).await;
+        if info["status"] != "running" {
+            return info;
+        }
+        assert!(Instant::now() < deadline, "任务没有结束: {}", info);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
+
+async fn status(job_id: &str) -> Value {
+    let response = call(JobStatus::handler(), "JobStatus", json!({ "job_id": job_id })).await;
+    serde_json::from_str(&text(&response)).unwrap()
+}
+
+#[tokio::test]
+async fn test_job_lifecycle() {
+    // 提示词为 slow 时长时间运行，否则立即输出；embed输出二维向量
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("wei-run");
+    let script = "#!/bin/sh\n\
+        if [ \"$1\" = embed ]; then echo '[3, 4]'; exit 0; fi\n\
+        if [ \"$3\" = slow ]; then sleep 30; fi\n\
+        echo \"generated: $3\"\n";
+    fs::write(&path, script).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    wei_run::configure(&WeiRunConfig {
+        path: Some(path),
+        ..Default::default()
+    });
+
+    // 成功的任务：轮询直到完成后取得输出
+    let job_id = submit("hello").await;
+    let info = wait_finished(&job_id).await;
+    assert_eq!(info["status"], "succeeded");
+    assert!(info["finished_at"].as_u64().unwrap() >= info["submitted_at"].as_u64().unwrap());
+    assert!(info["queue_wait_ms"].is_u64());
+    let result = call(JobResult::handler(), "JobResult", json!({ "job_id": job_id })).await;
+    assert_eq!(text(&result).trim(), "generated: hello");
+
+    // 运行中的任务没有结果，取消后终止wei-run
+    let job_id = submit("slow").await;
+    let result = call(JobResult::handler(), "JobResult", json!({ "job_id": job_id })).await;
+    let err = error(&result);
+    assert_eq!(err["type"], "job_not_finished");
+    assert_eq!(err["status"], "running");
+
+    let started = Instant::now();
+    let response = call(CancelJob::handler(), "CancelJob", json!({ "job_id": job_id })).await;
+    let info: Value = serde_json::from_str(&text(&response)).unwrap();
+    assert_eq!(info["status"], "cancelled");
+    assert!(started.elapsed() < Duration::from_secs(5));
+
+    let result = call(JobResult::handler(), "JobResult", json!({ "job_id": job_id })).await;
+    let err = error(&result);
+    assert_eq!(err["type"], "job_failed");
+    assert_eq!(err["cause"]["type"], "cancelled");
+
+    // 加载模型和创建嵌入向量也可以在后台执行
+    let job_id = submit_job(json!({ "kind": "load", "model_name": "qwen", "model_type": "llm" })).await;
+    assert_eq!(wait_finished(&job_id).await["status"], "succeeded");
+    let result = call(JobResult::handler(), "JobResult", json!({ "job_id": job_id })).await;
+    assert_eq!(text(&result).trim(), "generated: qwen");
+
+    let job_id = submit_job(json!({ "kind": "embed", "texts": ["a", "b"], "normalize": true })).await;
+    assert_eq!(wait_finished(&job_id).await["status"], "succeeded");
+    let result = call(JobResult::handler(), "JobResult", json!({ "job_id": job_id })).await;
+    let embeddings: Value = serde_json::from_str(&text(&result)).unwrap();
+    assert_eq!(embeddings["embeddings"], json!([[0.6, 0.8], [0.6, 0.8]]));
+
+    // 未知的任务
+    let response = call(JobStatus::handler(), "JobStatus", json!({ "job_id": "missing" })).await;
+    assert_eq!(error(&response)["type"], "job_not_found");
+}
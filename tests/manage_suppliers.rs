@@ -0,0 +1,117 @@
+//! 通过工具处理函数修改、删除、恢复和批量导入供应商
+
+mod common;
+
+use common::{call, error_type, result_json};
+use mcp_core::types::CallToolResponse;
+use rig_mcp_server::config::SuppliersConfig;
+use rig_mcp_server::suppliers;
+use rig_mcp_server::tools::{DeleteSupplier, ImportSuppliers, QuerySuppliers, UpdateSupplier};
+use serde_json::{json, Value};
+
+async fn import(arguments: Value) -> CallToolResponse {
+    call(ImportSuppliers::handler(), "ImportSuppliers", arguments).await
+}
+
+async fn contacts() -> Vec<String> {
+    let result = result_json(&call(QuerySuppliers::handler(), "QuerySuppliers", json!({})).await);
+    result["suppliers"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|supplier| supplier["contact"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_import_update_and_delete_suppliers() {
+    let dir = tempfile::tempdir().unwrap();
+    suppliers::configure(&SuppliersConfig {
+        db_path: dir.path().join("suppliers.db"),
+        export_dir: dir.path().join("exports"),
+    });
+
+    // 从表格导出的CSV：试运行只报告，不写入
+    let csv = "contact,phone,location,price\r\n张三,13800000000,深圳,12000\r\n李四,,北京,\"8,000\"\r\n王五,,北京,9000\r\n";
+    let report = result_json(&import(json!({ "data": csv, "dry_run": true })).await);
+    assert_eq!(report["dry_run"], true);
+    assert_eq!(report["committed"], false);
+    assert_eq!(report["created"], 2);
+    assert_eq!(report["invalid"], 1);
+    assert_eq!(report["rows"][1]["action"], "invalid");
+    assert_eq!(report["rows"][1]["error"]["type"], "invalid_arguments");
+    assert!(contacts().await.is_empty());
+
+    // 有不合法的行时不导入任何记录
+    let report = result_json(&import(json!({ "data": csv })).await);
+    assert_eq!(report["committed"], false);
+    assert!(contacts().await.is_empty());
+
+    let csv = csv.replace("\"8,000\"", "8000");
+    let report = result_json(&import(json!({ "data": csv, "format": "csv" })).await);
+    assert_eq!(report["committed"], true);
+    assert_eq!(report["created"], 3);
+    assert_eq!(contacts().await, ["张三", "李四", "王五"]);
+
+    // 再次导入同样的内容不会重复添加；JSON数组可以直接传入
+    let report = result_json(&import(json!({ "data": csv })).await);
+    assert_eq!(report["unchanged"], 3);
+    let records = json!([{ "id": 1, "contact": "张三", "location": "广州", "price": 11000 }, { "contact": "赵六" }]);
+    let report = result_json(&import(json!({ "data": records.to_string() })).await);
+    assert_eq!((report["updated"].clone(), report["created"].clone()), (json!(1), json!(1)));
+    assert_eq!(report["rows"][0]["changed_fields"], json!(["location", "price"]));
+    // 只修改给出的字段，电话保持不变
+    let found = result_json(&call(QuerySuppliers::handler(), "QuerySuppliers", json!({})).await);
+    assert_eq!(found["suppliers"][0]["phone"], "13800000000");
+    assert_eq!(found["suppliers"][0]["location"], "广州");
+    let report = result_json(&import(json!({ "data": records, "dry_run": true })).await);
+    assert_eq!(report["unchanged"], 2);
+
+    for invalid in [
+        json!({ "data": "contact,password\n张三,x" }),
+        json!({ "data": "{\"contact\": \"张三\"}", "format": "json" }),
+        json!({ "data": "contact\n张三", "format": "xml" }),
+        json!({ "data": 42 }),
+    ] {
+        assert_eq!(error_type(&import(invalid.clone()).await), "invalid_arguments", "{}", invalid);
+    }
+
+    // 修改部分字段
+    let updated = result_json(
+        &call(
+            UpdateSupplier::handler(),
+            "UpdateSupplier",
+            json!({ "id": 2, "fields": { "price": 7500, "wechat": "lisi" } }),
+        )
+        .await,
+    );
+    assert_eq!(updated["price"], 7500.0);
+    assert_eq!(updated["location"], "北京");
+    let updated = result_json(
+        &call(
+            UpdateSupplier::handler(),
+            "UpdateSupplier",
+            json!({ "id": 2, "fields": "{\"wechat\": null}" }),
+        )
+        .await,
+    );
+    assert_eq!(updated["wechat"], Value::Null);
+    let response = call(UpdateSupplier::handler(), "UpdateSupplier", json!({ "id": 99, "fields": { "price": 1 } })).await;
+    assert_eq!(error_type(&response), "not_found");
+    let response = call(UpdateSupplier::handler(), "UpdateSupplier", json!({ "id": 2, "fields": [1] })).await;
+    assert_eq!(error_type(&response), "invalid_arguments");
+
+    // 软删除和恢复
+    let deleted = result_json(&call(DeleteSupplier::handler(), "DeleteSupplier", json!({ "id": 2 })).await);
+    assert_eq!(deleted, json!({ "id": 2, "deleted": true }));
+    assert_eq!(contacts().await, ["张三", "王五", "赵六"]);
+    let response = call(DeleteSupplier::handler(), "DeleteSupplier", json!({ "id": 2 })).await;
+    assert_eq!(error_type(&response), "not_found");
+
+    let restored = result_json(
+        &call(DeleteSupplier::handler(), "DeleteSupplier", json!({ "id": 2, "restore": true })).await,
+    );
+    assert_eq!(restored["contact"], "李四");
+    assert_eq!(restored["price"], 7500.0);
+    assert_eq!(contacts().await, ["张三", "李四", "王五", "赵六"]);
+}
@@ -0,0 +1,133 @@
+//! 供应商数据库迁移：从版本1的数据库升级到最新版本，以及 `migrate` 子命令
+
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+use rig_mcp_server::migrations::MigrationError;
+use rig_mcp_server::suppliers::{self, SupplierError, SupplierQuery, SupplierStore, MIGRATIONS};
+use rusqlite::Connection;
+
+const FIXTURE_V1: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/suppliers_v1.db");
+
+fn server(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rig-mcp-server"))
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn test_upgrade_v1_fixture() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("suppliers.db");
+    std::fs::copy(FIXTURE_V1, &db).unwrap();
+    let latest = MIGRATIONS.last().unwrap().version;
+
+    // --status只报告，不修改数据库
+    let output = server(dir.path(), &["migrate", "--status", "--supplier-db", db.to_str().unwrap()]);
+    assert!(output.status.success(), "{:?}", output);
+    let report = stdout(&output);
+    assert!(report.contains(&format!("当前版本: 1，最新版本: {}", latest)), "{}", report);
+    assert!(report.contains("待应用:\n    2  增加软删除时间deleted_at"), "{}", report);
+    assert_eq!(suppliers::migration_status(&db).unwrap().current, 1);
+
+    let output = server(dir.path(), &["migrate", "--supplier-db", db.to_str().unwrap()]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(&output).contains("已应用迁移: [2]"), "{}", stdout(&output));
+    let status = suppliers::migration_status(&db).unwrap();
+    assert_eq!((status.current, status.latest), (latest, latest));
+    assert!(status.pending.is_empty());
+
+    // 原有数据保留，新的表结构可用
+    let store = SupplierStore::open(&db).unwrap();
+    let loaded = store.query(&SupplierQuery::new()).unwrap();
+    assert_eq!(loaded.len(), 2);
+    assert_eq!(loaded[0].price, Some(12000.5));
+    assert_eq!(loaded[1].rental_model.as_deref(), Some("按月"));
+    store.delete(1).unwrap();
+    assert_eq!(store.count(&SupplierQuery::new()).unwrap(), 1);
+    drop(store);
+
+    let output = server(dir.path(), &["migrate", "--supplier-db", db.to_str().unwrap()]);
+    assert!(stdout(&output).contains("没有待应用的迁移"), "{}", stdout(&output));
+}
+
+#[test]
+fn test_legacy_database_without_metadata() {
+    // 迁移功能加入之前创建的数据库只有suppliers表
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("suppliers.db");
+    std::fs::copy(FIXTURE_V1, &db).unwrap();
+    Connection::open(&db)
+        .unwrap()
+        .execute_batch("DROP TABLE schema_migrations")
+        .unwrap();
+
+    assert_eq!(suppliers::migration_status(&db).unwrap().current, 0);
+    assert_eq!(suppliers::migrate(&db).unwrap(), vec![1, 2]);
+    let store = SupplierStore::open(&db).unwrap();
+    assert_eq!(store.count(&SupplierQuery::new()).unwrap(), 2);
+}
+
+#[test]
+fn test_newer_database_is_refused() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("suppliers.db");
+    std::fs::copy(FIXTURE_V1, &db).unwrap();
+    suppliers::migrate(&db).unwrap();
+    Connection::open(&db)
+        .unwrap()
+        .execute_batch("INSERT INTO schema_migrations VALUES (99, '未来的版本', 0)")
+        .unwrap();
+
+    let error = SupplierStore::open(&db).err().unwrap();
+    assert!(matches!(error, SupplierError::Migration(MigrationError::TooNew { found: 99, .. })));
+    assert_eq!(error.to_json()["type"], "schema_too_new");
+
+    let output = server(dir.path(), &["migrate", "--status", "--supplier-db", db.to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(stdout(&output).contains("数据库版本高于本程序支持的版本"), "{}", stdout(&output));
+
+    // 服务器拒绝启动
+    let output = server(
+        dir.path(),
+        &["--transport", "stdio", "--tools", "suppliers", "--supplier-db", db.to_str().unwrap()],
+    );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("newer than the latest version"), "{}", stderr);
+}
+
+#[test]
+fn test_status_does_not_create_database() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("missing.db");
+    let status = suppliers::migration_status(&db).unwrap();
+    assert_eq!(status.current, 0);
+    assert_eq!(status.pending.len(), MIGRATIONS.len());
+    assert!(!db.exists());
+}
+
+#[test]
+fn test_startup_migrates_existing_database_only() {
+    // 数据库不存在时，在第一次使用供应商工具时才创建
+    let dir = tempfile::tempdir().unwrap();
+    let output = server(dir.path(), &["--transport", "stdio", "--tools", "suppliers"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(!dir.path().join("suppliers.db").exists());
+
+    // 已有的数据库在启动时升级到最新版本
+    let db = dir.path().join("suppliers.db");
+    std::fs::copy(FIXTURE_V1, &db).unwrap();
+    let output = server(dir.path(), &["--transport", "stdio", "--tools", "suppliers"]);
+    assert!(output.status.success(), "{:?}", output);
+    let status = suppliers::migration_status(&db).unwrap();
+    assert_eq!(status.current, MIGRATIONS.last().unwrap().version);
+    assert!(status.pending.is_empty());
+}
@@ -0,0 +1,96 @@
+//! 使用假wei-run验证模型加载状态的记录，以及未指定模型时的默认模型
+#![cfg(unix)]
+
+mod common;
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use mcp_core::tools::ToolHandlerFn;
+use rig_mcp_server::config::WeiRunConfig;
+use rig_mcp_server::tools::{CreateEmbedding, GenerateText, ListModels, LoadModel, ModelStatus, UnloadModel};
+use rig_mcp_server::wei_run;
+use serde_json::{json, Value};
+
+// 把每次调用的参数追加到calls.log的假wei-run
+fn recording_wei_run(dir: &Path) -> PathBuf {
+    let path = dir.join("wei-run");
+    let script = format!(
+        "#!/bin/sh\n\
+         echo \"$@\" >> {dir}/calls.log\n\
+         case \"$1\" in\n\
+         embed) echo '[1, 0]' ;;\n\
+         *) echo ok ;;\n\
+         esac\n",
+        dir = dir.display()
+    );
+    fs::write(&path, script).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+async fn call(handler: ToolHandlerFn, name: &str, arguments: Value) -> Value {
+    let text = common::text(&common::call(handler, name, arguments).await);
+    serde_json::from_str(&text).unwrap_or(json!(text))
+}
+
+fn last_call(dir: &Path) -> String {
+    let log = fs::read_to_string(dir.join("calls.log")).unwrap();
+    log.lines().last().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_model_lifecycle_and_defaults() {
+    let dir = tempfile::tempdir().unwrap();
+    wei_run::configure(&WeiRunConfig {
+        path: Some(recording_wei_run(dir.path())),
+        ..Default::default()
+    });
+
+    // 没有加载模型时不传 --model，由wei-run自行决定
+    call(GenerateText::handler(), "GenerateText", json!({ "prompt": "hi" })).await;
+    assert_eq!(last_call(dir.path()), "generate --prompt hi --max-tokens=1024");
+    call(GenerateText::handler(), "GenerateText", json!({ "prompt": "hi", "max_tokens": 50 })).await;
+    assert_eq!(last_call(dir.path()), "generate --prompt hi --max-tokens=50");
+
+    call(LoadModel::handler(), "LoadModel", json!({ "model_name": "qwen", "model_type": "llm" })).await;
+    call(LoadModel::handler(), "LoadModel", json!({ "model_name": "bge-m3", "model_type": "embedding" })).await;
+
+    call(GenerateText::handler(), "GenerateText", json!({ "prompt": "hi" })).await;
+    assert_eq!(last_call(dir.path()), "generate --prompt hi --model=qwen --max-tokens=1024");
+    let embedding = call(CreateEmbedding::handler(), "CreateEmbedding", json!({ "text": "hi" })).await;
+    assert_eq!(last_call(dir.path()), "embed --text hi --model=bge-m3");
+    assert_eq!(embedding["model"], "bge-m3");
+
+    // 显式指定的模型优先
+    call(GenerateText::handler(), "GenerateText", json!({ "prompt": "hi", "model": "llama" })).await;
+    assert_eq!(last_call(dir.path()), "generate --prompt hi --model=llama --max-tokens=1024");
+
+    let list = call(ListModels::handler(), "ListModels", json!({})).await;
+    let names: Vec<&str> = list["models"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|model| model["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["bge-m3", "qwen"]);
+    assert_eq!(list["defaults"], json!({ "llm": "qwen", "embedding": "bge-m3" }));
+
+    let list = call(ListModels::handler(), "ListModels", json!({ "model_type": "embedding" })).await;
+    assert_eq!(list["models"].as_array().unwrap().len(), 1);
+
+    let status = call(ModelStatus::handler(), "ModelStatus", json!({ "model_name": "qwen" })).await;
+    assert_eq!(status["loaded"], true);
+    assert_eq!(status["model_type"], "llm");
+    assert_eq!(status["is_default"], true);
+    assert!(status["loaded_at"].as_u64().unwrap() > 0);
+    assert!(status["last_used_at"].as_u64().unwrap() >= status["loaded_at"].as_u64().unwrap());
+
+    call(UnloadModel::handler(), "UnloadModel", json!({ "model_name": "qwen", "model_type": "llm" })).await;
+    let status = call(ModelStatus::handler(), "ModelStatus", json!({ "model_name": "qwen" })).await;
+    assert_eq!(status, json!({ "name": "qwen", "loaded": false }));
+
+    call(GenerateText::handler(), "GenerateText", json!({ "prompt": "hi" })).await;
+    assert_eq!(last_call(dir.path()), "generate --prompt hi --max-tokens=1024");
+}
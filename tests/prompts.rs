@@ -0,0 +1,64 @@
+//! 通过stdio传输使用仓库中 `prompts` 目录下的提示词模板
+
+mod common;
+
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+fn exchange(prompts_dir: &Path, requests: &[(&str, Value)]) -> Vec<Value> {
+    let dir = tempfile::tempdir().unwrap();
+    let args = ["--tools", "math", "--prompts-dir", prompts_dir.to_str().unwrap()];
+    common::exchange(dir.path(), &args, requests)
+}
+
+#[test]
+fn test_bundled_prompts() {
+    let prompts_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("prompts");
+    let responses = exchange(
+        &prompts_dir,
+        &[
+            ("prompts/list", json!({})),
+            (
+                "prompts/get",
+                json!({ "name": "compare_gpus", "arguments": { "gpu_a": "H100", "gpu_b": "A100", "workload": "70B模型推理" } }),
+            ),
+            ("prompts/get", json!({ "name": "summarize_supplier", "arguments": { "id": "abc" } })),
+            (
+                "prompts/get",
+                json!({ "name": "compare_gpus", "arguments": { "gpu_a": "L40S", "gpu_b": "RTX 4090", "workload": "7B模型微调" } }),
+            ),
+        ],
+    );
+    assert_eq!(responses[0]["result"]["capabilities"]["prompts"], json!({ "listChanged": false }));
+
+    let names: Vec<&str> = responses[1]["result"]["prompts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|prompt| prompt["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["compare_gpus", "generate_text", "summarize_supplier"]);
+
+    let message = &responses[2]["result"]["messages"][0];
+    assert_eq!(message["role"], "user");
+    let text = message["content"]["text"].as_str().unwrap();
+    assert!(text.contains("H100 和 A100"), "{}", text);
+    assert!(text.contains("70B模型推理"), "{}", text);
+    assert!(text.contains("CompareGPUs"), "{}", text);
+
+    // 参数类型不符
+    assert_eq!(responses[3]["error"]["code"], -32602);
+
+    // GPU型号不限于固定的几个，规格目录中的型号都可以使用
+    let text = responses[4]["result"]["messages"][0]["content"]["text"].as_str().unwrap();
+    assert!(text.contains("[\"L40S\", \"RTX 4090\"]"), "{}", text);
+}
+
+#[test]
+fn test_prompts_disabled_without_templates() {
+    let dir = tempfile::tempdir().unwrap();
+    let responses = exchange(&dir.path().join("missing"), &[("prompts/list", json!({}))]);
+    assert!(responses[0]["result"]["capabilities"].get("prompts").is_none());
+    assert_eq!(responses[1]["error"]["code"], -32601);
+}
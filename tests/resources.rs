@@ -0,0 +1,94 @@
+//! 通过stdio传输读取供应商资源，只有启用suppliers分组时才提供resources能力
+
+mod common;
+
+use std::path::Path;
+
+use rig_mcp_server::suppliers::{Supplier, SupplierStore};
+use serde_json::{json, Value};
+
+fn exchange(dir: &Path, tools: &str, requests: &[(&str, Value)]) -> Vec<Value> {
+    let db = dir.join("suppliers.db");
+    common::exchange(dir, &["--tools", tools, "--supplier-db", db.to_str().unwrap()], requests)
+}
+
+#[test]
+fn test_supplier_resources() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = SupplierStore::open(&dir.path().join("suppliers.db")).unwrap();
+    for (contact, location, price) in [("张三", "深圳", 12000.0), ("李四", "北京", 8000.0), ("王五", "北京", 9500.0)] {
+        store
+            .add(Supplier {
+                contact: contact.to_string(),
+                location: Some(location.to_string()),
+                price: Some(price),
+                server_name: Some("H100 x8".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+    }
+    drop(store);
+
+    let responses = exchange(
+        dir.path(),
+        "suppliers",
+        &[
+            ("resources/list", json!({})),
+            ("resources/templates/list", json!({})),
+            ("resources/read", json!({ "uri": "supplier://2" })),
+            (
+                "resources/read",
+                json!({ "uri": "supplier://query?location=%E5%8C%97%E4%BA%AC&price.gt=9000" }),
+            ),
+            ("resources/read", json!({ "uri": "supplier://99" })),
+            ("resources/read", json!({ "uri": "supplier://query?password=x" })),
+        ],
+    );
+    let capabilities = &responses[0]["result"]["capabilities"];
+    assert_eq!(capabilities["resources"], json!({ "subscribe": false, "listChanged": false }));
+
+    let resources = responses[1]["result"]["resources"].as_array().unwrap();
+    assert_eq!(resources.len(), 3);
+    assert_eq!(
+        resources[0],
+        json!({
+            "uri": "supplier://1",
+            "name": "张三",
+            "description": "H100 x8，深圳",
+            "mimeType": "application/json"
+        })
+    );
+    assert!(responses[1]["result"]["nextCursor"].is_null());
+
+    let templates: Vec<&str> = responses[2]["result"]["resourceTemplates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|template| template["uriTemplate"].as_str().unwrap())
+        .collect();
+    assert_eq!(templates[0], "supplier://{id}");
+    assert!(templates[1].starts_with("supplier://query{?"));
+
+    let contents = &responses[3]["result"]["contents"][0];
+    assert_eq!(contents["uri"], "supplier://2");
+    assert_eq!(contents["mimeType"], "application/json");
+    let supplier: Value = serde_json::from_str(contents["text"].as_str().unwrap()).unwrap();
+    assert_eq!(supplier["contact"], "李四");
+
+    let result: Value = serde_json::from_str(responses[4]["result"]["contents"][0]["text"].as_str().unwrap()).unwrap();
+    assert_eq!(result["total"], 1);
+    assert_eq!(result["suppliers"][0]["contact"], "王五");
+
+    assert_eq!(responses[5]["error"]["code"], -32002);
+    assert_eq!(responses[6]["error"]["code"], -32602);
+}
+
+#[test]
+fn test_resources_disabled_without_suppliers_group() {
+    let dir = tempfile::tempdir().unwrap();
+    let responses = exchange(dir.path(), "math", &[("resources/list", json!({}))]);
+    assert!(responses[0]["result"]["capabilities"].get("resources").is_none());
+    assert_eq!(responses[1]["error"]["code"], -32601);
+    // 未启用suppliers分组时不会打开数据库
+    assert!(!dir.path().join("suppliers.db").exists());
+}
@@ -0,0 +1,73 @@
+//! 使用较慢的假wei-run验证GPU任务排队：并发上限、排队时间和队列已满时的busy错误
+#![cfg(unix)]
+
+mod common;
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+
+use common::call;
+use mcp_core::types::CallToolResponse;
+use rig_mcp_server::config::{SchedulerConfig, WeiRunConfig};
+use rig_mcp_server::tools::{CreateEmbedding, GenerateText};
+use rig_mcp_server::{scheduler, wei_run};
+use serde_json::{json, Value};
+
+async fn generate(arguments: Value) -> CallToolResponse {
+    call(GenerateText::handler(), "GenerateText", arguments).await
+}
+
+async fn embed(arguments: Value) -> CallToolResponse {
+    call(CreateEmbedding::handler(), "CreateEmbedding", arguments).await
+}
+
+fn queue_wait_ms(response: &CallToolResponse) -> u64 {
+    response.meta.as_ref().unwrap()["queueWaitMs"].as_u64().unwrap()
+}
+
+#[tokio::test]
+async fn test_jobs_are_queued_and_rejected_when_busy() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("wei-run");
+    fs::write(&path, "#!/bin/sh\nsleep 0.3\necho '[1, 2]'\n").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    wei_run::configure(&WeiRunConfig {
+        path: Some(path),
+        ..Default::default()
+    });
+    scheduler::configure(&SchedulerConfig {
+        max_concurrent_jobs: 1,
+        max_queue_depth: 1,
+    });
+
+    // 同时只能运行一个任务，第二个任务要等第一个结束
+    let first = tokio::spawn(generate(json!({ "prompt": "first" })));
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    let second = tokio::spawn(generate(json!({ "prompt": "second", "priority": "high" })));
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert_eq!(scheduler::global().queued(), 1);
+
+    // 队列已满，第三个任务立即被拒绝
+    let third = generate(json!({ "prompt": "third" })).await;
+    assert_eq!(third.is_error, Some(true));
+    let meta = third.meta.unwrap();
+    assert_eq!(meta["error"]["type"], "busy");
+    assert_eq!(meta["error"]["maxQueueDepth"], 1);
+    assert!(meta.get("queueWaitMs").is_none());
+
+    let first = first.await.unwrap();
+    let second = second.await.unwrap();
+    assert_eq!(first.is_error, None, "{:?}", first);
+    assert_eq!(second.is_error, None, "{:?}", second);
+    assert!(queue_wait_ms(&first) < 100);
+    assert!(queue_wait_ms(&second) >= 150, "{:?}", second.meta);
+    assert_eq!(scheduler::global().running(), 0);
+
+    // 批量嵌入整个批次只占用一个名额，中途到达的任务要等所有文本处理完
+    let batch = tokio::spawn(embed(json!({ "texts": ["a", "b", "c"] })));
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    let waiting = generate(json!({ "prompt": "after batch" })).await;
+    let batch = batch.await.unwrap();
+    assert_eq!(batch.is_error, None, "{:?}", batch);
+    assert!(queue_wait_ms(&waiting) >= 750, "{:?}", waiting.meta);
+}
@@ -0,0 +1,99 @@
+//! 以子进程方式启动服务器，通过stdin/stdout交换JSON-RPC消息
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+
+use serde_json::{json, Value};
+
+#[test]
+fn test_stdio_transport_lists_tools() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rig-mcp-server"))
+        .args(["--transport", "stdio"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    {
+        let stdin = child.stdin.as_mut().unwrap();
+        let messages = [
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {},
+                    "clientInfo": { "name": "stdio-test", "version": "0.1.0" }
+                }
+            }),
+            json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list", "params": {} }),
+        ];
+        for message in messages {
+            writeln!(stdin, "{}", message).unwrap();
+        }
+    }
+    // 关闭stdin后服务器应当正常退出
+    drop(child.stdin.take());
+
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    let responses: Vec<Value> = stdout
+        .lines()
+        .map(|line| {
+            let line = line.unwrap();
+            // stdout上的每一行都必须是合法的JSON-RPC消息，日志不能混进来
+            serde_json::from_str(&line).unwrap_or_else(|_| panic!("非协议输出: {}", line))
+        })
+        .collect();
+    assert!(child.wait().unwrap().success());
+
+    // 请求并发处理，响应顺序不固定
+    assert_eq!(responses.len(), 2);
+    let response = |id: u64| responses.iter().find(|r| r["id"] == id).unwrap();
+    assert_eq!(response(1)["result"]["serverInfo"]["name"], "wei-server-mcp");
+
+    let tools: Vec<&str> = response(2)["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap())
+        .collect();
+    assert!(tools.contains(&"GenerateText"));
+    assert!(tools.contains(&"Add"));
+}
+
+#[test]
+fn test_stdio_transport_replies_to_malformed_input() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rig-mcp-server"))
+        .args(["--transport", "stdio", "--tools", "math"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    {
+        let stdin = child.stdin.as_mut().unwrap();
+        writeln!(stdin, "{{not json").unwrap();
+        writeln!(stdin, "{}", json!({ "jsonrpc": "2.0", "id": 7, "method": 1 })).unwrap();
+        writeln!(stdin, "{}", json!({ "jsonrpc": "2.0", "id": 8, "method": "ping" })).unwrap();
+    }
+    drop(child.stdin.take());
+
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    let responses: Vec<Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+        .collect();
+    assert!(child.wait().unwrap().success());
+
+    // 无法解析的行也有响应，客户端不会一直等待
+    assert_eq!(responses.len(), 3);
+    let parse_error = responses.iter().find(|r| r["id"].is_null()).unwrap();
+    assert_eq!(parse_error["error"]["code"], -32700);
+    let invalid = responses.iter().find(|r| r["id"] == 7).unwrap();
+    assert_eq!(invalid["error"]["code"], -32600);
+    assert_eq!(responses.iter().find(|r| r["id"] == 8).unwrap()["result"], json!({}));
+}
@@ -0,0 +1,183 @@
+//! 通过SSE传输调用GenerateText，验证生成过程中以进度通知推送输出
+#![cfg(unix)]
+
+use std::fs;
+use std::net::TcpListener;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use rig_mcp_server::config::WeiRunConfig;
+use rig_mcp_server::registry::{ToolGroup, ToolGroups, ToolRegistry};
+use rig_mcp_server::{server, wei_run};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+
+// 每隔一段时间输出一个token的假wei-run
+fn token_wei_run(dir: &Path) -> PathBuf {
+    let path = dir.join("wei-run");
+    let script = "#!/bin/sh\n\
+        [ \"$1\" = version ] && echo wei-run 1.0 && exit 0\n\
+        for token in Hello ' from' ' wei' '-run'; do printf '%s' \"$token\"; sleep 0.2; done\n";
+    fs::write(&path, script).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+// 最简单的SSE客户端：读取事件流中的 data 行
+struct SseClient {
+    port: u16,
+    endpoint: String,
+    lines: Lines<BufReader<OwnedReadHalf>>,
+    // 保持写半部分打开，否则服务器会认为连接已关闭
+    _write: OwnedWriteHalf,
+}
+
+impl SseClient {
+    async fn connect(port: u16) -> Self {
+        let mut stream = None;
+        for _ in 0..50 {
+            if let Ok(s) = TcpStream::connect(("127.0.0.1", port)).await {
+                stream = Some(s);
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        let (read, mut write) = stream.expect("服务器没有启动").into_split();
+        write
+            .write_all(b"GET /sse HTTP/1.1\r\nHost: 127.0.0.1\r\nAccept: text/event-stream\r\n\r\n")
+            .await
+            .unwrap();
+
+        let mut client = SseClient {
+            port,
+            endpoint: String::new(),
+            lines: BufReader::new(read).lines(),
+            _write: write,
+        };
+        client.endpoint = client.next_data().await;
+        client
+    }
+
+    async fn next_data(&mut self) -> String {
+        loop {
+            let line = tokio::time::timeout(Duration::from_secs(10), self.lines.next_line())
+                .await
+                .expect("等待SSE事件超时")
+                .unwrap()
+                .expect("SSE连接已关闭");
+            if let Some(data) = line.strip_prefix("data: ") {
+                return data.to_string();
+            }
+        }
+    }
+
+    async fn next_message(&mut self) -> Value {
+        serde_json::from_str(&self.next_data().await).unwrap()
+    }
+
+    async fn post(&self, message: Value) {
+        let body = message.to_string();
+        let mut stream = TcpStream::connect(("127.0.0.1", self.port)).await.unwrap();
+        let request = format!(
+            "POST {} HTTP/1.1\r\nHost: 127.0.0.1\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.endpoint,
+            body.len(),
+            body
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 202"), "{}", response);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_generate_text_streams_progress_over_sse() {
+    let dir = tempfile::tempdir().unwrap();
+    wei_run::configure(&WeiRunConfig {
+        path: Some(token_wei_run(dir.path())),
+        ..Default::default()
+    });
+
+    let port = free_port();
+    let groups: ToolGroups = [ToolGroup::WeiGpu].into_iter().collect();
+    let router = ToolRegistry::new().build_router("wei-server-mcp", "1.0", &groups);
+    tokio::spawn(async move {
+        let _ = server::sse::serve("127.0.0.1", port, router).await;
+    });
+
+    let mut client = SseClient::connect(port).await;
+    client
+        .post(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": "2024-11-05",
+                "capabilities": {},
+                "clientInfo": { "name": "streaming-test", "version": "0.1.0" }
+            }
+        }))
+        .await;
+    assert_eq!(client.next_message().await["id"], 1);
+    client
+        .post(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+        .await;
+
+    client
+        .post(json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "tools/call",
+            "params": {
+                "name": "GenerateText",
+                "arguments": { "prompt": "hello" },
+                "_meta": { "progressToken": "gen-1" }
+            }
+        }))
+        .await;
+
+    let mut progress = Vec::new();
+    let mut first_progress_at = None;
+    let response = loop {
+        let message = client.next_message().await;
+        if message["method"] == "notifications/progress" {
+            first_progress_at.get_or_insert_with(Instant::now);
+            progress.push(message["params"].clone());
+        } else {
+            break message;
+        }
+    };
+    let response_at = Instant::now();
+
+    assert_eq!(response["id"], 2);
+    assert!(response["result"]["isError"].is_null(), "{}", response);
+    let text = response["result"]["content"][0]["text"].as_str().unwrap();
+    assert_eq!(text, "Hello from wei-run");
+
+    // 输出在进程运行期间逐段到达，而不是结束时一次性返回
+    assert!(progress.len() >= 2, "进度通知太少: {:?}", progress);
+    assert!(response_at - first_progress_at.unwrap() >= Duration::from_millis(300));
+
+    let mut streamed = String::new();
+    let mut last = 0.0;
+    for params in &progress {
+        assert_eq!(params["progressToken"], "gen-1");
+        let value = params["progress"].as_f64().unwrap();
+        assert!(value > last, "进度必须递增");
+        last = value;
+        streamed.push_str(params["message"].as_str().unwrap());
+    }
+    assert_eq!(streamed, text);
+}
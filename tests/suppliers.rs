@@ -0,0 +1,106 @@
+//! 通过工具处理函数添加和查询供应商，数据保存在临时目录的SQLite数据库中
+
+mod common;
+
+use common::{call, error_type, result_json};
+use mcp_core::types::CallToolResponse;
+use rig_mcp_server::config::SuppliersConfig;
+use rig_mcp_server::suppliers;
+use rig_mcp_server::tools::{AddSupplier, QuerySuppliers};
+use serde_json::{json, Value};
+
+async fn add(arguments: Value) -> CallToolResponse {
+    call(AddSupplier::handler(), "AddSupplier", arguments).await
+}
+
+async fn query(arguments: Value) -> Value {
+    result_json(&call(QuerySuppliers::handler(), "QuerySuppliers", arguments).await)
+}
+
+#[tokio::test]
+async fn test_add_and_query_suppliers() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = SuppliersConfig {
+        db_path: dir.path().join("data").join("suppliers.db"),
+        export_dir: dir.path().join("exports"),
+    };
+    suppliers::configure(&config);
+
+    // 单独的字段
+    let added = result_json(
+        &add(json!({
+            "contact": "张三",
+            "phone": "13800000000",
+            "quantity": 8,
+            "location": "深圳",
+            "price": 12000.5,
+            "server_name": "H100 x8"
+        }))
+        .await,
+    );
+    assert_eq!(added["id"], 1);
+    assert_eq!(added["price"], 12000.5);
+    assert_eq!(added["wechat"], Value::Null);
+
+    // JSON对象和JSON字符串
+    let added = result_json(&add(json!({ "json": { "contact": "李四", "location": "北京" } })).await);
+    assert_eq!(added["id"], 2);
+    let record = json!({ "contact": "王五", "location": "北京", "rental_model": "按月" }).to_string();
+    let added = result_json(&add(json!({ "json": record })).await);
+    assert_eq!(added["rental_model"], "按月");
+
+    // 重复和不合法的记录
+    let response = add(json!({ "json": { "contact": "李四", "location": "北京" } })).await;
+    assert_eq!(error_type(&response), "duplicate");
+    assert_eq!(response.meta.unwrap()["error"]["existingId"], 2);
+    for invalid in [
+        json!({ "location": "北京" }),
+        json!({ "contact": "赵六", "quantity": -1 }),
+        json!({ "contact": "赵六", "json": { "contact": "赵六" } }),
+        json!({ "json": "not json" }),
+        json!({ "json": { "contact": "赵六", "unknown": 1 } }),
+        json!({ "contact": "赵六", "price": "cheap" }),
+    ] {
+        let response = add(invalid.clone()).await;
+        assert_eq!(error_type(&response), "invalid_arguments", "{}", invalid);
+    }
+
+    let all = query(json!({})).await;
+    assert_eq!(all["count"], 3);
+    let beijing = query(json!({ "filters": [{ "field": "location", "op": "eq", "value": "北京" }] })).await;
+    let contacts: Vec<&str> = beijing["suppliers"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|supplier| supplier["contact"].as_str().unwrap())
+        .collect();
+    assert_eq!(contacts, vec!["李四", "王五"]);
+    let by_id = query(json!({ "filters": [{ "field": "id", "op": "=", "value": 1 }] })).await;
+    assert_eq!(by_id["suppliers"][0]["server_name"], "H100 x8");
+
+    // 排序和分页，total不受分页影响
+    let page = query(json!({
+        "filters": [{ "field": "price", "op": "is_null" }],
+        "order_by": "contact desc",
+        "limit": 1,
+        "offset": 1
+    }))
+    .await;
+    assert_eq!(page["total"], 2);
+    assert_eq!(page["count"], 1);
+    assert_eq!(page["suppliers"][0]["contact"], "李四");
+
+    for invalid in [
+        json!({ "filters": [{ "field": "password", "op": "eq", "value": "x" }] }),
+        json!({ "filters": [{ "field": "price", "op": "between", "value": 1 }] }),
+        json!({ "filters": [{ "field": "price", "op": "gt", "value": "cheap" }] }),
+        json!({ "order_by": "price; DROP TABLE suppliers" }),
+    ] {
+        let response = call(QuerySuppliers::handler(), "QuerySuppliers", invalid.clone()).await;
+        assert_eq!(error_type(&response), "invalid_arguments", "{}", invalid);
+    }
+
+    // 重新打开数据库后数据仍然存在
+    suppliers::configure(&config);
+    assert_eq!(query(json!({})).await["count"], 3);
+}
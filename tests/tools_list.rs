@@ -0,0 +1,132 @@
+//! 通过真实的SSE传输调用 tools/list，验证注册表暴露的工具集合
+
+use std::collections::BTreeSet;
+use std::net::TcpListener;
+use std::time::Duration;
+
+use mcp_core::client::ClientBuilder;
+use mcp_core::transport::ClientSseTransport;
+use mcp_core::types::{ClientCapabilities, Implementation};
+use rig_mcp_server::registry::{ToolGroup, ToolGroups, ToolRegistry};
+use rig_mcp_server::server;
+
+// 让系统分配一个空闲端口
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+// 在后台启动SSE服务器，并等待端口可连接
+async fn start_server(groups: &ToolGroups) -> u16 {
+    let port = free_port();
+    let router = ToolRegistry::new().build_router("add", "1.0", groups);
+    tokio::spawn(async move {
+        let _ = server::sse::serve("127.0.0.1", port, router).await;
+    });
+
+    for _ in 0..50 {
+        if tokio::net::TcpStream::connect(("127.0.0.1", port)).await.is_ok() {
+            return port;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("服务器未能在端口 {} 上启动", port);
+}
+
+// 作为客户端连接服务器并返回工具名称集合
+async fn list_tool_names(port: u16) -> BTreeSet<String> {
+    let transport = ClientSseTransport::builder(format!("http://127.0.0.1:{}/sse", port)).build();
+    let client = ClientBuilder::new(transport).build();
+    client.open().await.unwrap();
+    client
+        .initialize(
+            Implementation {
+                name: "tools-list-test".to_string(),
+                version: "0.1.0".to_string(),
+            },
+            ClientCapabilities::default(),
+        )
+        .await
+        .unwrap();
+
+    client
+        .list_tools(None, None)
+        .await
+        .unwrap()
+        .tools
+        .into_iter()
+        .map(|tool| tool.name)
+        .collect()
+}
+
+fn names(list: &[&str]) -> BTreeSet<String> {
+    list.iter().map(|name| name.to_string()).collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_tools_list_exposes_all_groups() {
+    let port = start_server(&ToolGroups::all()).await;
+    let tools = list_tool_names(port).await;
+
+    assert_eq!(
+        tools,
+        names(&[
+            "Add",
+            "Sub",
+            "CheckAngel",
+            "QueryAngelType",
+            "QueryGPUSpecs",
+            "CompareGPUs",
+            "EstimateModelFit",
+            "GenerateText",
+            "CreateEmbedding",
+            "LoadModel",
+            "UnloadModel",
+            "ListModels",
+            "ModelStatus",
+            "SubmitGeneration",
+            "SubmitJob",
+            "JobStatus",
+            "JobResult",
+            "CancelJob",
+            "ListLocalGpus",
+            "AddSupplier",
+            "QuerySuppliers",
+            "UpdateSupplier",
+            "DeleteSupplier",
+            "ImportSuppliers",
+            "ExportSuppliersCsv",
+        ])
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_tools_list_respects_disabled_groups() {
+    let groups: ToolGroups = [ToolGroup::GpuSpecs, ToolGroup::WeiGpu].into_iter().collect();
+    let port = start_server(&groups).await;
+    let tools = list_tool_names(port).await;
+
+    assert_eq!(
+        tools,
+        names(&[
+            "QueryGPUSpecs",
+            "CompareGPUs",
+            "EstimateModelFit",
+            "GenerateText",
+            "CreateEmbedding",
+            "LoadModel",
+            "UnloadModel",
+            "ListModels",
+            "ModelStatus",
+            "SubmitGeneration",
+            "SubmitJob",
+            "JobStatus",
+            "JobResult",
+            "CancelJob",
+            "ListLocalGpus",
+        ])
+    );
+}